@@ -0,0 +1,286 @@
+use clap::{Parser, Subcommand};
+use huak::{
+    ops::{
+        self, BuildOptions, CleanOptions, FormatOptions, InstallerOptions, LintOptions,
+        OperationConfig, PublishOptions, TerminalOptions, TestOptions, WorkspaceOptions,
+    },
+    HuakError, HuakResult, OutputFormat, Package, Verbosity,
+};
+use std::{path::PathBuf, str::FromStr};
+
+/// A Python package manager written in Rust inspired by Cargo.
+#[derive(Parser)]
+#[command(version, author, about, arg_required_else_help = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+    /// Suppress all non-error output.
+    #[arg(short, long, global = true)]
+    quiet: bool,
+    /// Resolve and install packages only from the cache.
+    #[arg(long, global = true)]
+    offline: bool,
+    /// Emit newline-delimited JSON events on stdout instead of human-readable output.
+    #[arg(long, global = true)]
+    json: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Activate the project's virtual environment.
+    Activate,
+    /// Add dependencies to the project.
+    Add {
+        #[arg(num_args = 1.., required = true)]
+        dependencies: Vec<String>,
+        /// Adds an optional dependency group.
+        #[arg(long)]
+        group: Option<String>,
+    },
+    /// Build tarball and wheel for the project.
+    Build {
+        /// Pass trailing `KEY=VALUE` config settings with `--` to the build backend.
+        #[arg(last = true)]
+        trailing: Option<Vec<String>>,
+    },
+    /// Remove tarball and wheel from the built project.
+    Clean {
+        /// Remove all __pycache__ directories.
+        #[arg(long, required = false)]
+        include_pycache: bool,
+        /// Remove all .pyc files.
+        #[arg(long, required = false)]
+        include_compiled_bytecode: bool,
+    },
+    /// Auto-fix fixable lint conflicts.
+    Fix {
+        /// Pass trailing arguments with `--` to the linter.
+        #[arg(last = true)]
+        trailing: Option<Vec<String>>,
+    },
+    /// Format the project's Python code.
+    Fmt {
+        /// Check if Python code is formatted.
+        #[arg(long)]
+        check: bool,
+        /// Pass trailing arguments with `--` to the formatter.
+        #[arg(last = true)]
+        trailing: Option<Vec<String>>,
+    },
+    /// Initialize the existing project.
+    Init {
+        /// Don't initialize VCS in the project.
+        #[arg(long)]
+        no_vcs: bool,
+    },
+    /// Install the dependencies of an existing project.
+    Install {
+        /// Install optional dependency groups.
+        #[arg(long, num_args = 1..)]
+        groups: Option<Vec<String>>,
+        /// Require every package to be locked with a hash to verify it with.
+        #[arg(long)]
+        require_hashes: bool,
+    },
+    /// Lint the project's Python code.
+    Lint {
+        /// Auto-fix fixable lint conflicts.
+        #[arg(long, required = false)]
+        fix: bool,
+        /// Pass trailing arguments with `--` to the linter.
+        #[arg(last = true)]
+        trailing: Option<Vec<String>>,
+    },
+    /// Lock the project's dependencies.
+    Lock,
+    /// Create a new project at <path>.
+    New {
+        /// Use an application template.
+        #[arg(long, conflicts_with = "lib")]
+        app: bool,
+        /// Use a library template [default].
+        #[arg(long, conflicts_with = "app")]
+        lib: bool,
+        /// Path and name of the Python package.
+        path: String,
+        /// Don't initialize VCS in the new project.
+        #[arg(long)]
+        no_vcs: bool,
+    },
+    /// Builds and uploads current project to a registry.
+    Publish {
+        /// Pass trailing arguments with `--` to the publishing tool.
+        #[arg(last = true)]
+        trailing: Option<Vec<String>>,
+    },
+    /// Remove dependencies from the project.
+    Remove {
+        #[arg(num_args = 1.., required = true)]
+        dependencies: Vec<String>,
+        /// Remove from an optional dependency group.
+        #[arg(long)]
+        group: Option<String>,
+    },
+    /// Run a command within the project's environment context.
+    Run {
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        command: Vec<String>,
+    },
+    /// Test the project's Python code.
+    Test {
+        /// Pass trailing arguments with `--` to the test runner.
+        #[arg(last = true)]
+        trailing: Option<Vec<String>>,
+    },
+    /// Display the version of the project.
+    Version,
+}
+
+impl Cli {
+    /// Get the format the command's output is written in.
+    pub fn output_format(&self) -> OutputFormat {
+        if self.json {
+            OutputFormat::Json
+        } else {
+            OutputFormat::Human
+        }
+    }
+
+    /// Run the command with a configuration built from its flags.
+    pub fn run(self) -> HuakResult<()> {
+        let format = self.output_format();
+        let mut config = OperationConfig::new();
+        config
+            .with_root(std::env::current_dir()?)
+            .with_terminal_options(TerminalOptions {
+                verbosity: if self.quiet {
+                    Verbosity::Quiet
+                } else {
+                    Verbosity::Normal
+                },
+                format,
+            })
+            .with_installer_options(InstallerOptions {
+                offline: self.offline,
+                require_hashes: false,
+            });
+        match self.command {
+            Commands::Activate => ops::activate_venv(&config),
+            Commands::Add {
+                dependencies,
+                group,
+            } => {
+                let packages = parse_packages(&dependencies)?;
+                match group.as_ref() {
+                    Some(it) => ops::add_project_optional_dependencies(&config, &packages, it),
+                    None => ops::add_project_dependencies(&config, &packages),
+                }
+            }
+            Commands::Build { trailing } => {
+                config.with_build_options(BuildOptions { args: trailing });
+                ops::build_project(&config)
+            }
+            Commands::Clean {
+                include_pycache,
+                include_compiled_bytecode,
+            } => {
+                config.with_clean_options(CleanOptions {
+                    include_pycache,
+                    include_compiled_bytecode,
+                });
+                ops::clean_project(&config)
+            }
+            Commands::Fix { trailing } => {
+                config.with_lint_options(LintOptions {
+                    args: trailing,
+                    fix: true,
+                });
+                ops::lint_project(&config)
+            }
+            Commands::Lock => ops::lock_project(&config),
+            Commands::Fmt { check, trailing } => {
+                let mut args = trailing.unwrap_or_default();
+                if check {
+                    args.push("--check".to_string());
+                }
+                config.with_format_options(FormatOptions { args: Some(args) });
+                ops::format_project(&config)
+            }
+            Commands::Init { no_vcs } => {
+                config.with_workspace_options(WorkspaceOptions { uses_git: !no_vcs });
+                ops::init_project(&config)
+            }
+            Commands::Install {
+                groups,
+                require_hashes,
+            } => {
+                config.with_installer_options(InstallerOptions {
+                    offline: self.offline,
+                    require_hashes,
+                });
+                ops::install_project_dependencies(&config)?;
+                for group in groups.unwrap_or_default() {
+                    ops::install_project_optional_dependencies(&config, &group)?;
+                }
+                Ok(())
+            }
+            Commands::Lint { fix, trailing } => {
+                config.with_lint_options(LintOptions {
+                    args: trailing,
+                    fix,
+                });
+                ops::lint_project(&config)
+            }
+            Commands::New {
+                app,
+                lib: _,
+                path,
+                no_vcs,
+            } => {
+                let root = PathBuf::from(path);
+                let root = match root.is_absolute() {
+                    true => root,
+                    false => config.root().join(root),
+                };
+                config
+                    .with_root(root)
+                    .with_workspace_options(WorkspaceOptions { uses_git: !no_vcs });
+                if app {
+                    ops::create_new_app_project(&config)
+                } else {
+                    ops::create_new_lib_project(&config)
+                }
+            }
+            Commands::Publish { trailing } => {
+                config.with_publish_options(PublishOptions { args: trailing });
+                ops::publish_project(&config)
+            }
+            Commands::Remove {
+                dependencies,
+                group,
+            } => {
+                let names = dependencies
+                    .iter()
+                    .map(|item| item.as_str())
+                    .collect::<Vec<_>>();
+                match group.as_ref() {
+                    Some(it) => ops::remove_project_optional_dependencies(&config, &names, it),
+                    None => ops::remove_project_dependencies(&config, &names),
+                }
+            }
+            Commands::Run { command } => ops::run_command_with_context(&config, &command),
+            Commands::Test { trailing } => {
+                config.with_test_options(TestOptions { args: trailing });
+                ops::test_project(&config)
+            }
+            Commands::Version => ops::display_project_version(&config),
+        }
+    }
+}
+
+fn parse_packages(dependencies: &[String]) -> HuakResult<Vec<Package>> {
+    dependencies
+        .iter()
+        .map(|item| Package::from_str(item))
+        .collect::<Result<Vec<_>, HuakError>>()
+}
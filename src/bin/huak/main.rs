@@ -1 +1,34 @@
-fn main() {}
+use clap::Parser;
+use cli::Cli;
+use huak::{HuakError, Terminal};
+use std::process::ExitCode;
+
+mod cli;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let format = cli.output_format();
+    match cli.run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            let mut terminal = Terminal::new();
+            terminal.set_output_format(format);
+            terminal.print_error(&e).ok();
+            exit_code(&e)
+        }
+    }
+}
+
+/// Map an error to the exit code the process should exit with. Usage and
+/// configuration errors exit with 2 (like clap does), commands huak runs exit with
+/// their own code, and everything else exits with 1.
+fn exit_code(error: &HuakError) -> ExitCode {
+    match error {
+        HuakError::ClapError(_) | HuakError::HuakConfigurationError(_) => ExitCode::from(2),
+        HuakError::CommandExitError(_, code) => match u8::try_from(*code) {
+            Ok(code) if code != 0 => ExitCode::from(code),
+            _ => ExitCode::FAILURE,
+        },
+        _ => ExitCode::FAILURE,
+    }
+}
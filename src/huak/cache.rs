@@ -0,0 +1,407 @@
+///! This module implements huak's on-disk cache. The cache is shared by every project
+///! on the system and keeps:
+///!
+///! - HTTP responses from package indexes with the validators (`ETag` and
+///!   `Last-Modified`) and `Cache-Control` directives they were served with. Pages
+///!   an index doesn't have are remembered too so that offline searches know to skip
+///!   the index.
+///! - Downloaded artifacts such as wheels, addressed by their sha256 digest.
+///!   Artifacts served without a sha256 hash are found by the digest they had when
+///!   they were downloaded from their URL.
+///! - Core metadata extracted from artifacts.
+///! - The versions and implementations of Python interpreters found on the system.
+///!
+///! The cache lives in `HUAK_CACHE_DIR` if it's set, or in the platform's cache
+///! directory otherwise. Entries are written to a temporary file and renamed into
+///! place so that concurrent huak processes never read partial entries.
+use crate::error::HuakResult;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The environment variable overriding the cache directory.
+const CACHE_DIR_ENV_VAR: &str = "HUAK_CACHE_DIR";
+
+/// The on-disk cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cache {
+    /// The root directory of the cache.
+    root: PathBuf,
+}
+
+impl Cache {
+    /// Use the cache in `HUAK_CACHE_DIR` or the platform's cache directory.
+    pub fn new() -> Cache {
+        Cache {
+            root: default_cache_dir(),
+        }
+    }
+
+    /// Use a cache rooted at a directory.
+    pub fn from_path(path: impl AsRef<Path>) -> Cache {
+        Cache {
+            root: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Get the root directory of the cache.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Get the cached response for a URL.
+    pub(crate) fn http_response(&self, url: &str) -> HuakResult<Option<CachedResponse>> {
+        let path = self.http_path(url);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let response: CachedResponse = serde_json::from_slice(&std::fs::read(path)?)?;
+        Ok(Some(response).filter(|it| it.url == url))
+    }
+
+    /// Store the response for a URL unless it forbids storing.
+    pub(crate) fn store_http_response(&self, response: &CachedResponse) -> HuakResult<()> {
+        if response.control.no_store {
+            return Ok(());
+        }
+        write_atomic(
+            &self.http_path(&response.url),
+            &serde_json::to_vec(response)?,
+        )
+    }
+
+    /// Get the path to a cached artifact with a sha256 digest.
+    pub(crate) fn artifact_path(&self, sha256: &str) -> Option<PathBuf> {
+        Some(self.blob_path(sha256)).filter(|it| it.exists())
+    }
+
+    /// Store an artifact file by its sha256 digest and get its path. The file is
+    /// linked into the cache if it can be rather than copied.
+    pub(crate) fn store_artifact(&self, file_path: &Path, sha256: &str) -> HuakResult<PathBuf> {
+        let path = self.blob_path(sha256);
+        if path.exists() {
+            return Ok(path);
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let temp_path = path.with_file_name(format!(".{file_name}.{}.tmp", std::process::id()));
+        let _ = std::fs::remove_file(&temp_path);
+        crate::fs::link_or_copy_file(file_path, &temp_path)?;
+        std::fs::rename(&temp_path, &path)?;
+        Ok(path)
+    }
+
+    /// Get the sha256 digest of the artifact downloaded from a URL.
+    pub(crate) fn artifact_sha256(&self, url: &str) -> HuakResult<Option<String>> {
+        let path = self.artifact_url_path(url);
+        match path.exists() {
+            true => Ok(Some(std::fs::read_to_string(path)?.trim().to_string())),
+            false => Ok(None),
+        }
+    }
+
+    /// Store the sha256 digest of the artifact downloaded from a URL.
+    pub(crate) fn store_artifact_sha256(&self, url: &str, sha256: &str) -> HuakResult<()> {
+        write_atomic(&self.artifact_url_path(url), sha256.as_bytes())
+    }
+
+    /// Get the core metadata extracted from an artifact.
+    pub(crate) fn metadata(&self, key: &str) -> HuakResult<Option<String>> {
+        let path = self.metadata_path(key);
+        match path.exists() {
+            true => Ok(Some(std::fs::read_to_string(path)?)),
+            false => Ok(None),
+        }
+    }
+
+    /// Store the core metadata extracted from an artifact.
+    pub(crate) fn store_metadata(&self, key: &str, contents: &str) -> HuakResult<()> {
+        write_atomic(&self.metadata_path(key), contents.as_bytes())
+    }
+
+    /// Get the cached query of a Python interpreter.
+    pub(crate) fn interpreter(&self, key: &str) -> HuakResult<Option<String>> {
+        let path = self.interpreter_path(key);
+        match path.exists() {
+            true => Ok(Some(std::fs::read_to_string(path)?)),
+            false => Ok(None),
+        }
+    }
+
+    /// Store the query of a Python interpreter.
+    pub(crate) fn store_interpreter(&self, key: &str, contents: &str) -> HuakResult<()> {
+        write_atomic(&self.interpreter_path(key), contents.as_bytes())
+    }
+
+    fn http_path(&self, url: &str) -> PathBuf {
+        let digest = hex_digest(url.as_bytes());
+        self.root
+            .join("http-v2")
+            .join(&digest[..2])
+            .join(format!("{digest}.json"))
+    }
+
+    fn blob_path(&self, sha256: &str) -> PathBuf {
+        let sha256 = sha256.to_lowercase();
+        self.root
+            .join("artifacts-v1")
+            .join("sha256")
+            .join(&sha256[..2.min(sha256.len())])
+            .join(&sha256)
+    }
+
+    fn artifact_url_path(&self, url: &str) -> PathBuf {
+        let digest = hex_digest(url.as_bytes());
+        self.root
+            .join("artifacts-v1")
+            .join("urls")
+            .join(&digest[..2])
+            .join(digest)
+    }
+
+    fn metadata_path(&self, key: &str) -> PathBuf {
+        let digest = hex_digest(key.as_bytes());
+        self.root
+            .join("metadata-v1")
+            .join(&digest[..2])
+            .join(format!("{digest}.METADATA"))
+    }
+
+    fn interpreter_path(&self, key: &str) -> PathBuf {
+        let digest = hex_digest(key.as_bytes());
+        self.root
+            .join("interpreters-v1")
+            .join(&digest[..2])
+            .join(format!("{digest}.json"))
+    }
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Cache::new()
+    }
+}
+
+/// A response stored in the cache.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CachedResponse {
+    /// The URL the response was requested from.
+    pub(crate) url: String,
+    /// The URL the response was served from after any redirects.
+    pub(crate) final_url: String,
+    pub(crate) content_type: Option<String>,
+    pub(crate) etag: Option<String>,
+    pub(crate) last_modified: Option<String>,
+    /// The `Cache-Control` directives the response was served with.
+    pub(crate) control: CacheControl,
+    /// When the response was stored or last revalidated in seconds since the Unix
+    /// epoch.
+    pub(crate) stored_at: u64,
+    /// The server didn't have the resource.
+    #[serde(default)]
+    pub(crate) not_found: bool,
+    /// The body, stored as base64 rather than as a JSON array of numbers.
+    #[serde(with = "base64_body")]
+    pub(crate) body: Vec<u8>,
+}
+
+/// Serialize bytes as a base64 string.
+mod base64_body {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(body: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(body))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD.decode(encoded).map_err(D::Error::custom)
+    }
+}
+
+impl CachedResponse {
+    /// Check if the response can be used without revalidating it.
+    pub(crate) fn is_fresh(&self) -> bool {
+        if self.control.no_cache {
+            return false;
+        }
+        match self.control.max_age {
+            Some(max_age) => now().saturating_sub(self.stored_at) < max_age,
+            None => false,
+        }
+    }
+
+    /// Get the headers that make a request conditional on the response being stale.
+    pub(crate) fn conditional_headers(&self) -> Vec<(&'static str, &str)> {
+        let mut headers = Vec::new();
+        if let Some(etag) = self.etag.as_deref() {
+            headers.push(("If-None-Match", etag));
+        }
+        if let Some(last_modified) = self.last_modified.as_deref() {
+            headers.push(("If-Modified-Since", last_modified));
+        }
+        headers
+    }
+
+    /// Mark the response as revalidated, updating its directives from a
+    /// `304 Not Modified` response.
+    pub(crate) fn revalidate(&mut self, cache_control: Option<&str>) {
+        if let Some(cache_control) = cache_control {
+            self.control = CacheControl::parse(cache_control);
+        }
+        self.stored_at = now();
+    }
+}
+
+/// The directives of a `Cache-Control` header relevant to a private cache.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct CacheControl {
+    pub(crate) max_age: Option<u64>,
+    pub(crate) no_cache: bool,
+    pub(crate) no_store: bool,
+}
+
+impl CacheControl {
+    /// Parse the value of a `Cache-Control` header.
+    pub(crate) fn parse(value: &str) -> CacheControl {
+        let mut control = CacheControl::default();
+        for directive in value.split(',').map(|it| it.trim().to_ascii_lowercase()) {
+            match directive.split_once('=') {
+                Some(("max-age", seconds)) => {
+                    control.max_age = seconds.trim_matches('"').parse().ok()
+                }
+                _ if directive == "no-cache" => control.no_cache = true,
+                _ if directive == "no-store" => control.no_store = true,
+                _ => (),
+            }
+        }
+        control
+    }
+}
+
+/// Get the current time in seconds since the Unix epoch.
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|it| it.as_secs())
+        .unwrap_or_default()
+}
+
+pub(crate) fn hex_digest(contents: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(contents))
+}
+
+/// Write a file by writing a temporary file next to it and renaming it into place.
+fn write_atomic(path: &Path, contents: &[u8]) -> HuakResult<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp_path = path.with_file_name(format!(".{file_name}.{}.tmp", std::process::id()));
+    std::fs::write(&temp_path, contents)?;
+    std::fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+/// Get the default cache directory.
+fn default_cache_dir() -> PathBuf {
+    if let Some(path) = std::env::var_os(CACHE_DIR_ENV_VAR).filter(|it| !it.is_empty()) {
+        return PathBuf::from(path);
+    }
+    #[cfg(target_os = "macos")]
+    let dir = std::env::var_os("HOME").map(|it| PathBuf::from(it).join("Library").join("Caches"));
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let dir = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|it| !it.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|it| PathBuf::from(it).join(".cache")));
+    #[cfg(windows)]
+    let dir = std::env::var_os("LOCALAPPDATA").map(PathBuf::from);
+    dir.unwrap_or_else(std::env::temp_dir).join("huak")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn cache_http_responses() {
+        let cache = Cache::from_path(tempdir().unwrap().into_path());
+        let mut response = CachedResponse {
+            url: "https://example.com/simple/demo/".to_string(),
+            final_url: "https://example.com/simple/demo/".to_string(),
+            content_type: Some("text/html".to_string()),
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+            control: CacheControl::parse("max-age=600, public"),
+            stored_at: now(),
+            not_found: false,
+            body: b"<a href=\"demo-1.0.tar.gz\">demo-1.0.tar.gz</a>".to_vec(),
+        };
+
+        cache.store_http_response(&response).unwrap();
+        let stored = std::fs::read_to_string(cache.http_path(&response.url)).unwrap();
+
+        assert!(stored
+            .contains(r#""body":"PGEgaHJlZj0iZGVtby0xLjAudGFyLmd6Ij5kZW1vLTEuMC50YXIuZ3o8L2E+""#));
+        assert_eq!(
+            cache.http_response(&response.url).unwrap(),
+            Some(response.clone())
+        );
+        assert!(response.is_fresh());
+        assert_eq!(
+            response.conditional_headers(),
+            [("If-None-Match", "\"abc\"")]
+        );
+
+        response.stored_at -= 601;
+        assert!(!response.is_fresh());
+        response.revalidate(Some("no-cache"));
+        assert!(!response.is_fresh());
+
+        response.url = "https://example.com/simple/other/".to_string();
+        response.control = CacheControl::parse("no-store");
+        cache.store_http_response(&response).unwrap();
+        assert_eq!(cache.http_response(&response.url).unwrap(), None);
+    }
+
+    #[test]
+    fn cache_artifacts_and_metadata() {
+        let cache = Cache::from_path(tempdir().unwrap().into_path());
+        let digest = hex_digest(b"wheel");
+
+        assert_eq!(cache.artifact_path(&digest), None);
+
+        let dir = tempdir().unwrap().into_path();
+        std::fs::write(dir.join("demo.whl"), b"wheel").unwrap();
+        let path = cache
+            .store_artifact(&dir.join("demo.whl"), &digest)
+            .unwrap();
+
+        assert_eq!(
+            cache.artifact_path(&digest.to_uppercase()),
+            Some(path.clone())
+        );
+        assert_eq!(std::fs::read(path).unwrap(), b"wheel");
+
+        cache
+            .store_metadata("sha256:abc", "Metadata-Version: 2.1\nName: demo\n")
+            .unwrap();
+
+        assert!(cache
+            .metadata("sha256:abc")
+            .unwrap()
+            .unwrap()
+            .contains("demo"));
+        assert_eq!(cache.metadata("sha256:def").unwrap(), None);
+    }
+}
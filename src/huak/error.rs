@@ -10,8 +10,14 @@ pub type HuakResult<T> = Result<T, HuakError>;
 
 #[derive(Error, Debug)]
 pub enum HuakError {
+    #[error("the installed package at {0} is broken: {1}")]
+    BrokenDistInfoError(PathBuf, String),
+    #[error("a problem occurred running the build backend: {0}")]
+    BuildBackendError(String),
     #[error("a problem occurred with resolving build options")]
     BuildOptionsMissingError,
+    #[error("huak is offline and {0} is not in the cache")]
+    CacheMissError(String),
     #[error("a problem with argument parsing occurred: {0}")]
     ClapError(#[from] clap::Error),
     #[error("a problem with dependency resolution occurred: {0}")]
@@ -26,6 +32,10 @@ pub enum HuakError {
     FormatOptionsMissingError,
     #[error("a problem with git occurred: {0}")]
     GitError(#[from] git2::Error),
+    #[error("hash mismatch for {0}: expected {1}, got {2}")]
+    HashMismatchError(String, String, String),
+    #[error("{0} has no hash and hashes are required")]
+    HashMissingError(String),
     #[error("a problem with huak configuration occurred: {0}")]
     HuakConfigurationError(String),
     #[error("a problem with huak's internals occurred: {0}")]
@@ -40,10 +50,16 @@ pub enum HuakError {
     LinterError(String),
     #[error("a problem occurred with resolving lint options")]
     LintOptionsMissingError,
+    #[error("a problem occurred with the lock file: {0}")]
+    LockFileError(String),
+    #[error("huak.lock is out of date with pyproject.toml, run `huak lock` to update it")]
+    LockFileOutdatedError,
+    #[error("a problem occurred parsing environment markers: {0}")]
+    MarkerParseError(String),
     #[error("a problem with building the project occurred")]
     PackageBuildError,
-    #[error("a problem occurred initializing a package from a string")]
-    PackageFromStringError,
+    #[error("a problem occurred initializing a package from a string: {0}")]
+    PackageFromStringError(String),
     #[error("a problem with the package index occurred: {0}")]
     PackageIndexError(String),
     #[error("a problem with package installation occurred: {0}")]
@@ -52,6 +68,8 @@ pub enum HuakError {
     PackageInvalidVersionOperator(String),
     #[error("a problem with the package version occurred: {0}")]
     PackageInvalidVersion(String),
+    #[error("a problem occurred parsing package metadata: {0}")]
+    PackageMetadataError(String),
     #[error("a problem with the package version specifier occurred")]
     PackageVersionSpecifierError,
     #[error("a project file could not be found")]
@@ -64,10 +82,20 @@ pub enum HuakError {
     ProjectRootMissingError,
     #[error("a problem occurred with resolving publish options")]
     PublishOptionsMissingError,
+    #[error("a problem occurred installing python: {0}")]
+    PythonInstallError(String),
     #[error("an installed python module could not be found: {0}")]
     PythonModuleMissingError(String),
     #[error("a python interpreter could not be found")]
     PythonNotFoundError,
+    #[error("a python interpreter matching {0} could not be found")]
+    PythonVersionNotFoundError(String),
+    #[error("hashes are required but {0} isn't locked, run `huak lock` to lock the project's dependencies")]
+    RequireHashesUnlockedError(String),
+    #[error("the environment's python {0} doesn't satisfy requires-python `{1}` (found {2})")]
+    RequiresPythonMismatchError(String, String, String),
+    #[error("no python interpreter satisfies requires-python `{0}` (found {1})")]
+    RequiresPythonNotFoundError(String, String),
     #[error("a problem occurred parsing the virtual environment's config file: {0}")]
     VenvInvalidConfigFile(String),
     #[error("a venv could not be found")]
@@ -86,6 +114,12 @@ pub enum HuakError {
     TOMLEditSerializationError(#[from] toml_edit::ser::Error),
     #[error("a problem with utf-8 parsing occurred: {0}")]
     UTF8Error(#[from] std::str::Utf8Error),
+    #[error("a problem occurred parsing a wheel file name: {0}")]
+    WheelFilenameError(String),
+    #[error("a problem with a zip archive occurred: {0}")]
+    ZipError(#[from] zip::result::ZipError),
     #[error("{0}")]
     CommandError(String),
+    #[error("`{0}` exited with code {1}")]
+    CommandExitError(String, i32),
 }
@@ -0,0 +1,132 @@
+///! This module implements the events a `Terminal` emits in its JSON output format.
+///!
+///! Each event is written to stdout as one line of JSON with an `event` field naming
+///! it. Events only gain fields over time, so consumers should ignore fields they
+///! don't know about. Human-readable output and the output of commands huak runs are
+///! kept off stdout while events are emitted. Command output huak captures is emitted
+///! as `command-output` events instead.
+use crate::{LockedPackage, Package};
+use serde::Serialize;
+use std::path::Path;
+
+/// An event emitted by huak.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Event {
+    /// Progress of an operation, like `{"event":"status","status":"Built",...}`.
+    Status {
+        status: String,
+        message: Option<String>,
+    },
+    Note {
+        message: String,
+    },
+    Warning {
+        message: String,
+    },
+    Error {
+        message: String,
+    },
+    /// A package was installed to an environment.
+    PackageInstalled {
+        name: String,
+        version: Option<String>,
+    },
+    /// A package was uninstalled from an environment.
+    PackageUninstalled {
+        name: String,
+        version: Option<String>,
+    },
+    /// A package was pinned to a version by resolving a project's dependencies.
+    PackageResolved {
+        name: String,
+        version: String,
+        markers: Option<String>,
+    },
+    /// A project's distribution was built.
+    ProjectBuilt {
+        path: String,
+    },
+    /// The version of a project.
+    ProjectVersion {
+        version: String,
+    },
+    /// A line of the output of a command huak ran.
+    CommandOutput {
+        line: String,
+    },
+}
+
+impl Event {
+    /// Create an event for a package that was installed.
+    pub fn package_installed(package: &Package) -> Event {
+        Event::PackageInstalled {
+            name: package.name().to_string(),
+            version: package.version().map(|it| it.to_string()),
+        }
+    }
+
+    /// Create an event for a package that was uninstalled.
+    pub fn package_uninstalled(package: &Package) -> Event {
+        Event::PackageUninstalled {
+            name: package.name().to_string(),
+            version: package.version().map(|it| it.to_string()),
+        }
+    }
+
+    /// Create an event for a package pinned by a lock.
+    pub fn package_resolved(package: &LockedPackage) -> Event {
+        Event::PackageResolved {
+            name: package.name().to_string(),
+            version: package.version().to_string(),
+            markers: package.markers().map(|it| it.to_string()),
+        }
+    }
+
+    /// Create an event for a distribution that was built.
+    pub fn project_built(path: &Path) -> Event {
+        Event::ProjectBuilt {
+            path: path.display().to_string(),
+        }
+    }
+
+    /// Create an event for a line of a command's output. Output that isn't UTF-8 is
+    /// converted lossily.
+    pub fn command_output(line: &[u8]) -> Event {
+        Event::CommandOutput {
+            line: String::from_utf8_lossy(line)
+                .trim_end_matches(['\r', '\n'])
+                .to_string(),
+        }
+    }
+
+    /// Get the event as a line of JSON.
+    pub fn to_json(&self) -> String {
+        // Events only contain strings, so serializing them can't fail.
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn event_json() {
+        let package = Package::from_str("click==8.1.3").unwrap();
+
+        assert_eq!(
+            Event::package_installed(&package).to_json(),
+            r#"{"event":"package-installed","name":"click","version":"8.1.3"}"#
+        );
+        assert_eq!(
+            Event::Status {
+                status: "Built".to_string(),
+                message: None
+            }
+            .to_json(),
+            r#"{"event":"status","status":"Built","message":null}"#
+        );
+    }
+}
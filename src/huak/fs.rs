@@ -44,6 +44,15 @@ pub fn copy_dir<T: AsRef<Path>>(from: T, to: T) -> HuakResult<()> {
     Ok(())
 }
 
+/// Hard link a file to a new path, or copy it if it can't be linked, like when the
+/// paths are on different filesystems.
+pub fn link_or_copy_file(from: impl AsRef<Path>, to: impl AsRef<Path>) -> HuakResult<()> {
+    if fs::hard_link(from.as_ref(), to.as_ref()).is_err() {
+        fs::copy(from, to)?;
+    }
+    Ok(())
+}
+
 pub fn flatten_directories(
     directories: impl IntoIterator<Item = PathBuf>,
 ) -> impl Iterator<Item = PathBuf> {
@@ -55,6 +64,24 @@ pub fn flatten_directories(
         .map(|e| e.path())
 }
 
+/// Check if two existing paths are on the same filesystem. Paths that can't be read
+/// are treated as being on the same filesystem.
+pub fn is_same_filesystem(a: impl AsRef<Path>, b: impl AsRef<Path>) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        match (fs::metadata(a), fs::metadata(b)) {
+            (Ok(a), Ok(b)) => a.dev() == b.dev(),
+            _ => true,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (a, b);
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use tempfile::tempdir;
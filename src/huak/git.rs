@@ -1,17 +1,25 @@
 use crate::error::HuakResult;
+use git2::Repository;
 use std::path::Path;
 
 /// Initialize a directory on a local system as a git repository.
 pub fn init(dir_path: impl AsRef<Path>) -> HuakResult<()> {
-    todo!()
+    Repository::init(dir_path)?;
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
+    use tempfile::tempdir;
+
     use super::*;
 
     #[test]
     fn test_init() {
-        todo!()
+        let dir = tempdir().unwrap().into_path();
+
+        init(&dir).unwrap();
+
+        assert!(dir.join(".git").exists());
     }
 }
@@ -0,0 +1,826 @@
+///! This module implements parsing for the responses of simple repository APIs.
+///! Both the HTML (PEP 503) and JSON (PEP 691) forms of a project's page are
+///! supported.
+///! See https://packaging.python.org/en/latest/specifications/simple-repository-api/
+///!
+///! Projects can configure the indexes packages are found on with `[tool.huak]`:
+///!
+///! ```toml
+///! [[tool.huak.index]]
+///! name = "internal"
+///! url = "https://pkgs.example.com/simple"
+///!
+///! [tool.huak.index-pins]
+///! internal-lib = "internal"
+///! ```
+///!
+///! Indexes are searched in priority order: the primary index (PyPI unless an index
+///! sets `default = true`) followed by the extra indexes in the order they're listed.
+///! A package is found on the first index that serves it. Pinned packages are only
+///! ever found on their pinned index, which prevents a package with the same name on
+///! another index from being installed in its place.
+use crate::{
+    error::{HuakError, HuakResult},
+    resolve::Artifact,
+};
+use pep440_rs::Version;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Component, Path},
+    str::FromStr,
+};
+
+/// The media type of PEP 691 JSON responses.
+pub(crate) const SIMPLE_JSON_CONTENT_TYPE: &str = "application/vnd.pypi.simple.v1+json";
+
+/// The `Accept` header sent for project pages. JSON responses are preferred.
+pub(crate) const SIMPLE_ACCEPT: &str = "application/vnd.pypi.simple.v1+json, application/vnd.pypi.simple.v1+html;q=0.2, text/html;q=0.01";
+
+/// A project's page from a simple repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimpleProject {
+    /// The name of the project.
+    name: String,
+    /// The files the repository serves for the project.
+    files: Vec<SimpleFile>,
+}
+
+impl SimpleProject {
+    /// Parse a project page from a response body. Bodies with the PEP 691 JSON media
+    /// type are parsed as JSON. Anything else is parsed as PEP 503 HTML. Relative
+    /// URLs are resolved against the page's URL.
+    pub(crate) fn from_response(
+        name: &str,
+        url: &Url,
+        content_type: &str,
+        body: &str,
+    ) -> HuakResult<SimpleProject> {
+        if content_type.starts_with(SIMPLE_JSON_CONTENT_TYPE) {
+            parse_simple_json(body, url)
+        } else {
+            parse_simple_html(name, body, url)
+        }
+    }
+
+    /// Get the name of the project.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the files the repository serves for the project.
+    pub fn files(&self) -> &[SimpleFile] {
+        &self.files
+    }
+}
+
+/// A file listed on a project's page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimpleFile {
+    /// The name of the file.
+    filename: String,
+    /// The absolute URL of the file without any hash fragment.
+    url: String,
+    /// The file's hashes keyed by their algorithm.
+    hashes: BTreeMap<String, String>,
+    /// The Python versions the file supports as a version specifier string.
+    requires_python: Option<String>,
+    /// The hashes of the file's core metadata if the repository serves it separately.
+    core_metadata: Option<BTreeMap<String, String>>,
+    /// The reason the file was yanked if it was. The reason may be empty.
+    yanked: Option<String>,
+}
+
+impl SimpleFile {
+    /// Get the name of the file.
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Get the absolute URL of the file.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Get the file's hashes keyed by their algorithm.
+    pub fn hashes(&self) -> &BTreeMap<String, String> {
+        &self.hashes
+    }
+
+    /// Get the Python versions the file supports.
+    pub fn requires_python(&self) -> Option<&str> {
+        self.requires_python.as_deref()
+    }
+
+    /// Get the URL of the file's core metadata if the repository serves it
+    /// separately. See PEP 658.
+    pub fn core_metadata_url(&self) -> Option<String> {
+        self.core_metadata
+            .as_ref()
+            .map(|_| format!("{}.metadata", self.url))
+    }
+
+    /// Get the hashes of the file's core metadata.
+    pub fn core_metadata_hashes(&self) -> Option<&BTreeMap<String, String>> {
+        self.core_metadata.as_ref()
+    }
+
+    /// Check if the file was yanked. See PEP 592.
+    pub fn is_yanked(&self) -> bool {
+        self.yanked.is_some()
+    }
+
+    /// Get the reason the file was yanked.
+    pub fn yanked_reason(&self) -> Option<&str> {
+        self.yanked.as_deref().filter(|it| !it.is_empty())
+    }
+
+    /// Get the version of the distribution from the file's name.
+    pub fn version(&self) -> Option<Version> {
+        filename_version(&self.filename)
+    }
+
+    /// Get the file as an artifact that can be locked.
+    pub fn artifact(&self) -> Artifact {
+        Artifact {
+            name: self.filename.clone(),
+            url: self.url.clone(),
+            hash: self.hashes.get("sha256").map(|it| format!("sha256:{it}")),
+        }
+    }
+}
+
+/// Check if a file name is a single plain path component, so a file saved under it
+/// can't end up outside of the directory it's saved to.
+pub(crate) fn is_plain_file_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    !name.contains(['/', '\\'])
+        && matches!(components.next(), Some(Component::Normal(_)))
+        && components.next().is_none()
+}
+
+/// Check that a file listed on a project's page has a plain file name.
+fn check_filename(filename: &str) -> HuakResult<()> {
+    if is_plain_file_name(filename) {
+        return Ok(());
+    }
+    Err(HuakError::PackageIndexError(format!(
+        "invalid file name {filename:?}"
+    )))
+}
+
+/// Get the version of a distribution from the name of its wheel or source archive.
+fn filename_version(filename: &str) -> Option<Version> {
+    let version = match filename.strip_suffix(".whl") {
+        Some(stem) => stem.split('-').nth(1)?,
+        None => {
+            let stem = [".tar.gz", ".zip", ".tar.bz2", ".tgz"]
+                .iter()
+                .find_map(|it| filename.strip_suffix(it))?;
+            stem.rsplit_once('-')?.1
+        }
+    };
+    Version::from_str(version).ok()
+}
+
+/// Resolve a file's URL against the page it's listed on, splitting off any
+/// `#<algorithm>=<digest>` hash fragment.
+fn resolve_file_url(base_url: &Url, href: &str) -> HuakResult<(String, Option<(String, String)>)> {
+    let mut url = base_url
+        .join(href)
+        .map_err(|e| HuakError::PackageIndexError(format!("invalid url {href}: {e}")))?;
+    let hash = url
+        .fragment()
+        .and_then(|it| it.split_once('='))
+        .map(|(algorithm, digest)| (algorithm.to_lowercase(), digest.to_lowercase()));
+    url.set_fragment(None);
+    Ok((url.to_string(), hash))
+}
+
+/// Parse a `<algorithm>=<digest>` hash attribute into a map.
+fn parse_hash_attribute(value: &str) -> BTreeMap<String, String> {
+    value
+        .split_once('=')
+        .map(|(algorithm, digest)| (algorithm.to_lowercase(), digest.to_lowercase()))
+        .into_iter()
+        .collect()
+}
+
+/// Parse a PEP 503 HTML project page. Every anchor on the page is a file.
+pub(crate) fn parse_simple_html(
+    name: &str,
+    contents: &str,
+    url: &Url,
+) -> HuakResult<SimpleProject> {
+    let mut files = Vec::new();
+    for (attributes, text) in anchors(contents) {
+        let href = match attributes.get("href") {
+            Some(it) => it,
+            None => continue,
+        };
+        let (url, hash) = resolve_file_url(url, href)?;
+        let core_metadata = attributes
+            .get("data-core-metadata")
+            .or_else(|| attributes.get("data-dist-info-metadata"))
+            .filter(|it| it.as_str() != "false")
+            .map(|it| parse_hash_attribute(it));
+        let filename = text.trim().to_string();
+        check_filename(&filename)?;
+        files.push(SimpleFile {
+            filename,
+            url,
+            hashes: hash.into_iter().collect(),
+            requires_python: attributes.get("data-requires-python").cloned(),
+            core_metadata,
+            yanked: attributes.get("data-yanked").cloned(),
+        });
+    }
+    Ok(SimpleProject {
+        name: name.to_string(),
+        files,
+    })
+}
+
+/// Find the anchors of an HTML document as their attributes and text.
+fn anchors(contents: &str) -> Vec<(BTreeMap<String, String>, String)> {
+    let mut anchors = Vec::new();
+    let lowercase = contents.to_ascii_lowercase();
+    let mut position = 0;
+    while let Some(start) = lowercase[position..].find("<a").map(|it| it + position) {
+        position = start + 2;
+        if !lowercase[position..].starts_with(|c: char| c.is_ascii_whitespace() || c == '>') {
+            continue;
+        }
+        let (attributes, tag_end) = parse_attributes(&contents[position..]);
+        position += tag_end;
+        let text_end = lowercase[position..]
+            .find("</a")
+            .map_or(contents.len(), |it| it + position);
+        anchors.push((attributes, unescape(&contents[position..text_end])));
+        position = text_end;
+    }
+    anchors
+}
+
+/// Parse the attributes of a tag up to its closing `>`. The attributes are returned
+/// with the length of the remaining tag.
+fn parse_attributes(tag: &str) -> (BTreeMap<String, String>, usize) {
+    let mut attributes = BTreeMap::new();
+    let mut chars = tag.char_indices().peekable();
+    loop {
+        while chars
+            .next_if(|(_, c)| c.is_ascii_whitespace() || *c == '/')
+            .is_some()
+        {}
+        let (start, c) = match chars.next() {
+            Some(it) => it,
+            None => return (attributes, tag.len()),
+        };
+        if c == '>' {
+            return (attributes, start + 1);
+        }
+        let mut name_end = tag.len();
+        while let Some((i, c)) = chars.peek().copied() {
+            if c.is_ascii_whitespace() || matches!(c, '=' | '>' | '/') {
+                name_end = i;
+                break;
+            }
+            chars.next();
+        }
+        let name = tag[start..name_end].to_ascii_lowercase();
+        while chars.next_if(|(_, c)| c.is_ascii_whitespace()).is_some() {}
+        if chars.next_if(|(_, c)| *c == '=').is_none() {
+            attributes.insert(name, String::new());
+            continue;
+        }
+        while chars.next_if(|(_, c)| c.is_ascii_whitespace()).is_some() {}
+        let quote = chars.next_if(|(_, c)| matches!(c, '"' | '\''));
+        let value_start = match chars.peek() {
+            Some((i, _)) => *i,
+            None => tag.len(),
+        };
+        let mut value_end = tag.len();
+        while let Some((i, c)) = chars.peek().copied() {
+            let end = match quote {
+                Some((_, quote)) => c == quote,
+                None => c.is_ascii_whitespace() || c == '>',
+            };
+            if end {
+                value_end = i;
+                if quote.is_some() {
+                    chars.next();
+                }
+                break;
+            }
+            chars.next();
+        }
+        attributes.insert(name, unescape(&tag[value_start..value_end]));
+    }
+}
+
+/// Replace the HTML character references of a string.
+fn unescape(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let reference = rest.find(';').map(|end| (&rest[1..end], end));
+        let c = reference.and_then(|(name, _)| match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                None => name
+                    .strip_prefix('#')
+                    .and_then(|it| it.parse().ok())
+                    .and_then(char::from_u32),
+            },
+        });
+        match (c, reference) {
+            (Some(c), Some((_, end))) => {
+                unescaped.push(c);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+#[derive(Deserialize)]
+struct JsonProject {
+    meta: JsonMeta,
+    name: String,
+    files: Vec<JsonFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct JsonMeta {
+    api_version: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct JsonFile {
+    filename: String,
+    url: String,
+    #[serde(default)]
+    hashes: BTreeMap<String, String>,
+    requires_python: Option<String>,
+    core_metadata: Option<JsonCoreMetadata>,
+    dist_info_metadata: Option<JsonCoreMetadata>,
+    yanked: Option<JsonYanked>,
+}
+
+/// The `core-metadata` key is either a boolean or the hashes of the metadata file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonCoreMetadata {
+    Available(bool),
+    Hashes(BTreeMap<String, String>),
+}
+
+/// The `yanked` key is either a boolean or the reason the file was yanked.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonYanked {
+    Yanked(bool),
+    Reason(String),
+}
+
+/// Parse a PEP 691 JSON project page.
+pub(crate) fn parse_simple_json(contents: &str, url: &Url) -> HuakResult<SimpleProject> {
+    let project: JsonProject = serde_json::from_str(contents)?;
+    if project.meta.api_version.split('.').next() != Some("1") {
+        return Err(HuakError::PackageIndexError(format!(
+            "unsupported simple api version {}",
+            project.meta.api_version
+        )));
+    }
+    let mut files = Vec::new();
+    for file in project.files {
+        let (url, hash) = resolve_file_url(url, &file.url)?;
+        let mut hashes = file.hashes;
+        if let Some((algorithm, digest)) = hash {
+            hashes.entry(algorithm).or_insert(digest);
+        }
+        let core_metadata = match file.core_metadata.or(file.dist_info_metadata) {
+            Some(JsonCoreMetadata::Available(true)) => Some(BTreeMap::new()),
+            Some(JsonCoreMetadata::Hashes(hashes)) => Some(hashes),
+            Some(JsonCoreMetadata::Available(false)) | None => None,
+        };
+        let yanked = match file.yanked {
+            Some(JsonYanked::Yanked(true)) => Some(String::new()),
+            Some(JsonYanked::Reason(reason)) => Some(reason),
+            Some(JsonYanked::Yanked(false)) | None => None,
+        };
+        check_filename(&file.filename)?;
+        files.push(SimpleFile {
+            filename: file.filename,
+            url,
+            hashes,
+            requires_python: file.requires_python,
+            core_metadata,
+            yanked,
+        });
+    }
+    Ok(SimpleProject {
+        name: project.name,
+        files,
+    })
+}
+
+/// The name of the index used when no primary index is configured.
+const DEFAULT_INDEX_NAME: &str = "pypi";
+
+/// The URL of the index used when no primary index is configured.
+pub(crate) const DEFAULT_INDEX_URL: &str = "https://pypi.org/simple";
+
+/// A package index listed in the `[tool.huak]` table.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PackageIndex {
+    /// The name the index is referred to by.
+    name: String,
+    /// The base URL of the index's simple repository API.
+    url: String,
+    /// Whether the index replaces PyPI as the primary index.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    default: bool,
+}
+
+impl PackageIndex {
+    pub fn new(name: &str, url: &str) -> PackageIndex {
+        PackageIndex {
+            name: name.to_string(),
+            url: url.trim_end_matches('/').to_string(),
+            default: false,
+        }
+    }
+
+    /// Get the name of the index.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the base URL of the index's simple repository API.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Check if the index replaces PyPI as the primary index.
+    pub fn is_default(&self) -> bool {
+        self.default
+    }
+
+    /// Make the index replace PyPI as the primary index.
+    pub fn with_default(&mut self, default: bool) -> &mut PackageIndex {
+        self.default = default;
+        self
+    }
+}
+
+/// The indexes packages are found on in priority order, and the packages pinned to
+/// an index.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct IndexConfig {
+    /// The indexes in the order they're searched. The first index is the primary.
+    indexes: Vec<PackageIndex>,
+    /// The names of the indexes packages are pinned to keyed by canonical package
+    /// name.
+    pins: BTreeMap<String, String>,
+}
+
+impl IndexConfig {
+    /// Create the configuration from the indexes and pins of a `[tool.huak]` table.
+    /// The primary index is the index marked `default`, or PyPI if there isn't one.
+    pub fn new(
+        indexes: Vec<PackageIndex>,
+        pins: BTreeMap<String, String>,
+    ) -> HuakResult<IndexConfig> {
+        let (mut primary, extra): (Vec<_>, Vec<_>) =
+            indexes.into_iter().partition(|it| it.is_default());
+        if primary.len() > 1 {
+            return Err(HuakError::HuakConfigurationError(
+                "only one index can be the default".to_string(),
+            ));
+        }
+        if primary.is_empty() {
+            primary.push(PackageIndex::new(DEFAULT_INDEX_NAME, DEFAULT_INDEX_URL));
+        }
+        let mut indexes = primary;
+        indexes.extend(extra);
+        for index in indexes.iter_mut() {
+            index.url = index.url.trim_end_matches('/').to_string();
+        }
+        for (i, index) in indexes.iter().enumerate() {
+            if indexes[..i].iter().any(|it| it.name == index.name) {
+                return Err(HuakError::HuakConfigurationError(format!(
+                    "index {} is listed more than once",
+                    index.name
+                )));
+            }
+        }
+        let mut canonical_pins = BTreeMap::new();
+        for (package, index) in pins {
+            if !indexes.iter().any(|it| it.name == index) {
+                return Err(HuakError::HuakConfigurationError(format!(
+                    "{package} is pinned to index {index} which isn't listed"
+                )));
+            }
+            canonical_pins.insert(crate::canonical_package_name(&package), index);
+        }
+        Ok(IndexConfig {
+            indexes,
+            pins: canonical_pins,
+        })
+    }
+
+    /// Get the indexes in the order they're searched.
+    pub fn indexes(&self) -> &[PackageIndex] {
+        &self.indexes
+    }
+
+    /// Get the primary index.
+    pub fn primary(&self) -> &PackageIndex {
+        &self.indexes[0]
+    }
+
+    /// Get the index a package is pinned to.
+    pub fn pinned_index(&self, name: &str) -> Option<&PackageIndex> {
+        let index = self.pins.get(&crate::canonical_package_name(name))?;
+        self.indexes.iter().find(|it| &it.name == index)
+    }
+
+    /// Get the indexes a package can be found on in the order they're searched.
+    pub fn indexes_for(&self, name: &str) -> Vec<&PackageIndex> {
+        match self.pinned_index(name) {
+            Some(index) => vec![index],
+            None => self.indexes.iter().collect(),
+        }
+    }
+
+    /// Replace the URL of the primary index.
+    pub(crate) fn set_primary_url(&mut self, url: &str) {
+        self.indexes[0].url = url.trim_end_matches('/').to_string();
+    }
+}
+
+impl Default for IndexConfig {
+    fn default() -> Self {
+        IndexConfig {
+            indexes: vec![PackageIndex::new(DEFAULT_INDEX_NAME, DEFAULT_INDEX_URL)],
+            pins: BTreeMap::new(),
+        }
+    }
+}
+
+/// Serve fixed responses over HTTP on a local port for tests. Each route is a path
+/// with the content type and body served for it. The base URL of the server is
+/// returned.
+#[cfg(test)]
+pub(crate) fn serve(routes: Vec<(String, String, Vec<u8>)>) -> String {
+    serve_authenticated(routes, None)
+}
+
+/// Serve fixed responses like `serve`, answering requests without the `Authorization`
+/// header value with 401 Unauthorized.
+#[cfg(test)]
+pub(crate) fn serve_authenticated(
+    routes: Vec<(String, String, Vec<u8>)>,
+    authorization: Option<String>,
+) -> String {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(it) => it,
+                Err(_) => continue,
+            };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            if reader.read_line(&mut request_line).is_err() {
+                continue;
+            }
+            let mut authorized = authorization.is_none();
+            let mut line = String::new();
+            while reader.read_line(&mut line).is_ok() && line.trim() != "" {
+                if let (Some((key, value)), Some(expected)) =
+                    (line.split_once(':'), authorization.as_ref())
+                {
+                    authorized |=
+                        key.eq_ignore_ascii_case("authorization") && value.trim() == expected;
+                }
+                line.clear();
+            }
+            let path = request_line.split(' ').nth(1).unwrap_or_default();
+            if !authorized {
+                let _ = stream.write_all(
+                    b"HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                );
+                continue;
+            }
+            let response = match routes.iter().find(|(route, _, _)| route == path) {
+                Some((_, content_type, body)) => {
+                    let mut response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body.len()
+                    )
+                    .into_bytes();
+                    response.extend_from_slice(body);
+                    response
+                }
+                None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_vec(),
+            };
+            let _ = stream.write_all(&response);
+        }
+    });
+    format!("http://{address}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simple_html() {
+        let url = Url::parse("https://example.com/simple/demo/").unwrap();
+        let contents = r#"<!DOCTYPE html>
+<html>
+  <body>
+    <h1>Links for demo</h1>
+    <a href="../../files/demo-1.0-py3-none-any.whl#sha256=ABC123" data-requires-python="&gt;=3.7" data-dist-info-metadata="sha256=def456">demo-1.0-py3-none-any.whl</a><br/>
+    <A HREF='https://files.example.com/demo-1.1.tar.gz' data-yanked="">demo-1.1.tar.gz</A>
+    <a href="demo-2.0-py3-none-any.whl" data-yanked="broken &amp; bad" data-core-metadata>demo-2.0-py3-none-any.whl</a>
+  </body>
+</html>
+"#;
+
+        let project = parse_simple_html("demo", contents, &url).unwrap();
+        let files = project.files();
+
+        assert_eq!(files.len(), 3);
+        assert_eq!(
+            files[0].url(),
+            "https://example.com/files/demo-1.0-py3-none-any.whl"
+        );
+        assert_eq!(files[0].hashes().get("sha256").unwrap(), "abc123");
+        assert_eq!(files[0].requires_python(), Some(">=3.7"));
+        assert_eq!(
+            files[0].core_metadata_url().unwrap(),
+            "https://example.com/files/demo-1.0-py3-none-any.whl.metadata"
+        );
+        assert_eq!(
+            files[0]
+                .core_metadata_hashes()
+                .unwrap()
+                .get("sha256")
+                .unwrap(),
+            "def456"
+        );
+        assert!(!files[0].is_yanked());
+        assert_eq!(files[0].artifact().hash.unwrap(), "sha256:abc123");
+        assert_eq!(files[1].version(), Some(Version::from_str("1.1").unwrap()));
+        assert!(files[1].is_yanked());
+        assert_eq!(files[1].yanked_reason(), None);
+        assert_eq!(
+            files[2].url(),
+            "https://example.com/simple/demo/demo-2.0-py3-none-any.whl"
+        );
+        assert_eq!(files[2].yanked_reason(), Some("broken & bad"));
+        assert!(files[2].core_metadata_hashes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn simple_file_names_outside_of_download_dir() {
+        let url = Url::parse("https://example.com/simple/demo/").unwrap();
+        for filename in [
+            "../../evil-1.0-py3-none-any.whl",
+            "/etc/x.whl",
+            "dir\\evil-1.0-py3-none-any.whl",
+            "..",
+        ] {
+            let html = format!(r#"<a href="/files/demo-1.0-py3-none-any.whl">{filename}</a>"#);
+            let json = serde_json::json!({
+                "meta": {"api-version": "1.0"},
+                "name": "demo",
+                "files": [{"filename": filename, "url": "/files/demo-1.0-py3-none-any.whl", "hashes": {}}],
+            });
+
+            assert!(matches!(
+                parse_simple_html("demo", &html, &url),
+                Err(HuakError::PackageIndexError(..))
+            ));
+            assert!(matches!(
+                parse_simple_json(&json.to_string(), &url),
+                Err(HuakError::PackageIndexError(..))
+            ));
+        }
+        assert!(is_plain_file_name("demo-1.0-py3-none-any.whl"));
+    }
+
+    #[test]
+    fn index_config_priority_and_pins() {
+        let mut internal = PackageIndex::new("internal", "https://pkgs.example.com/simple/");
+        let mirror = PackageIndex::new("mirror", "https://mirror.example.com/simple");
+        let pins = BTreeMap::from([("Internal_Lib".to_string(), "internal".to_string())]);
+
+        let config =
+            IndexConfig::new(vec![internal.clone(), mirror.clone()], pins.clone()).unwrap();
+        let names = |indexes: Vec<&PackageIndex>| {
+            indexes
+                .iter()
+                .map(|it| it.name().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(config.indexes_for("click")),
+            ["pypi", "internal", "mirror"]
+        );
+        assert_eq!(names(config.indexes_for("internal-lib")), ["internal"]);
+        assert_eq!(
+            config.pinned_index("internal.lib").unwrap().url(),
+            "https://pkgs.example.com/simple"
+        );
+
+        internal.with_default(true);
+        let config = IndexConfig::new(vec![mirror.clone(), internal.clone()], pins).unwrap();
+
+        assert_eq!(names(config.indexes_for("click")), ["internal", "mirror"]);
+        assert!(IndexConfig::new(
+            vec![mirror.clone()],
+            BTreeMap::from([("click".to_string(), "internal".to_string())])
+        )
+        .is_err());
+        assert!(IndexConfig::new(vec![mirror.clone(), mirror], BTreeMap::new()).is_err());
+    }
+
+    #[test]
+    fn simple_json() {
+        let url = Url::parse("https://example.com/simple/demo/").unwrap();
+        let contents = r#"{
+  "meta": {"api-version": "1.1"},
+  "name": "demo",
+  "files": [
+    {
+      "filename": "demo-1.0-py3-none-any.whl",
+      "url": "/files/demo-1.0-py3-none-any.whl",
+      "hashes": {"sha256": "abc123"},
+      "requires-python": ">=3.7",
+      "core-metadata": {"sha256": "def456"}
+    },
+    {
+      "filename": "demo-1.1.tar.gz",
+      "url": "https://files.example.com/demo-1.1.tar.gz#sha256=fed",
+      "hashes": {},
+      "dist-info-metadata": false,
+      "yanked": "broken"
+    }
+  ]
+}"#;
+
+        let project = parse_simple_json(contents, &url).unwrap();
+        let files = project.files();
+
+        assert_eq!(project.name(), "demo");
+        assert_eq!(
+            files[0].url(),
+            "https://example.com/files/demo-1.0-py3-none-any.whl"
+        );
+        assert_eq!(files[0].requires_python(), Some(">=3.7"));
+        assert_eq!(
+            files[0]
+                .core_metadata_hashes()
+                .unwrap()
+                .get("sha256")
+                .unwrap(),
+            "def456"
+        );
+        assert_eq!(files[1].url(), "https://files.example.com/demo-1.1.tar.gz");
+        assert_eq!(files[1].hashes().get("sha256").unwrap(), "fed");
+        assert_eq!(files[1].core_metadata_url(), None);
+        assert_eq!(files[1].yanked_reason(), Some("broken"));
+        assert!(parse_simple_json(
+            r#"{"meta": {"api-version": "2.0"}, "name": "demo", "files": []}"#,
+            &url
+        )
+        .is_err());
+    }
+}
@@ -1,28 +1,68 @@
-use error::{HuakError, HuakResult};
-use pep440_rs::{Operator as VersionOperator, Version};
+use pep440_rs::{Operator as VersionOperator, Version, VersionSpecifier};
 use pyproject_toml::PyProjectToml as ProjectToml;
-use serde::{Deserialize, Serialize};
+use requirement::Requirement;
+use serde::Deserialize;
 use std::{
-    collections::{hash_map::RandomState, HashMap},
-    fs::File,
+    collections::{hash_map::RandomState, BTreeMap, HashMap},
     path::{Path, PathBuf},
+    process::Command,
     str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
 };
-use sys::Terminal;
+use toml_edit::{Array, Document, Item, Table, Value};
 
+mod auth;
+mod cache;
 mod error;
+mod events;
 mod fs;
 mod git;
-mod ops;
+mod index;
+mod lock;
+mod markers;
+mod metadata;
+pub mod ops;
+mod pep517;
+mod progress;
+mod python;
+mod requirement;
+mod resolve;
+mod standalone;
 mod sys;
+mod tags;
+mod venv;
+mod wheel;
+
+pub use auth::{CredentialProvider, Credentials};
+pub use cache::Cache;
+use cache::{CacheControl, CachedResponse};
+pub use error::{HuakError, HuakResult};
+pub use events::Event;
+pub use index::{IndexConfig, PackageIndex, SimpleFile, SimpleProject};
+pub use lock::{LockFile, LockedPackage, LOCK_FILE_NAME};
+pub use markers::{MarkerEnvironment, MarkerTree};
+pub use metadata::{EntryPoint, WheelMetadata};
+pub use pep517::{BuildSystem, SourceBuild};
+pub use progress::{Progress, ProgressBar, ProgressUnit};
+pub use python::{
+    find_python_interpreters, PythonImplementation, PythonInterpreter, PythonRequest,
+};
+pub use resolve::{Artifact, PackageSource, Resolver};
+pub use standalone::{ManagedPython, ManagedPythons, PythonSource};
+pub use sys::{CapturedOutput, OutputFormat, Platform, Terminal, Verbosity};
+pub use tags::{CompatibilityTag, PlatformTag, SupportedTags, WheelFilename};
+pub use wheel::RecordEntry;
 
 const DEFAULT_VENV_NAME: &str = ".venv";
 const DEFAULT_PYPROJECT_TOML_CONTENTS: &str = r#"[project]
 name = ""
 version = "0.0.1"
 description = ""
-
-[project.dependencies]
+requires-python = ">=3.8"
+dependencies = []
 
 [build-system]
 requires = ["hatchling"]
@@ -112,30 +152,94 @@ impl Project {
     }
 
     /// Add a Python package as a dependency to the project's project file.
-    pub fn add_dependency(&mut self, package_str: &str) {
-        self.pyproject_toml.add_dependency(package_str);
+    pub fn add_dependency(&mut self, package_str: &str) -> HuakResult<()> {
+        self.pyproject_toml.add_dependency(package_str)
     }
 
     /// Add a Python package as a dependency to the project' project file.
-    pub fn add_optional_dependency(&mut self, package_str: &str, group_name: &str) {
+    pub fn add_optional_dependency(
+        &mut self,
+        package_str: &str,
+        group_name: &str,
+    ) -> HuakResult<()> {
         self.pyproject_toml
             .add_optional_dependency(package_str, group_name)
     }
 
     /// Remove a dependency from the project's project file.
-    pub fn remove_dependency(&mut self, package_str: &str) {
-        self.pyproject_toml.remove_dependency(package_str);
+    pub fn remove_dependency(&mut self, package_name: &str) -> HuakResult<()> {
+        self.pyproject_toml.remove_dependency(package_name)
     }
 
     /// Remove an optional dependency from the project's project file.
-    pub fn remove_optional_dependency(&mut self, package_str: &str, group_name: &str) {
+    pub fn remove_optional_dependency(
+        &mut self,
+        package_name: &str,
+        group_name: &str,
+    ) -> HuakResult<()> {
         self.pyproject_toml
-            .remove_optional_dependency(package_str, group_name);
+            .remove_optional_dependency(package_name, group_name)
     }
 
     /// Write the current project to some directory path.
     pub fn write_project(&self, dir_path: impl AsRef<Path>) -> HuakResult<()> {
-        todo!()
+        let root = dir_path.as_ref();
+        let manifest_path = root.join("pyproject.toml");
+        if manifest_path.exists() {
+            return Err(HuakError::ProjectTomlExistsError);
+        }
+        let name = match self.pyproject_toml.project_name() {
+            Some(it) if !it.is_empty() => it.to_string(),
+            _ => root
+                .file_name()
+                .and_then(|name| name.to_str())
+                .ok_or(HuakError::ProjectRootMissingError)?
+                .to_string(),
+        };
+        let importable_name = importable_package_name(&name);
+        let mut pyproject_toml = self.pyproject_toml.clone();
+        pyproject_toml.set_project_name(&name)?;
+        if self.project_type == ProjectType::Application {
+            pyproject_toml.add_script(&name, &format!("{importable_name}.main:main"))?;
+        }
+
+        let src_path = root.join("src").join(&importable_name);
+        let tests_path = root.join("tests");
+        std::fs::create_dir_all(&src_path)?;
+        std::fs::create_dir_all(&tests_path)?;
+        pyproject_toml.write_file(&manifest_path)?;
+        std::fs::write(
+            src_path.join("__init__.py"),
+            format!(
+                "__version__ = \"{}\"\n",
+                pyproject_toml.project_version().unwrap_or("0.0.1")
+            ),
+        )?;
+        std::fs::write(tests_path.join("__init__.py"), "")?;
+        std::fs::write(
+            tests_path.join("test_version.py"),
+            format!(
+                r#"from {importable_name} import __version__
+
+
+def test_version():
+    __version__
+"#
+            ),
+        )?;
+        if self.project_type == ProjectType::Application {
+            std::fs::write(
+                src_path.join("main.py"),
+                r#"def main():
+    print("Hello, World!")
+
+
+if __name__ == "__main__":
+    main()
+"#,
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -170,12 +274,12 @@ pub struct ProjectLayout {
     pyproject_toml_path: PathBuf,
 }
 
-/// A pyproject.toml as specified in PEP 517
-#[derive(Serialize, Deserialize, Debug, Clone)]
-#[serde(rename_all = "kebab-case")]
+/// A pyproject.toml as specified in PEP 517. The toml document is kept alongside its
+/// parsed data so that edits can be written without disturbing the rest of the file.
+#[derive(Debug, Clone)]
 pub struct PyProjectToml {
-    #[serde(flatten)]
     inner: ProjectToml,
+    document: Document,
 }
 
 impl std::ops::Deref for PyProjectToml {
@@ -186,12 +290,6 @@ impl std::ops::Deref for PyProjectToml {
     }
 }
 
-impl std::ops::DerefMut for PyProjectToml {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.inner
-    }
-}
-
 impl PyProjectToml {
     /// Create new pyproject.toml data.
     pub fn new() -> PyProjectToml {
@@ -201,8 +299,27 @@ impl PyProjectToml {
     /// Create new pyproject.toml data from a pyproject.toml's path.
     pub fn from_path(path: impl AsRef<Path>) -> HuakResult<PyProjectToml> {
         let contents = std::fs::read_to_string(path)?;
-        let pyproject_toml: PyProjectToml = toml::from_str(&contents)?;
-        Ok(pyproject_toml)
+        PyProjectToml::from_contents(&contents)
+    }
+
+    /// Create new pyproject.toml data from the contents of a pyproject.toml.
+    fn from_contents(contents: &str) -> HuakResult<PyProjectToml> {
+        let document = contents
+            .parse::<Document>()
+            .map_err(toml_edit::de::Error::from)?;
+        let inner = toml_edit::de::from_document(document.clone())?;
+        Ok(PyProjectToml { inner, document })
+    }
+
+    /// Update the parsed data after the document has been edited.
+    fn sync(&mut self) -> HuakResult<()> {
+        self.inner = toml_edit::de::from_document(self.document.clone())?;
+        Ok(())
+    }
+
+    /// Get the document's `[project]` table.
+    fn project_table_mut(&mut self) -> Option<&mut Table> {
+        self.document.get_mut("project")?.as_table_mut()
     }
 
     /// Get the project name.
@@ -211,10 +328,11 @@ impl PyProjectToml {
     }
 
     /// Set the project name listed in the toml.
-    pub fn set_project_name(&mut self, name: &str) {
-        if let Some(project) = self.project.as_mut() {
-            project.name = name.to_string();
+    pub fn set_project_name(&mut self, name: &str) -> HuakResult<()> {
+        if let Some(project) = self.project_table_mut() {
+            set_table_value(project, "name", name);
         }
+        self.sync()
     }
 
     /// Get the project version.
@@ -225,6 +343,33 @@ impl PyProjectToml {
         None
     }
 
+    /// Get the versions of Python the project supports from `[project].requires-python`.
+    pub fn requires_python(&self) -> HuakResult<Option<PythonRequest>> {
+        self.project
+            .as_ref()
+            .and_then(|it| it.requires_python.as_deref())
+            .map(PythonRequest::from_str)
+            .transpose()
+    }
+
+    /// Get the package indexes configured in the `[tool.huak]` table.
+    pub fn index_config(&self) -> HuakResult<IndexConfig> {
+        let tool = self.tool_huak()?;
+        IndexConfig::new(tool.index, tool.index_pins)
+    }
+
+    /// Get the URL of the repository the project is published to from the
+    /// `[tool.huak]` table.
+    pub fn publish_url(&self) -> HuakResult<Option<String>> {
+        Ok(self.tool_huak()?.publish_url)
+    }
+
+    /// Get the `[tool.huak]` table.
+    fn tool_huak(&self) -> HuakResult<ToolHuak> {
+        let manifest: Manifest = toml_edit::de::from_document(self.document.clone())?;
+        Ok(manifest.tool.and_then(|it| it.huak).unwrap_or_default())
+    }
+
     /// Get the Python project's main dependencies.
     pub fn dependencies(&self) -> Option<&Vec<String>> {
         if let Some(project) = self.project.as_ref() {
@@ -243,54 +388,72 @@ impl PyProjectToml {
         None
     }
 
-    /// Add a Python package as a dependency to the project.
-    pub fn add_dependency(&mut self, package_str: &str) {
-        self.project.as_mut().map(|project| {
-            if let Some(dependencies) = project.dependencies.as_mut() {
-                dependencies.push(package_str.to_string());
+    /// Add a Python package as a dependency to the project. A dependency already
+    /// listed under the same name is replaced in place.
+    pub fn add_dependency(&mut self, package_str: &str) -> HuakResult<()> {
+        if let Some(project) = self.project_table_mut() {
+            let item = project
+                .entry("dependencies")
+                .or_insert(toml_edit::value(Array::new()));
+            if let Some(dependencies) = item.as_array_mut() {
+                add_dependency_to_array(dependencies, package_str);
             }
-        });
+        }
+        self.sync()
     }
 
     /// Add a Python package as a dependency to the project.
-    pub fn add_optional_dependency(&mut self, package_str: &str, group_name: &str) {
-        self.project.as_mut().map(|project| {
-            if let Some(group) = project.optional_dependencies.as_mut() {
-                if let Some(dependencies) = group.get_mut(group_name) {
-                    dependencies.push(package_str.to_string());
-                };
+    pub fn add_optional_dependency(
+        &mut self,
+        package_str: &str,
+        group_name: &str,
+    ) -> HuakResult<()> {
+        if let Some(project) = self.project_table_mut() {
+            let groups = project
+                .entry("optional-dependencies")
+                .or_insert(toml_edit::table());
+            if let Some(groups) = groups.as_table_like_mut() {
+                let item = groups
+                    .entry(group_name)
+                    .or_insert(toml_edit::value(Array::new()));
+                if let Some(dependencies) = item.as_array_mut() {
+                    add_dependency_to_array(dependencies, package_str);
+                }
             }
-        });
+        }
+        self.sync()
     }
 
     /// Remove a dependency from the project.
-    pub fn remove_dependency(&mut self, package_str: &str) {
-        self.project.as_mut().map(|project| {
-            if let Some(dependencies) = project.dependencies.as_mut() {
-                if let Some(i) = dependencies
-                    .iter()
-                    .position(|item| item.contains(package_str))
-                {
-                    dependencies.remove(i);
-                };
+    pub fn remove_dependency(&mut self, package_name: &str) -> HuakResult<()> {
+        if let Some(project) = self.project_table_mut() {
+            if let Some(dependencies) = project
+                .get_mut("dependencies")
+                .and_then(|it| it.as_array_mut())
+            {
+                remove_dependency_from_array(dependencies, package_name);
             }
-        });
+        }
+        self.sync()
     }
 
     /// Remove an optional dependency from the project.
-    pub fn remove_optional_dependency(&mut self, package_str: &str, group_name: &str) {
-        self.project.as_mut().map(|project| {
-            if let Some(group) = project.optional_dependencies.as_mut() {
-                if let Some(dependencies) = group.get_mut(group_name) {
-                    if let Some(i) = dependencies
-                        .iter()
-                        .position(|item| item.contains(package_str))
-                    {
-                        dependencies.remove(i);
-                    };
-                };
+    pub fn remove_optional_dependency(
+        &mut self,
+        package_name: &str,
+        group_name: &str,
+    ) -> HuakResult<()> {
+        if let Some(project) = self.project_table_mut() {
+            if let Some(dependencies) = project
+                .get_mut("optional-dependencies")
+                .and_then(|it| it.as_table_like_mut())
+                .and_then(|it| it.get_mut(group_name))
+                .and_then(|it| it.as_array_mut())
+            {
+                remove_dependency_from_array(dependencies, package_name);
             }
-        });
+        }
+        self.sync()
     }
 
     /// Get the scripts listed in the toml.
@@ -301,28 +464,149 @@ impl PyProjectToml {
         None
     }
 
+    /// Add an entry point to the project's scripts.
+    pub fn add_script(&mut self, name: &str, entrypoint: &str) -> HuakResult<()> {
+        if let Some(project) = self.project_table_mut() {
+            let scripts = project.entry("scripts").or_insert(toml_edit::table());
+            if let Some(scripts) = scripts.as_table_like_mut() {
+                scripts.insert(name, toml_edit::value(entrypoint));
+            }
+        }
+        self.sync()
+    }
+
     /// Save the toml contents to a filepath.
     pub fn write_file(&self, path: impl AsRef<Path>) -> HuakResult<()> {
         let string = self.to_string_pretty()?;
         Ok(std::fs::write(path, string)?)
     }
 
-    /// Convert the toml struct to a formatted String.
+    /// Convert the toml to a formatted String. The document's original formatting
+    /// is preserved.
     pub fn to_string_pretty(&self) -> HuakResult<String> {
-        Ok(toml_edit::ser::to_string_pretty(&self)?)
+        Ok(self.document.to_string())
     }
 
     /// Convert the toml to a string as-is.
     pub fn to_string(&self) -> HuakResult<String> {
-        Ok(toml_edit::ser::to_string(&self)?)
+        Ok(self.document.to_string())
     }
 }
 
+/// The parts of a pyproject.toml read by huak that aren't specified by PEPs.
+#[derive(Deserialize)]
+struct Manifest {
+    tool: Option<Tool>,
+}
+
+#[derive(Deserialize)]
+struct Tool {
+    huak: Option<ToolHuak>,
+}
+
+/// Settings from the `[tool.huak]` table.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+struct ToolHuak {
+    /// The package indexes in addition to PyPI.
+    #[serde(default)]
+    index: Vec<PackageIndex>,
+    /// The names of the indexes packages are pinned to keyed by package name.
+    #[serde(default)]
+    index_pins: BTreeMap<String, String>,
+    /// The URL of the repository the project is published to.
+    publish_url: Option<String>,
+}
+
 impl Default for PyProjectToml {
     fn default() -> Self {
-        Self {
-            inner: ProjectToml::new(default_pyproject_toml_contents())
-                .expect("could not initilize default pyproject.toml"),
+        PyProjectToml::from_contents(default_pyproject_toml_contents())
+            .expect("could not initilize default pyproject.toml")
+    }
+}
+
+/// Set a value in a toml table, keeping the whitespace and comments around the value
+/// it replaces.
+fn set_table_value(table: &mut Table, key: &str, value: &str) {
+    let mut new_value = Value::from(value);
+    if let Some(old_value) = table.get(key).and_then(|it| it.as_value()) {
+        *new_value.decor_mut() = old_value.decor().clone();
+    }
+    table.insert(key, Item::Value(new_value));
+}
+
+/// Add a dependency string to a toml array, replacing an existing dependency with the
+/// same name. New items copy the indentation of the array's last item so multi-line
+/// arrays stay multi-line.
+fn add_dependency_to_array(array: &mut Array, package_str: &str) {
+    let name = Package::from_str(package_str)
+        .map(|it| it.canonical_name)
+        .unwrap_or_else(|_| package_str.to_string());
+    let position = array.iter().position(|it| {
+        it.as_str()
+            .and_then(|s| Package::from_str(s).ok())
+            .is_some_and(|package| package.canonical_name == name)
+    });
+    match position {
+        Some(i) => {
+            let mut value = Value::from(package_str);
+            if let Some(old_value) = array.get(i) {
+                *value.decor_mut() = old_value.decor().clone();
+            }
+            array.replace(i, value);
+        }
+        None => {
+            // Only the indentation is copied so comments aren't duplicated.
+            let prefix = array
+                .iter()
+                .last()
+                .and_then(|it| it.decor().prefix())
+                .and_then(|it| it.as_str())
+                .map(|it| match it.rfind('\n') {
+                    Some(i) => it[i..].to_string(),
+                    None => it.to_string(),
+                });
+            array.push(package_str);
+            if let (Some(prefix), Some(value)) = (prefix, array.iter_mut().last()) {
+                value.decor_mut().set_prefix(prefix);
+            }
+        }
+    }
+}
+
+/// Remove every dependency with a name from a toml array.
+fn remove_dependency_from_array(array: &mut Array, package_name: &str) {
+    let name = canonical_package_name(package_name);
+    let mut i = 0;
+    while i < array.len() {
+        let matches = array
+            .get(i)
+            .and_then(|it| it.as_str())
+            .and_then(|s| Package::from_str(s).ok())
+            .is_some_and(|package| package.canonical_name == name);
+        if !matches {
+            i += 1;
+            continue;
+        }
+        let prefix = array
+            .get(i)
+            .and_then(|it| it.decor().prefix())
+            .and_then(|it| it.as_str())
+            .map(|it| it.to_string());
+        array.remove(i);
+        // The first item of an array doesn't carry the separating whitespace, so the
+        // new first item takes the removed item's indentation unless it has comments.
+        if i == 0 {
+            if let (Some(prefix), Some(value)) = (prefix, array.get_mut(0)) {
+                let whitespace = value
+                    .decor()
+                    .prefix()
+                    .and_then(|it| it.as_str())
+                    .map_or(true, |it| it.trim().is_empty());
+                if whitespace {
+                    value.decor_mut().set_prefix(prefix);
+                }
+            }
         }
     }
 }
@@ -331,6 +615,42 @@ pub fn default_pyproject_toml_contents() -> &'static str {
     DEFAULT_PYPROJECT_TOML_CONTENTS
 }
 
+/// Create a unique temporary directory.
+pub(crate) fn create_temp_dir(prefix: &str) -> HuakResult<PathBuf> {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join("huak").join(format!(
+        "{prefix}-{}-{}",
+        std::process::id(),
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    ));
+    if path.exists() {
+        std::fs::remove_dir_all(&path)?;
+    }
+    std::fs::create_dir_all(&path)?;
+    Ok(path)
+}
+
+/// Normalize a package name as specified in PEP 503. Runs of `-`, `_`, and `.` are
+/// replaced with a single `-` and the name is lowercased.
+fn canonical_package_name(name: &str) -> String {
+    let mut canonical_name = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !canonical_name.ends_with('-') {
+                canonical_name.push('-');
+            }
+        } else {
+            canonical_name.push(c.to_ascii_lowercase());
+        }
+    }
+    canonical_name
+}
+
+/// Convert a project name to the name its Python package is imported with.
+fn importable_package_name(name: &str) -> String {
+    name.to_lowercase().replace(['-', '.'], "_")
+}
+
 /// A PEP-compliant Python environment API.
 ///
 /// Python environments contain the following:
@@ -342,20 +662,25 @@ pub fn default_pyproject_toml_contents() -> &'static str {
 ///        ├── some_pkg
 ///        └── some_pkg-X.X.X.dist-info
 ///   pyvenv.cfg
-#[derive(Default)]
 pub struct VirtualEnvironment {
     /// Absolute path to the root of the virtual environment directory.
     root: PathBuf,
     /// The installer the virtual environment uses to install python packages.
     installer: Installer,
+    /// The virtual environment's pyvenv.cfg data.
+    config: VirtualEnvironmentConfig,
+    /// Where downloads and installs are reported.
+    progress: Progress,
 }
 
 impl VirtualEnvironment {
     /// Create a new virtual environment.
     pub fn new() -> VirtualEnvironment {
         VirtualEnvironment {
-            root: PathBuf::new(),
+            root: PathBuf::from(DEFAULT_VENV_NAME),
             installer: Installer::new(),
+            config: VirtualEnvironmentConfig::default(),
+            progress: Progress::hidden(),
         }
     }
 
@@ -364,22 +689,83 @@ impl VirtualEnvironment {
         self.root.as_ref()
     }
 
-    /// Create a virtual environment from its root path.
+    /// Create a virtual environment from its root path. The environment's pyvenv.cfg
+    /// file is read for its configuration.
     pub fn from_path(path: impl AsRef<Path>) -> HuakResult<VirtualEnvironment> {
+        let root = path.as_ref();
+        let cfg_path = root.join("pyvenv.cfg");
+        if !cfg_path.exists() {
+            return Err(HuakError::VenvNotFoundError);
+        }
+        Ok(VirtualEnvironment {
+            root: root.to_path_buf(),
+            config: VirtualEnvironmentConfig::from_path(cfg_path)?,
+            ..Default::default()
+        })
+    }
+
+    /// Create a virtual environment at a root path that uses a base Python interpreter.
+    /// The virtual environment isn't written to the system until `write_venv` is used.
+    pub fn from_base_python_path(
+        root: impl AsRef<Path>,
+        python_path: impl AsRef<Path>,
+    ) -> HuakResult<VirtualEnvironment> {
+        let base_python = venv::query_base_python(python_path)?;
+        let config = VirtualEnvironmentConfig {
+            home: base_python
+                .executable
+                .parent()
+                .ok_or(HuakError::PythonNotFoundError)?
+                .to_path_buf(),
+            include_system_site_packages: false,
+            version: Some(base_python.version),
+            executable: Some(base_python.executable),
+            ..Default::default()
+        };
         Ok(VirtualEnvironment {
-            root: path.as_ref().to_path_buf(),
+            root: root.as_ref().to_path_buf(),
+            config,
             ..Default::default()
         })
     }
 
     /// Get the python environment config.
-    fn python_environment_config(&self) -> VirtualEnvironmentConfig {
-        todo!()
+    fn python_environment_config(&self) -> &VirtualEnvironmentConfig {
+        &self.config
     }
 
-    /// Create a Python virtual environment on the system.
+    /// Create a Python virtual environment on the system. The environment's
+    /// interpreter is linked to its base interpreter, so neither the `venv` nor
+    /// `ensurepip` modules are needed.
     pub fn write_venv(&self) -> HuakResult<()> {
-        todo!()
+        let executable = self
+            .config
+            .executable
+            .as_ref()
+            .ok_or(HuakError::PythonNotFoundError)?;
+        let version = self
+            .config
+            .version
+            .as_ref()
+            .ok_or(HuakError::PythonNotFoundError)?;
+        std::fs::create_dir_all(self.executables_dir_path())?;
+        std::fs::create_dir_all(self.site_packages_dir_path())?;
+        // Some 64-bit posix interpreters look for packages in lib64.
+        #[cfg(all(unix, target_pointer_width = "64", not(target_os = "macos")))]
+        {
+            let lib64 = self.root.join("lib64");
+            if lib64.symlink_metadata().is_err() {
+                std::os::unix::fs::symlink("lib", lib64)?;
+            }
+        }
+        std::fs::write(self.root.join("pyvenv.cfg"), self.config.to_string())?;
+        venv::link_python_executables(&self.executables_dir_path(), executable, version)?;
+        let root = std::fs::canonicalize(&self.root)?;
+        let prompt = root
+            .file_name()
+            .and_then(|it| it.to_str())
+            .unwrap_or(DEFAULT_VENV_NAME);
+        venv::write_activation_scripts(&root, &self.executables_dir_path(), prompt)
     }
 
     /// The absolute path to the Python environment's python interpreter binary.
@@ -393,18 +779,57 @@ impl VirtualEnvironment {
 
     /// The version of the Python environment's Python interpreter.
     pub fn python_version(&self) -> Option<Version> {
-        self.python_environment_config().version
+        self.python_environment_config().version.clone()
     }
 
     /// The absolute path to the Python interpreter used to create the Python
     /// environment.
     pub fn base_python_path(&self) -> PathBuf {
-        todo!()
+        if let Some(executable) = self.config.executable.as_ref() {
+            return executable.clone();
+        }
+        #[cfg(windows)]
+        let file_names = vec!["python.exe".to_string()];
+        #[cfg(unix)]
+        let file_names = {
+            let mut file_names = Vec::new();
+            if let Some(name) = self.config.version.as_ref().map(python_lib_dir_name) {
+                file_names.push(name);
+            }
+            file_names.extend(["python3".to_string(), "python".to_string()]);
+            file_names
+        };
+        let home = self.base_executables_dir_path();
+        file_names
+            .iter()
+            .map(|it| home.join(it))
+            .find(|it| it.exists())
+            .unwrap_or_else(|| home.join(&file_names[0]))
     }
 
     /// The version of the Python interpreter used to create the Python environment.
-    pub fn base_python_version(&self) -> &Version {
-        todo!()
+    pub fn base_python_version(&self) -> Option<&Version> {
+        self.config.version.as_ref()
+    }
+
+    /// Check that the environment's Python satisfies a project's `requires-python`.
+    /// The environment's interpreter is queried for its version if pyvenv.cfg doesn't
+    /// record one.
+    pub fn check_requires_python(&self, requires_python: &PythonRequest) -> HuakResult<()> {
+        let version = match self.python_version() {
+            Some(it) => it,
+            None => PythonInterpreter::from_path(self.python_path())?
+                .version()
+                .clone(),
+        };
+        if requires_python.contains(&version) {
+            return Ok(());
+        }
+        Err(HuakError::RequiresPythonMismatchError(
+            version.to_string(),
+            requires_python.to_string(),
+            describe_python_paths(Platform::new().python_paths()),
+        ))
     }
 
     /// The absolute path to the Python environment's executables directory.
@@ -418,54 +843,312 @@ impl VirtualEnvironment {
 
     /// The absolute path to the system's executables directory.
     pub fn base_executables_dir_path(&self) -> &PathBuf {
-        todo!()
+        &self.config.home
     }
 
     /// The absolute path to the Python environment's site-packages directory.
-    pub fn site_packages_dir_path(&self) -> &PathBuf {
-        todo!()
+    pub fn site_packages_dir_path(&self) -> PathBuf {
+        #[cfg(windows)]
+        let path = self.root.join("Lib").join("site-packages");
+        #[cfg(unix)]
+        let path = {
+            let lib = self.root.join("lib");
+            // Without a version the existing lib/pythonX.Y directory is used.
+            let dir_name = match self.config.version.as_ref() {
+                Some(version) => python_lib_dir_name(version),
+                None => std::fs::read_dir(&lib)
+                    .ok()
+                    .and_then(|entries| {
+                        entries
+                            .filter_map(|entry| entry.ok())
+                            .filter_map(|entry| entry.file_name().into_string().ok())
+                            .find(|name| name.starts_with("python"))
+                    })
+                    .unwrap_or_else(|| "python3".to_string()),
+            };
+            lib.join(dir_name).join("site-packages")
+        };
+        path
     }
 
     /// The absolute path to the system's site-packages directory.
-    pub fn base_site_packages_dir_path(&self) -> &PathBuf {
-        todo!()
+    pub fn base_site_packages_dir_path(&self) -> PathBuf {
+        #[cfg(windows)]
+        let path = self.config.home.join("Lib").join("site-packages");
+        #[cfg(unix)]
+        let path = {
+            // The base prefix is the parent of the base interpreter's bin directory.
+            let prefix = self.config.home.parent().unwrap_or(&self.config.home);
+            let dir_name = self
+                .config
+                .version
+                .as_ref()
+                .map(python_lib_dir_name)
+                .unwrap_or_else(|| "python3".to_string());
+            prefix.join("lib").join(dir_name).join("site-packages")
+        };
+        path
+    }
+
+    /// Get the marker environment of the Python environment's interpreter.
+    pub fn marker_environment(&self) -> HuakResult<MarkerEnvironment> {
+        MarkerEnvironment::from_python_path(self.python_path())
+    }
+
+    /// Get the compatibility tags of the environment's interpreter.
+    pub fn supported_tags(&self) -> HuakResult<SupportedTags> {
+        SupportedTags::from_marker_environment(&self.marker_environment()?)
+    }
+
+    /// Install many Python packages to the environment. Packages already installed
+    /// with a version satisfying their specifiers are skipped. The packages' wheels
+    /// are downloaded concurrently before any of them are installed. The packages that
+    /// were installed are returned. Packages named by `requested` are marked as
+    /// requested by the user rather than installed as dependencies.
+    /// Packages that aren't locked can only be verified with hashes from the index
+    /// serving them, so installing them is an error if hashes are required.
+    pub fn install_packages(
+        &mut self,
+        packages: &[Package],
+        requested: &[&str],
+    ) -> HuakResult<Vec<Package>> {
+        let mut pending = Vec::new();
+        for package in packages {
+            let installed = self
+                .find_site_packages_package(package.name())?
+                .and_then(|it| it.version().cloned());
+            let satisfied = installed.is_some_and(|version| {
+                package
+                    .version_specifiers()
+                    .iter()
+                    .all(|it| it.contains(&version))
+            });
+            if !satisfied {
+                pending.push(package.clone());
+            }
+        }
+        if pending.is_empty() {
+            return Ok(pending);
+        }
+        if self.installer.config().require_hashes() {
+            return Err(HuakError::RequireHashesUnlockedError(
+                pending[0].dependency_string(),
+            ));
+        }
+        let client = self.index_client()?;
+        let dir = create_temp_dir("downloads")?;
+        let result =
+            download_concurrently(&pending, |package| client.download_wheel(package, &dir))
+                .and_then(|wheel_paths| {
+                    self.install_downloaded_wheels(&pending, wheel_paths, requested)
+                });
+        let _ = std::fs::remove_dir_all(&dir);
+        result?;
+        Ok(pending)
+    }
+
+    /// Install packages pinned by a lock to the environment. Packages already
+    /// installed at their locked version are skipped. Each package is installed from
+    /// its locked wheel, and the wheel is verified against its locked hash. The
+    /// packages that were installed are returned. Packages named by `requested` are
+    /// marked as requested by the user rather than installed as dependencies.
+    /// Installed packages that aren't locked are kept. Use
+    /// `uninstall_unlocked_packages` to sync the environment with the lock.
+    pub fn install_locked_packages(
+        &mut self,
+        packages: &[&LockedPackage],
+        requested: &[&str],
+    ) -> HuakResult<Vec<Package>> {
+        let client = self.index_client()?;
+        let mut pending = Vec::new();
+        let mut artifacts = Vec::new();
+        for locked in packages {
+            let package = locked.package()?;
+            let installed = self.find_site_packages_package(package.name())?;
+            if installed.is_some_and(|it| it.version() == package.version()) {
+                continue;
+            }
+            let best = client.best_filename(locked.files().iter().map(|it| it.name.as_str()));
+            let artifact = locked
+                .files()
+                .iter()
+                .find(|it| Some(it.name.as_str()) == best)
+                .ok_or_else(|| {
+                    HuakError::PackageInstallationError(format!(
+                        "no compatible wheel is locked for {}",
+                        package.dependency_string()
+                    ))
+                })?;
+            pending.push(package);
+            artifacts.push(artifact);
+        }
+        if pending.is_empty() {
+            return Ok(pending);
+        }
+        let dir = create_temp_dir("downloads")?;
+        let result = download_concurrently(&artifacts, |artifact| {
+            client.download_wheel_artifact(artifact, &dir)
+        })
+        .and_then(|wheel_paths| self.install_downloaded_wheels(&pending, wheel_paths, requested));
+        let _ = std::fs::remove_dir_all(&dir);
+        result?;
+        Ok(pending)
+    }
+
+    /// Install the downloaded wheels of packages, replacing the versions of the
+    /// packages already installed. Every wheel is verified before any installed
+    /// package is removed, so a bad wheel doesn't leave a package uninstalled.
+    fn install_downloaded_wheels(
+        &mut self,
+        packages: &[Package],
+        wheel_paths: Vec<PathBuf>,
+        requested: &[&str],
+    ) -> HuakResult<()> {
+        let scheme = self.install_scheme();
+        let python_path = self.python_path();
+        let wheels = wheel_paths
+            .iter()
+            .map(|it| wheel::verify_wheel(it, &scheme))
+            .collect::<HuakResult<Vec<_>>>()?;
+        let bar = self.progress.add_bar(
+            "Installing",
+            "packages",
+            Some(packages.len() as u64),
+            ProgressUnit::Items,
+        );
+        for (package, wheel) in packages.iter().zip(wheels) {
+            bar.set_message(package.name());
+            if let Some(installed) = self.find_site_packages_package(package.name())? {
+                self.remove_package_from_site_packages(&installed)?;
+            }
+            let is_requested = requested
+                .iter()
+                .any(|it| canonical_package_name(it) == package.canonical_name());
+            wheel::install_verified_wheel(&wheel, &scheme, &python_path, "huak", is_requested)?;
+            bar.inc(1);
+        }
+        Ok(())
     }
 
-    /// Install many Python packages to the environment.
-    pub fn install_packages(&mut self, packages: &[Package]) -> HuakResult<()> {
-        todo!()
+    /// Install a wheel file to the environment's site-packages directory. The wheel is
+    /// marked as requested by the user.
+    pub fn install_wheel(&mut self, path: impl AsRef<Path>) -> HuakResult<()> {
+        wheel::install_wheel(
+            path.as_ref(),
+            &self.install_scheme(),
+            &self.python_path(),
+            "huak",
+            true,
+        )?;
+        Ok(())
     }
 
-    /// Uninstall many Python packages from the environment.
-    pub fn uninstall_packages(&mut self, package_names: &[&str]) -> HuakResult<()> {
-        todo!()
+    /// Get the directories the parts of a wheel are installed to.
+    fn install_scheme(&self) -> wheel::InstallScheme {
+        let site_packages = self.site_packages_dir_path();
+        #[cfg(windows)]
+        let headers = self.root.join("Include");
+        #[cfg(unix)]
+        let headers = {
+            let dir_name = self
+                .config
+                .version
+                .as_ref()
+                .map(python_lib_dir_name)
+                .unwrap_or_else(|| "python3".to_string());
+            self.root.join("include").join("site").join(dir_name)
+        };
+        wheel::InstallScheme {
+            purelib: site_packages.clone(),
+            platlib: site_packages,
+            scripts: self.executables_dir_path(),
+            headers,
+            data: self.root.clone(),
+        }
+    }
+
+    /// Uninstall many Python packages from the environment. Packages that aren't
+    /// installed are skipped. The paths of the removed files are returned. If the
+    /// installer is configured for a dry run the files that would be removed are
+    /// returned instead.
+    pub fn uninstall_packages(&mut self, package_names: &[&str]) -> HuakResult<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        for name in package_names {
+            if let Some(package) = self.find_site_packages_package(name)? {
+                paths.extend(self.remove_package_from_site_packages(&package)?);
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Uninstall every installed package that isn't one of some locked packages, so
+    /// that the environment contains exactly the locked packages once they're
+    /// installed. The packages that were uninstalled are returned. If the installer is
+    /// configured for a dry run the packages that would be uninstalled are returned
+    /// instead.
+    pub fn uninstall_unlocked_packages(
+        &mut self,
+        packages: &[&LockedPackage],
+    ) -> HuakResult<Vec<Package>> {
+        let unlocked = self
+            .installed_packages()?
+            .into_iter()
+            .filter(|installed| {
+                !packages
+                    .iter()
+                    .any(|it| canonical_package_name(it.name()) == installed.canonical_name())
+            })
+            .collect::<Vec<_>>();
+        for package in unlocked.iter() {
+            self.remove_package_from_site_packages(package)?;
+        }
+        Ok(unlocked)
     }
 
     /// Get a package from the site-packages directory if it is already installed.
-    pub fn find_site_packages_package(&self, name: &str) -> Option<Package> {
-        todo!()
+    pub fn find_site_packages_package(&self, name: &str) -> HuakResult<Option<Package>> {
+        Ok(self
+            .find_site_packages_dist_info(name)?
+            .map(|it| it.package()))
     }
 
     /// Get a package's dist info from the site-packages directory if it is there.
-    pub fn find_site_packages_dist_info(&self, name: &str) -> Option<DistInfo> {
-        todo!()
+    /// A .dist-info directory that can't be read is an error.
+    pub fn find_site_packages_dist_info(&self, name: &str) -> HuakResult<Option<DistInfo>> {
+        find_dist_info_path(self.site_packages_dir_path(), name)
+            .map(read_installed_dist_info)
+            .transpose()
     }
 
     /// Get a package from the system's site-packages directory if it is already
     /// installed.
-    pub fn find_base_site_packages_package(&self, name: &str) -> Option<Package> {
-        todo!()
+    pub fn find_base_site_packages_package(&self, name: &str) -> HuakResult<Option<Package>> {
+        Ok(self
+            .find_base_site_packages_dist_info(name)?
+            .map(|it| it.package()))
     }
 
     /// Get a package's dist info from the system's site-packages directory if it is
-    /// there.
-    pub fn find_base_site_packages_dist_info(&self, name: &str) -> Option<DistInfo> {
-        todo!()
-    }
-
-    /// Add a package to the site-packages directory.
-    fn add_package_to_site_packages(&mut self, package: &Package) -> HuakResult<()> {
-        todo!()
+    /// there. A .dist-info directory that can't be read is an error.
+    pub fn find_base_site_packages_dist_info(&self, name: &str) -> HuakResult<Option<DistInfo>> {
+        find_dist_info_path(self.base_site_packages_dir_path(), name)
+            .map(read_installed_dist_info)
+            .transpose()
+    }
+
+    /// Get a client for the indexes the installer is configured with, picking
+    /// wheels compatible with the environment.
+    fn index_client(&self) -> HuakResult<PackageIndexClient> {
+        let config = self.installer.config();
+        let mut client = PackageIndexClient::new();
+        client
+            .with_index_config(config.index_config().clone())
+            .with_offline(config.offline())
+            .with_require_hashes(config.require_hashes())
+            .with_supported_tags(self.supported_tags()?)
+            .with_build_python(self.python_path())
+            .with_progress(self.progress.clone());
+        Ok(client)
     }
 
     /// Add a package to the system's site-packages directory.
@@ -473,9 +1156,19 @@ impl VirtualEnvironment {
         todo!()
     }
 
-    /// Remove a package from the site-packages directory.
-    fn remove_package_from_site_packages(&mut self, package: &Package) -> HuakResult<()> {
-        todo!()
+    /// Remove a package from the site-packages directory using the RECORD of its
+    /// .dist-info directory.
+    fn remove_package_from_site_packages(&mut self, package: &Package) -> HuakResult<Vec<PathBuf>> {
+        let dist_info_path = find_dist_info_path(self.site_packages_dir_path(), package.name())
+            .ok_or_else(|| {
+                HuakError::PackageInstallationError(format!("{} is not installed", package.name()))
+            })?;
+        wheel::uninstall_dist_info(
+            &dist_info_path,
+            &self.install_scheme(),
+            &self.root,
+            self.installer.config().dry_run(),
+        )
     }
 
     /// Remove a package from the system's site-packages directory.
@@ -485,18 +1178,28 @@ impl VirtualEnvironment {
 
     /// Check if the Python environment is isolated from any system site-packages
     /// directory.
-    pub fn is_isolated(&self) -> &bool {
-        todo!()
+    pub fn is_isolated(&self) -> bool {
+        !self.config.include_system_site_packages
     }
 
     /// Activate the Python environment with a given terminal.
     pub fn activate_with_terminal(&self, terminal: &mut Terminal) -> HuakResult<()> {
-        todo!()
+        let mut paths = sys::env_path_values();
+        paths.insert(0, self.executables_dir_path());
+        let mut cmd = Command::new(sys::shell_path());
+        let cmd = cmd
+            .env("VIRTUAL_ENV", self.root())
+            .env(
+                "PATH",
+                std::env::join_paths(paths).map_err(|e| HuakError::InternalError(e.to_string()))?,
+            )
+            .env_remove("PYTHONHOME");
+        terminal.run_command(cmd)
     }
 
     /// Get all of the packages installed to the environment.
     pub fn installed_packages(&self) -> HuakResult<Vec<Package>> {
-        todo!()
+        installed_packages_in(self.site_packages_dir_path())
     }
 
     /// Get the environment's installer.
@@ -506,16 +1209,160 @@ impl VirtualEnvironment {
 
     /// Set the installer of the virtual environment.
     pub fn with_installer_config(&mut self, config: &InstallerConfig) -> &mut VirtualEnvironment {
-        self.installer.set_config(*config);
+        self.installer.set_config(config.clone());
+        self
+    }
+
+    /// Report downloads and installs to a progress display.
+    pub fn with_progress(&mut self, progress: Progress) -> &mut VirtualEnvironment {
+        self.progress = progress;
         self
     }
 }
 
+impl Default for VirtualEnvironment {
+    fn default() -> Self {
+        VirtualEnvironment::new()
+    }
+}
+
+/// Get the packages installed to a site-packages directory from its .dist-info
+/// directories. A .dist-info directory that can't be read is an error naming it.
+fn installed_packages_in(site_packages: impl AsRef<Path>) -> HuakResult<Vec<Package>> {
+    let mut packages = dist_info_paths(site_packages)
+        .map(|it| read_installed_dist_info(it).map(|it| it.package()))
+        .collect::<HuakResult<Vec<_>>>()?;
+    packages.sort_by(|a, b| a.canonical_name().cmp(b.canonical_name()));
+    Ok(packages)
+}
+
+/// Read the .dist-info directory of an installed package, reporting which directory
+/// is broken if it can't be read.
+fn read_installed_dist_info(path: PathBuf) -> HuakResult<DistInfo> {
+    DistInfo::from_path(&path).map_err(|e| HuakError::BrokenDistInfoError(path, e.to_string()))
+}
+
+/// Find the .dist-info directory of a package installed to a site-packages
+/// directory.
+fn find_dist_info_path(site_packages: impl AsRef<Path>, name: &str) -> Option<PathBuf> {
+    let canonical_name = canonical_package_name(name);
+    dist_info_paths(site_packages).find(|it| {
+        it.file_name()
+            .and_then(|it| it.to_str())
+            .and_then(|it| it.strip_suffix(".dist-info"))
+            .and_then(|it| it.rsplit_once('-'))
+            .is_some_and(|(name, _)| canonical_package_name(name) == canonical_name)
+    })
+}
+
+/// Get the paths of the .dist-info directories in a site-packages directory.
+fn dist_info_paths(site_packages: impl AsRef<Path>) -> impl Iterator<Item = PathBuf> {
+    std::fs::read_dir(site_packages)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|it| it.is_dir() && it.extension().is_some_and(|ext| ext == "dist-info"))
+}
+
 /// Search for a Python virtual environment.
 /// 1. Check PATHS. If VIRTUAL_ENV exists then a venv is active; use it.
-/// 2. Walk from CWD up searching for dir containing pyvenv.cfg.
-pub fn find_venv() -> HuakResult<VirtualEnvironment> {
-    todo!()
+/// 2. Walk from the start directory up searching for a dir containing pyvenv.cfg or
+///    for a `.venv` or `venv` dir containing one. The search stops at the first
+///    directory with a pyproject.toml (the project root) or at a filesystem boundary.
+pub fn find_venv(start_dir: impl AsRef<Path>) -> HuakResult<VirtualEnvironment> {
+    if let Some(path) = std::env::var_os("VIRTUAL_ENV").map(PathBuf::from) {
+        if path.join("pyvenv.cfg").exists() {
+            return VirtualEnvironment::from_path(path);
+        }
+    }
+    search_venv(start_dir)
+}
+
+/// Walk from a directory up searching for a virtual environment.
+fn search_venv(start_dir: impl AsRef<Path>) -> HuakResult<VirtualEnvironment> {
+    let mut dir = start_dir.as_ref().to_path_buf();
+    loop {
+        if dir.join("pyvenv.cfg").exists() {
+            return VirtualEnvironment::from_path(dir);
+        }
+        for name in [DEFAULT_VENV_NAME, "venv"] {
+            let path = dir.join(name);
+            if path.join("pyvenv.cfg").exists() {
+                return VirtualEnvironment::from_path(path);
+            }
+        }
+        if dir.join("pyproject.toml").exists() {
+            break;
+        }
+        match dir.parent() {
+            Some(parent) if fs::is_same_filesystem(&dir, parent) => dir = parent.to_path_buf(),
+            _ => break,
+        }
+    }
+    Err(HuakError::VenvNotFoundError)
+}
+
+/// Search from a directory up for the root of a project. The root is the first
+/// directory containing a pyproject.toml.
+pub fn find_project_root(start_dir: impl AsRef<Path>) -> Option<PathBuf> {
+    start_dir
+        .as_ref()
+        .ancestors()
+        .find(|it| it.join("pyproject.toml").exists())
+        .map(|it| it.to_path_buf())
+}
+
+/// Create a virtual environment named `DEFAULT_VENV_NAME` in a directory using the
+/// latest Python interpreter found on the system that satisfies a project's
+/// `requires-python`.
+pub fn create_default_venv(
+    dir: impl AsRef<Path>,
+    requires_python: Option<&PythonRequest>,
+) -> HuakResult<VirtualEnvironment> {
+    let python_path = find_python_path(requires_python)?;
+    let venv = VirtualEnvironment::from_base_python_path(
+        dir.as_ref().join(DEFAULT_VENV_NAME),
+        python_path,
+    )?;
+    venv.write_venv()?;
+    Ok(venv)
+}
+
+/// Find the latest Python interpreter satisfying a project's `requires-python` out of
+/// the interpreters found on the system and the standalone builds installed by huak.
+pub fn find_python_path(requires_python: Option<&PythonRequest>) -> HuakResult<PathBuf> {
+    let platform = Platform::new();
+    let python_path = match requires_python {
+        Some(it) => platform.python_path_matching(it),
+        None => platform.python_path_latest(),
+    };
+    match (python_path, requires_python) {
+        (Some(path), _) => Ok(path.clone()),
+        (None, Some(requires_python)) => Err(HuakError::RequiresPythonNotFoundError(
+            requires_python.to_string(),
+            describe_python_paths(platform.python_paths()),
+        )),
+        (None, None) => Err(HuakError::PythonNotFoundError),
+    }
+}
+
+/// Describe Python interpreters by their versions and paths for error messages.
+fn describe_python_paths(
+    python_paths: &HashMap<(PythonImplementation, Version), PathBuf>,
+) -> String {
+    if python_paths.is_empty() {
+        return "no interpreters".to_string();
+    }
+    let mut python_paths = python_paths.iter().collect::<Vec<_>>();
+    python_paths.sort();
+    python_paths
+        .iter()
+        .map(|((implementation, version), path)| {
+            format!("{implementation} {version} at {}", path.display())
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 /// A struct for managing installing packages.
@@ -541,17 +1388,68 @@ impl Installer {
     }
 }
 
-#[derive(Default, Copy, Clone)]
-pub struct InstallerConfig;
+#[derive(Default, Clone)]
+pub struct InstallerConfig {
+    /// Report what would change without modifying the environment.
+    dry_run: bool,
+    /// The indexes packages are installed from.
+    index_config: IndexConfig,
+    /// Only install packages from the cache.
+    offline: bool,
+    /// Only install locked packages with a hash to verify them with.
+    require_hashes: bool,
+}
 
 impl InstallerConfig {
     pub fn new() -> InstallerConfig {
-        InstallerConfig
+        InstallerConfig {
+            dry_run: false,
+            index_config: IndexConfig::default(),
+            offline: false,
+            require_hashes: false,
+        }
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    pub fn with_dry_run(&mut self, dry_run: bool) -> &mut InstallerConfig {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn index_config(&self) -> &IndexConfig {
+        &self.index_config
+    }
+
+    pub fn with_index_config(&mut self, index_config: IndexConfig) -> &mut InstallerConfig {
+        self.index_config = index_config;
+        self
+    }
+
+    pub fn offline(&self) -> bool {
+        self.offline
+    }
+
+    pub fn with_offline(&mut self, offline: bool) -> &mut InstallerConfig {
+        self.offline = offline;
+        self
+    }
+
+    pub fn require_hashes(&self) -> bool {
+        self.require_hashes
+    }
+
+    pub fn with_require_hashes(&mut self, require_hashes: bool) -> &mut InstallerConfig {
+        self.require_hashes = require_hashes;
+        self
     }
 }
 
 /// Data about some environment's Python configuration. This abstraction is modeled after
 /// the pyenv.cfg file used for Python virtual environments.
+#[derive(Debug, Clone)]
 pub struct VirtualEnvironmentConfig {
     /// Path to directory containing the Python installation used to create the
     /// environment.
@@ -561,12 +1459,129 @@ pub struct VirtualEnvironmentConfig {
     include_system_site_packages: bool,
     // The version of the environment's Python interpreter.
     version: Option<Version>,
+    /// Whether `version` was taken from virtualenv's version_info entry because the
+    /// file has no version entry.
+    version_from_info: bool,
+    /// Path to the Python interpreter used to create the environment.
+    executable: Option<PathBuf>,
+    /// The command used to create the environment.
+    command: Option<String>,
+    /// Any other entries in the order they were read.
+    other_entries: Vec<(String, String)>,
 }
 
-impl ToString for VirtualEnvironmentConfig {
-    /// Convert the `VirtualEnvironmentConfig` to str.
-    fn to_string(&self) -> String {
-        todo!()
+impl VirtualEnvironmentConfig {
+    /// Read a `VirtualEnvironmentConfig` from a pyvenv.cfg file.
+    pub fn from_path(path: impl AsRef<Path>) -> HuakResult<VirtualEnvironmentConfig> {
+        let contents = std::fs::read_to_string(path)?;
+        VirtualEnvironmentConfig::from_str(&contents)
+    }
+
+    /// Get the value of an entry that isn't modeled by the config.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.other_entries
+            .iter()
+            .find(|(it, _)| it == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl FromStr for VirtualEnvironmentConfig {
+    type Err = HuakError;
+
+    /// Parse pyvenv.cfg contents. Keys are matched case-insensitively like the
+    /// interpreter's `site` module does.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = VirtualEnvironmentConfig::default();
+        let mut home = None;
+        let mut version_info = None;
+        for (i, line) in s.lines().enumerate() {
+            let error = |message: &str| {
+                HuakError::VenvInvalidConfigFile(format!("line {}: {message}", i + 1))
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error(&format!("expected `key = value`, found `{line}`")))?;
+            let (key, value) = (key.trim().to_lowercase(), value.trim());
+            if key.is_empty() {
+                return Err(error("expected a key before `=`"));
+            }
+            match key.as_str() {
+                "home" => home = Some(PathBuf::from(value)),
+                "include-system-site-packages" => {
+                    config.include_system_site_packages = match value.to_lowercase().as_str() {
+                        "true" => true,
+                        "false" => false,
+                        _ => {
+                            return Err(error(&format!("expected true or false, found `{value}`")))
+                        }
+                    }
+                }
+                "version" => {
+                    config.version = Some(
+                        Version::from_str(value)
+                            .map_err(|e| error(&format!("invalid version `{value}`: {e}")))?,
+                    )
+                }
+                "version_info" => {
+                    // virtualenv writes versions like `3.11.2.final.0`.
+                    let release = value
+                        .split('.')
+                        .take_while(|it| it.parse::<u64>().is_ok())
+                        .collect::<Vec<_>>()
+                        .join(".");
+                    version_info = Some(
+                        Version::from_str(&release)
+                            .map_err(|e| error(&format!("invalid version `{value}`: {e}")))?,
+                    );
+                    config.other_entries.push((key, value.to_string()));
+                }
+                "executable" => config.executable = Some(PathBuf::from(value)),
+                "command" => config.command = Some(value.to_string()),
+                _ => config.other_entries.push((key, value.to_string())),
+            }
+        }
+        config.home = home.ok_or_else(|| {
+            HuakError::VenvInvalidConfigFile("missing required key `home`".to_string())
+        })?;
+        if config.version.is_none() {
+            config.version_from_info = version_info.is_some();
+            config.version = version_info;
+        }
+        Ok(config)
+    }
+}
+
+impl std::fmt::Display for VirtualEnvironmentConfig {
+    /// Write the `VirtualEnvironmentConfig` in the pyvenv.cfg format.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "home = {}", self.home.display())?;
+        writeln!(
+            f,
+            "include-system-site-packages = {}",
+            self.include_system_site_packages
+        )?;
+        // A version taken from virtualenv's version_info entry is only written back
+        // as that entry, which is kept with the other entries.
+        if let Some(version) = self.version.as_ref() {
+            if !self.version_from_info {
+                writeln!(f, "version = {version}")?;
+            }
+        }
+        if let Some(executable) = self.executable.as_ref() {
+            writeln!(f, "executable = {}", executable.display())?;
+        }
+        if let Some(command) = self.command.as_ref() {
+            writeln!(f, "command = {command}")?;
+        }
+        for (key, value) in &self.other_entries {
+            writeln!(f, "{key} = {value}")?;
+        }
+        Ok(())
     }
 }
 
@@ -576,10 +1591,22 @@ impl Default for VirtualEnvironmentConfig {
             home: Default::default(),
             include_system_site_packages: Default::default(),
             version: None,
+            version_from_info: false,
+            executable: None,
+            command: None,
+            other_entries: Vec::new(),
         }
     }
 }
 
+/// The name of the lib directory an interpreter's packages are installed under, like
+/// `python3.11`.
+fn python_lib_dir_name(version: &Version) -> String {
+    let major = version.release.first().copied().unwrap_or(3);
+    let minor = version.release.get(1).copied().unwrap_or(0);
+    format!("python{major}.{minor}")
+}
+
 /// The python package compliant with packaging.python.og.
 #[derive(Clone)]
 pub struct Package {
@@ -587,13 +1614,17 @@ pub struct Package {
     name: String,
     /// Normalized name of the Python package.
     canonical_name: String,
+    /// Optional features of the package requested with it.
+    extras: Vec<String>,
     /// The package's core metadata.
     /// https://packaging.python.org/en/latest/specifications/core-metadata/
     core_metadata: PackageMetadata,
-    /// The PEP 440 version of the package.
-    version: Version,
-    /// The PEP 400 version operator.
-    version_operator: VersionOperator,
+    /// The PEP 440 version specifiers the package's version must satisfy.
+    version_specifiers: Vec<VersionSpecifier>,
+    /// A direct URL reference to the package.
+    url: Option<String>,
+    /// The PEP 508 environment markers the package is requested under.
+    markers: Option<MarkerTree>,
     /// Tags used to indicate platform compatibility.
     platform_tags: Vec<PlatformTag>,
 }
@@ -604,28 +1635,105 @@ impl Package {
         self.name.as_ref()
     }
 
-    /// Get the pacakge's PEP440 version operator.
-    pub fn version_operator(&self) -> &VersionOperator {
-        &self.version_operator
+    /// Get the normalized name of the package.
+    /// See https://peps.python.org/pep-0503/#normalized-names
+    pub fn canonical_name(&self) -> &str {
+        self.canonical_name.as_ref()
+    }
+
+    /// Get the extras requested with the package.
+    pub fn extras(&self) -> &[String] {
+        &self.extras
+    }
+
+    /// Get the package's PEP 440 version specifiers.
+    pub fn version_specifiers(&self) -> &[VersionSpecifier] {
+        &self.version_specifiers
+    }
+
+    /// Get the pacakge's PEP440 version operator. If the package has many version
+    /// specifiers the first specifier's operator is used.
+    pub fn version_operator(&self) -> Option<&VersionOperator> {
+        self.version_specifiers.first().map(|it| it.operator())
+    }
+
+    /// Get the package's PEP440 version. If the package has many version specifiers
+    /// the first specifier's version is used.
+    pub fn version(&self) -> Option<&Version> {
+        self.version_specifiers.first().map(|it| it.version())
+    }
+
+    /// Get the direct URL reference to the package if it has one.
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// Get the package's core metadata.
+    pub fn core_metadata(&self) -> &PackageMetadata {
+        &self.core_metadata
+    }
+
+    /// Get the package's environment markers if it has any.
+    pub fn markers(&self) -> Option<&MarkerTree> {
+        self.markers.as_ref()
+    }
+
+    /// Get the platforms the package is compatible with. Only installed packages
+    /// know their platforms.
+    pub fn platform_tags(&self) -> &[PlatformTag] {
+        &self.platform_tags
     }
 
-    /// Get the package's PEP440 version.
-    pub fn version(&self) -> &Version {
-        &self.version
+    /// Check if the package is required in an environment when the given extras
+    /// are requested. Packages without markers always apply.
+    pub fn applies_to(&self, environment: &MarkerEnvironment, extras: &[&str]) -> bool {
+        self.markers
+            .as_ref()
+            .map_or(true, |it| it.evaluate(environment, extras))
     }
 
     /// Get the pacakge name with its version specifier as a &str.
     pub fn dependency_string(&self) -> String {
-        format!("{}{}", self.name(), self.version_operator())
+        let mut string = self.name.clone();
+        if !self.extras.is_empty() {
+            string.push_str(&format!("[{}]", self.extras.join(",")));
+        }
+        let specifiers = self
+            .version_specifiers
+            .iter()
+            .map(|it| it.to_string())
+            .collect::<Vec<_>>();
+        string.push_str(&specifiers.join(","));
+        if let Some(url) = self.url.as_ref() {
+            string.push_str(&format!(" @ {url}"));
+        }
+        if let Some(markers) = self.markers.as_ref() {
+            // Whitespace is required to separate a URL from its markers.
+            match self.url.is_some() {
+                true => string.push_str(&format!(" ; {markers}")),
+                false => string.push_str(&format!("; {markers}")),
+            }
+        }
+        string
     }
 }
 
 impl FromStr for Package {
     type Err = HuakError;
 
-    /// Create a Python package from str.
+    /// Create a Python package from a PEP 508 dependency specifier str.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        todo!()
+        let requirement = Requirement::from_str(s)?;
+        Ok(Package {
+            canonical_name: canonical_package_name(&requirement.name),
+            name: requirement.name,
+            extras: requirement.extras,
+            core_metadata: PackageMetadata::default(),
+            version_specifiers: requirement.version_specifiers,
+            url: requirement.url,
+            markers: requirement.markers,
+            platform_tags: Vec::new(),
+        })
     }
 }
 
@@ -633,9 +1741,11 @@ impl PartialEq for Package {
     fn eq(&self, other: &Self) -> bool {
         self.name == other.name
             && self.canonical_name == other.canonical_name
+            && self.extras == other.extras
             && self.core_metadata == other.core_metadata
-            && self.version == other.version
-            && self.version_operator == other.version_operator
+            && self.version_specifiers == other.version_specifiers
+            && self.url == other.url
+            && self.markers == other.markers
             && self.platform_tags == other.platform_tags
     }
 }
@@ -644,142 +1754,1053 @@ impl Eq for Package {}
 
 /// Core package metadata.
 /// https://packaging.python.org/en/latest/specifications/core-metadata/
-#[derive(PartialEq, Eq, Clone)]
-pub struct PackageMetadata;
+#[derive(Default, PartialEq, Eq, Clone)]
+pub struct PackageMetadata {
+    /// The version of the metadata format.
+    metadata_version: String,
+    /// The name of the distribution.
+    name: String,
+    /// The version of the distribution.
+    version: Option<Version>,
+    /// Platforms the distribution supports.
+    platforms: Vec<String>,
+    /// Binary platforms the distribution was built for.
+    supported_platforms: Vec<String>,
+    /// A one-line summary of the distribution.
+    summary: Option<String>,
+    /// A longer description of the distribution.
+    description: Option<String>,
+    /// The markup of the description such as `text/markdown`.
+    description_content_type: Option<String>,
+    /// Keywords used to search for the distribution.
+    keywords: Vec<String>,
+    home_page: Option<String>,
+    download_url: Option<String>,
+    author: Option<String>,
+    author_email: Option<String>,
+    maintainer: Option<String>,
+    maintainer_email: Option<String>,
+    license: Option<String>,
+    /// Paths to license files included with the distribution.
+    license_files: Vec<String>,
+    /// Trove classifiers.
+    classifiers: Vec<String>,
+    /// The distributions the distribution depends on.
+    requires_dist: Vec<Package>,
+    /// The Python versions the distribution supports.
+    requires_python: Vec<VersionSpecifier>,
+    /// Dependencies on software that isn't distributed as a Python package.
+    requires_external: Vec<String>,
+    /// Labeled URLs as `(label, url)` pairs.
+    project_urls: Vec<(String, String)>,
+    /// The optional features of the distribution.
+    provides_extra: Vec<String>,
+    provides_dist: Vec<String>,
+    obsoletes_dist: Vec<String>,
+    /// Fields the metadata left to be filled in when the distribution is built.
+    dynamic: Vec<String>,
+}
 
-/// Tags used to indicate platform compatibility.
-/// https://packaging.python.org/en/latest/specifications/platform-compatibility-tags/
-#[derive(PartialEq, Eq, Clone)]
-pub enum PlatformTag {}
+impl PackageMetadata {
+    /// Read package metadata from a METADATA or PKG-INFO file.
+    pub fn from_path(path: impl AsRef<Path>) -> HuakResult<PackageMetadata> {
+        let contents = std::fs::read_to_string(path)?;
+        PackageMetadata::from_str(&contents)
+    }
+
+    pub fn metadata_version(&self) -> &str {
+        &self.metadata_version
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version(&self) -> Option<&Version> {
+        self.version.as_ref()
+    }
+
+    pub fn platforms(&self) -> &[String] {
+        &self.platforms
+    }
+
+    pub fn supported_platforms(&self) -> &[String] {
+        &self.supported_platforms
+    }
+
+    pub fn summary(&self) -> Option<&str> {
+        self.summary.as_deref()
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn description_content_type(&self) -> Option<&str> {
+        self.description_content_type.as_deref()
+    }
+
+    pub fn keywords(&self) -> &[String] {
+        &self.keywords
+    }
+
+    pub fn home_page(&self) -> Option<&str> {
+        self.home_page.as_deref()
+    }
+
+    pub fn download_url(&self) -> Option<&str> {
+        self.download_url.as_deref()
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub fn author_email(&self) -> Option<&str> {
+        self.author_email.as_deref()
+    }
+
+    pub fn maintainer(&self) -> Option<&str> {
+        self.maintainer.as_deref()
+    }
+
+    pub fn maintainer_email(&self) -> Option<&str> {
+        self.maintainer_email.as_deref()
+    }
+
+    pub fn license(&self) -> Option<&str> {
+        self.license.as_deref()
+    }
+
+    pub fn license_files(&self) -> &[String] {
+        &self.license_files
+    }
+
+    pub fn classifiers(&self) -> &[String] {
+        &self.classifiers
+    }
+
+    pub fn requires_dist(&self) -> &[Package] {
+        &self.requires_dist
+    }
+
+    pub fn requires_python(&self) -> &[VersionSpecifier] {
+        &self.requires_python
+    }
+
+    pub fn requires_external(&self) -> &[String] {
+        &self.requires_external
+    }
+
+    pub fn project_urls(&self) -> &[(String, String)] {
+        &self.project_urls
+    }
+
+    pub fn provides_extra(&self) -> &[String] {
+        &self.provides_extra
+    }
+
+    pub fn provides_dist(&self) -> &[String] {
+        &self.provides_dist
+    }
+
+    pub fn obsoletes_dist(&self) -> &[String] {
+        &self.obsoletes_dist
+    }
+
+    pub fn dynamic(&self) -> &[String] {
+        &self.dynamic
+    }
+}
+
+impl FromStr for PackageMetadata {
+    type Err = HuakError;
+
+    /// Parse core metadata versions 1.0 through 2.3.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (headers, body) = metadata::parse_headers(s)?;
+        let mut data = PackageMetadata::default();
+        for (key, value) in headers {
+            // Field names are case-insensitive.
+            match key.to_ascii_lowercase().as_str() {
+                "metadata-version" => data.metadata_version = value,
+                "name" => data.name = value,
+                "version" => {
+                    data.version = Some(
+                        Version::from_str(&value)
+                            .map_err(|e| HuakError::PackageMetadataError(e.to_string()))?,
+                    )
+                }
+                "platform" => data.platforms.push(value),
+                "supported-platform" => data.supported_platforms.push(value),
+                "summary" => data.summary = Some(value),
+                "description" => data.description = Some(value),
+                "description-content-type" => data.description_content_type = Some(value),
+                "keywords" => {
+                    // Keywords were space separated before metadata 1.1.
+                    let separator = match value.contains(',') {
+                        true => ',',
+                        false => ' ',
+                    };
+                    data.keywords = value
+                        .split(separator)
+                        .map(|it| it.trim().to_string())
+                        .filter(|it| !it.is_empty())
+                        .collect();
+                }
+                "home-page" => data.home_page = Some(value),
+                "download-url" => data.download_url = Some(value),
+                "author" => data.author = Some(value),
+                "author-email" => data.author_email = Some(value),
+                "maintainer" => data.maintainer = Some(value),
+                "maintainer-email" => data.maintainer_email = Some(value),
+                "license" => data.license = Some(value),
+                "license-file" => data.license_files.push(value),
+                "classifier" => data.classifiers.push(value),
+                "requires-dist" => data.requires_dist.push(Package::from_str(&value)?),
+                "requires-python" => {
+                    data.requires_python = value
+                        .split(',')
+                        .filter(|it| !it.trim().is_empty())
+                        .map(|it| {
+                            VersionSpecifier::from_str(it.trim())
+                                .map_err(|e| HuakError::PackageMetadataError(e.to_string()))
+                        })
+                        .collect::<HuakResult<_>>()?;
+                }
+                "requires-external" => data.requires_external.push(value),
+                "project-url" => {
+                    let (label, url) = value.split_once(',').unwrap_or(("", &value));
+                    data.project_urls
+                        .push((label.trim().to_string(), url.trim().to_string()));
+                }
+                "provides-extra" => data.provides_extra.push(value),
+                "provides-dist" => data.provides_dist.push(value),
+                "obsoletes-dist" => data.obsoletes_dist.push(value),
+                "dynamic" => data.dynamic.push(value),
+                _ => (),
+            }
+        }
+        // Metadata 2.1 moved the description to the message body.
+        if body.is_some() {
+            data.description = body;
+        }
+        match data.metadata_version.split('.').next() {
+            Some("1") | Some("2") => (),
+            _ => {
+                return Err(HuakError::PackageMetadataError(format!(
+                    "unsupported Metadata-Version `{}`",
+                    data.metadata_version
+                )))
+            }
+        }
+        if data.name.is_empty() {
+            return Err(HuakError::PackageMetadataError(
+                "missing a Name field".to_string(),
+            ));
+        }
+        Ok(data)
+    }
+}
 
 /// Package distribtion info stored in the site-packages directory adjacent to the
 /// installed package artifact.
 /// https://peps.python.org/pep-0376/#one-dist-info-directory-per-installed-distribution
 pub struct DistInfo {
-    /// File containing the name of the tool used to install the package.
-    installer_file: File,
-    /// File containing the package's license information.
-    license_file: Option<File>,
-    /// File containing metadata about the package.
+    /// The path to the .dist-info directory.
+    path: PathBuf,
+    /// The name of the tool used to install the package.
+    installer: Option<String>,
+    /// Metadata about the package.
     /// See
     ///   https://peps.python.org/pep-0345/
     ///   https://peps.python.org/pep-0314/
     ///   https://peps.python.org/pep-0241/
-    metadata_file: File,
-    /// File containing each file isntalled as part of the package's installation.
+    metadata: PackageMetadata,
+    /// Each file installed as part of the package's installation.
     /// See https://peps.python.org/pep-0376/#record
-    record_file: File,
-    /// File added to the .dist-info directory of the installed distribution if the
-    /// package was explicitly requested.
+    record: Vec<RecordEntry>,
+    /// Whether the package was explicitly requested.
     /// See https://peps.python.org/pep-0376/#requested
-    requested_file: Option<File>,
-    /// File containing metadata about the archive.
-    wheel_file: Option<File>,
+    requested: bool,
+    /// Metadata about the archive the package was installed from.
+    wheel: Option<WheelMetadata>,
+    /// The entry points the package advertises.
+    entry_points: Vec<EntryPoint>,
 }
 
-/// A client used to interact with a package index.
-pub struct PackageIndexClient;
+impl DistInfo {
+    /// Read a .dist-info directory. METADATA is required; the other files are
+    /// read if they exist.
+    pub fn from_path(path: impl AsRef<Path>) -> HuakResult<DistInfo> {
+        let path = path.as_ref();
+        let read_optional = |file_name: &str| match std::fs::read_to_string(path.join(file_name)) {
+            Ok(it) => Ok(Some(it)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(HuakError::IOError(e)),
+        };
+        let metadata = PackageMetadata::from_path(path.join("METADATA"))?;
+        let record = match read_optional("RECORD")? {
+            Some(it) => wheel::parse_record(&it)?,
+            None => Vec::new(),
+        };
+        let wheel = match read_optional("WHEEL")? {
+            Some(it) => Some(WheelMetadata::from_str(&it)?),
+            None => None,
+        };
+        let entry_points = match read_optional("entry_points.txt")? {
+            Some(it) => metadata::parse_entry_points(&it)?,
+            None => Vec::new(),
+        };
+        Ok(DistInfo {
+            path: path.to_path_buf(),
+            installer: read_optional("INSTALLER")?.map(|it| it.trim().to_string()),
+            metadata,
+            record,
+            requested: path.join("REQUESTED").exists(),
+            wheel,
+            entry_points,
+        })
+    }
 
-impl PackageIndexClient {
-    pub fn new() -> PackageIndexClient {
-        PackageIndexClient
+    /// Get the path to the .dist-info directory.
+    pub fn path(&self) -> &Path {
+        &self.path
     }
 
-    pub fn query(&self, package: &Package) -> HuakResult<PackageIndexData> {
-        let url = format!("https://pypi.org/pypi/{}/json", package.name());
-        reqwest::blocking::get(url)?
-            .json()
-            .map_err(|e| HuakError::ReqwestError(e))
+    /// Get the name of the tool used to install the package.
+    pub fn installer(&self) -> Option<&str> {
+        self.installer.as_deref()
+    }
+
+    /// Get the package's core metadata.
+    pub fn metadata(&self) -> &PackageMetadata {
+        &self.metadata
+    }
+
+    /// Get the files installed as part of the package's installation.
+    pub fn record(&self) -> &[RecordEntry] {
+        &self.record
+    }
+
+    /// Check if the package was explicitly requested.
+    pub fn requested(&self) -> bool {
+        self.requested
     }
-}
 
-/// Data about a package from a package index.
-// TODO: Support more than https://pypi.org/pypi/<package name>/json
-//       Ex: See https://peps.python.org/pep-0503/
-#[derive(Serialize, Deserialize, Debug)]
-pub struct PackageIndexData {
-    pub info: PackageInfo,
-    last_serial: u64,
-    releases: serde_json::value::Value,
-    urls: Vec<serde_json::value::Value>,
-    vulnerabilities: Vec<serde_json::value::Value>,
+    /// Get metadata about the archive the package was installed from.
+    pub fn wheel(&self) -> Option<&WheelMetadata> {
+        self.wheel.as_ref()
+    }
+
+    /// Get the entry points the package advertises.
+    pub fn entry_points(&self) -> &[EntryPoint] {
+        &self.entry_points
+    }
+
+    /// Get the installed package described by the dist info.
+    pub fn package(&self) -> Package {
+        let metadata = self.metadata.clone();
+        Package {
+            canonical_name: canonical_package_name(&metadata.name),
+            name: metadata.name.clone(),
+            extras: Vec::new(),
+            version_specifiers: metadata
+                .version
+                .clone()
+                .and_then(|it| VersionSpecifier::from_version(VersionOperator::Equal, it).ok())
+                .into_iter()
+                .collect(),
+            core_metadata: metadata,
+            url: None,
+            markers: None,
+            platform_tags: self
+                .wheel
+                .as_ref()
+                .map(|it| wheel_platform_tags(it.tags()))
+                .unwrap_or_default(),
+        }
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct PackageInfo {
-    pub author: String,
-    pub author_email: String,
-    pub bugtrack_url: serde_json::value::Value,
-    pub classifiers: Vec<String>,
-    pub description: String,
-    pub description_content_type: String,
-    pub docs_url: serde_json::value::Value,
-    pub download_url: serde_json::value::Value,
-    pub downloads: serde_json::value::Value,
-    pub home_page: serde_json::value::Value,
-    pub keywords: serde_json::value::Value,
-    pub license: serde_json::value::Value,
-    pub maintainer: serde_json::value::Value,
-    pub maintainer_email: serde_json::value::Value,
-    pub name: String,
-    pub package_url: String,
-    pub platform: serde_json::value::Value,
-    pub project_url: String,
-    pub project_urls: serde_json::value::Value,
-    pub release_url: String,
-    pub requires_dist: serde_json::value::Value,
-    pub requires_python: String,
-    pub summary: String,
-    pub version: String,
-    pub yanked: bool,
-    pub yanked_reason: serde_json::value::Value,
+/// Get the platforms of a wheel's compatibility tags. Invalid tags are skipped.
+fn wheel_platform_tags(tags: &[String]) -> Vec<PlatformTag> {
+    let mut platforms = Vec::new();
+    for tag in tags
+        .iter()
+        .filter_map(|it| CompatibilityTag::expand(it).ok())
+        .flatten()
+    {
+        if !platforms.contains(&tag.platform) {
+            platforms.push(tag.platform);
+        }
+    }
+    platforms
 }
 
-/// Get a hashmap of Python interpreters. Each entry is stored with the interpreter's
-/// version as its key and the absolute path the the interpreter as the value.
-/// NOTE: This search implementation is inspired by brettcannon/python-launcher
-pub fn find_python_interpreter_paths() -> HashMap<Version, PathBuf> {
-    let paths = fs::flatten_directories(sys::env_path_values());
-    let interpreters = all_python_interpreters_in_paths(paths);
-    interpreters
+/// A client used to interact with package indexes. Packages are found using the
+/// indexes' simple repository APIs.
+pub struct PackageIndexClient {
+    /// The indexes packages are found on.
+    config: IndexConfig,
+    /// The project pages already fetched keyed by canonical name, with the index
+    /// each was found on.
+    projects: HashMap<String, (PackageIndex, SimpleProject)>,
+    /// The credentials requests to the indexes are authenticated with.
+    credentials: CredentialProvider,
+    /// The cache responses, artifacts and metadata are stored in.
+    cache: Cache,
+    /// Only use the cache instead of making requests.
+    offline: bool,
+    /// Reject artifacts without a hash.
+    require_hashes: bool,
+    /// The tags of the environment wheels are installed to. Any wheel is accepted
+    /// without tags.
+    tags: Option<SupportedTags>,
+    /// The interpreter source distributions are built with. Source distributions are
+    /// only used if it's set.
+    build_python: Option<PathBuf>,
+    /// Where downloads are reported.
+    progress: Progress,
+    /// The HTTP client every request is made with, so that connections are reused.
+    http: reqwest::blocking::Client,
 }
 
-fn all_python_interpreters_in_paths(
-    paths: impl IntoIterator<Item = PathBuf>,
-) -> HashMap<Version, PathBuf> {
-    let mut interpreters = HashMap::new();
-    paths.into_iter().for_each(|path| {
-        python_version_from_path(&path).map_or((), |version| {
-            interpreters.entry(version).or_insert(path);
+impl PackageIndexClient {
+    pub fn new() -> PackageIndexClient {
+        // Some servers, such as GitHub's API, reject requests without a user agent.
+        let http = reqwest::blocking::Client::builder()
+            .user_agent(concat!("huak/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .unwrap_or_default();
+        PackageIndexClient {
+            config: IndexConfig::default(),
+            projects: HashMap::new(),
+            credentials: CredentialProvider::new(),
+            cache: Cache::new(),
+            offline: false,
+            require_hashes: false,
+            tags: None,
+            build_python: None,
+            progress: Progress::hidden(),
+            http,
+        }
+    }
+
+    /// Get the base URL of the primary index's simple repository API.
+    pub fn url(&self) -> &str {
+        self.config.primary().url()
+    }
+
+    /// Use the simple repository API at a base URL such as
+    /// `https://pypi.org/simple` as the primary index.
+    pub fn with_url(&mut self, url: &str) -> &mut PackageIndexClient {
+        self.config.set_primary_url(url);
+        self.projects.clear();
+        self
+    }
+
+    /// Get the indexes packages are found on.
+    pub fn index_config(&self) -> &IndexConfig {
+        &self.config
+    }
+
+    /// Find packages on the indexes of a configuration.
+    pub fn with_index_config(&mut self, config: IndexConfig) -> &mut PackageIndexClient {
+        self.config = config;
+        self.projects.clear();
+        self
+    }
+
+    /// Authenticate requests with credentials from a provider.
+    pub fn with_credential_provider(
+        &mut self,
+        provider: CredentialProvider,
+    ) -> &mut PackageIndexClient {
+        self.credentials = provider;
+        self
+    }
+
+    /// Store responses, artifacts and metadata in a cache.
+    pub fn with_cache(&mut self, cache: Cache) -> &mut PackageIndexClient {
+        self.cache = cache;
+        self.projects.clear();
+        self
+    }
+
+    /// Only use the cache instead of making requests to the indexes.
+    pub fn with_offline(&mut self, offline: bool) -> &mut PackageIndexClient {
+        self.offline = offline;
+        self
+    }
+
+    /// Reject downloading artifacts that have no hash to verify them with.
+    pub fn with_require_hashes(&mut self, require_hashes: bool) -> &mut PackageIndexClient {
+        self.require_hashes = require_hashes;
+        self
+    }
+
+    /// Get the tags of the environment wheels are installed to.
+    pub fn supported_tags(&self) -> Option<&SupportedTags> {
+        self.tags.as_ref()
+    }
+
+    /// Only use wheels compatible with an environment's tags.
+    pub fn with_supported_tags(&mut self, tags: SupportedTags) -> &mut PackageIndexClient {
+        self.tags = Some(tags);
+        self
+    }
+
+    /// Build wheels from source distributions with a Python interpreter when a
+    /// package has no compatible wheel.
+    pub fn with_build_python(&mut self, python_path: impl AsRef<Path>) -> &mut PackageIndexClient {
+        self.build_python = Some(python_path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Report the bytes of each artifact downloaded to a progress display.
+    pub fn with_progress(&mut self, progress: Progress) -> &mut PackageIndexClient {
+        self.progress = progress;
+        self
+    }
+
+    /// Get a project's page from the first index that serves it. Packages pinned to
+    /// an index are only searched for on that index.
+    pub fn simple_project(&self, name: &str) -> HuakResult<SimpleProject> {
+        Ok(self.find_project(name)?.1)
+    }
+
+    /// Find the first index that serves a project and get the project's page.
+    /// Offline, an index without a cached page is an error rather than skipped, since
+    /// the package may be served by it instead of by an index with lower priority.
+    fn find_project(&self, name: &str) -> HuakResult<(PackageIndex, SimpleProject)> {
+        for index in self.config.indexes_for(name) {
+            if let Some(project) = self.fetch_project(index, name)? {
+                return Ok((index.clone(), project));
+            }
+        }
+        Err(HuakError::PackageIndexError(format!(
+            "{name} was not found on any index"
+        )))
+    }
+
+    /// Get a project's page from an index's simple repository API. JSON responses
+    /// (PEP 691) are requested and HTML responses (PEP 503) are accepted. `None` is
+    /// returned if the index doesn't serve the project.
+    fn fetch_project(&self, index: &PackageIndex, name: &str) -> HuakResult<Option<SimpleProject>> {
+        let url = format!("{}/{}/", index.url(), canonical_package_name(name));
+        let response = match self.get_cached(&url, index::SIMPLE_ACCEPT)? {
+            Some(it) => it,
+            None => return Ok(None),
+        };
+        let url = reqwest::Url::parse(&response.final_url).map_err(|e| {
+            HuakError::PackageIndexError(format!("invalid url {}: {e}", response.final_url))
+        })?;
+        SimpleProject::from_response(
+            name,
+            &url,
+            response.content_type.as_deref().unwrap_or_default(),
+            std::str::from_utf8(&response.body)?,
+        )
+        .map(Some)
+    }
+
+    /// Get a response through the cache. Fresh cached responses are used as they
+    /// are, and stale ones are revalidated with the server. `None` is returned if
+    /// the server doesn't have the resource, which is cached too. Offline, only
+    /// cached responses are used.
+    fn get_cached(&self, url: &str, accept: &str) -> HuakResult<Option<CachedResponse>> {
+        Ok(self
+            .get_cached_response(url, accept)?
+            .filter(|it| !it.not_found))
+    }
+
+    fn get_cached_response(&self, url: &str, accept: &str) -> HuakResult<Option<CachedResponse>> {
+        let key = match reqwest::Url::parse(url) {
+            Ok(it) => auth::strip_credentials(&it).to_string(),
+            Err(_) => url.to_string(),
+        };
+        // A corrupt entry is treated like a missing one and replaced.
+        let cached = self.cache.http_response(&key).ok().flatten();
+        if self.offline {
+            return cached
+                .map(Some)
+                .ok_or_else(|| HuakError::CacheMissError(auth::redact(url)));
+        }
+        if let Some(cached) = cached.as_ref().filter(|it| it.is_fresh()) {
+            return Ok(Some(cached.clone()));
+        }
+
+        let mut request = self.get(url)?.header(reqwest::header::ACCEPT, accept);
+        if let Some(cached) = cached.as_ref() {
+            for (name, value) in cached.conditional_headers() {
+                request = request.header(name, value);
+            }
+        }
+        let response = request.send()?;
+        let header = |name: reqwest::header::HeaderName| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|it| it.to_str().ok())
+                .map(|it| it.to_string())
+        };
+        let cache_control = header(reqwest::header::CACHE_CONTROL);
+        let content_type = header(reqwest::header::CONTENT_TYPE);
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+        if let (reqwest::StatusCode::NOT_MODIFIED, Some(mut cached)) = (response.status(), cached) {
+            cached.revalidate(cache_control.as_deref());
+            self.cache.store_http_response(&cached)?;
+            return Ok(Some(cached));
+        }
+        let not_found = response.status() == reqwest::StatusCode::NOT_FOUND;
+        let response = match not_found {
+            true => response,
+            false => response.error_for_status()?,
+        };
+        let fresh = CachedResponse {
+            url: key,
+            final_url: auth::strip_credentials(response.url()).to_string(),
+            content_type,
+            etag,
+            last_modified,
+            control: cache_control
+                .as_deref()
+                .map(CacheControl::parse)
+                .unwrap_or_default(),
+            stored_at: cache::now(),
+            not_found,
+            body: match not_found {
+                true => Vec::new(),
+                false => response.bytes()?.to_vec(),
+            },
+        };
+        self.cache.store_http_response(&fresh)?;
+        Ok(Some(fresh))
+    }
+
+    /// Build a GET request for a URL. Only requests to a configured index are
+    /// authenticated, using the credentials of the index the URL belongs to. Files
+    /// served from the index's host, such as `https://host/files/...` for an index at
+    /// `https://host/simple`, count as belonging to it.
+    fn get(&self, url: &str) -> HuakResult<reqwest::blocking::RequestBuilder> {
+        let url = reqwest::Url::parse(url).map_err(|e| {
+            HuakError::PackageIndexError(format!("invalid url {}: {e}", auth::redact(url)))
+        })?;
+        let stripped = auth::strip_credentials(&url);
+        let index_urls = self
+            .config
+            .indexes()
+            .iter()
+            .filter_map(|index| {
+                reqwest::Url::parse(index.url())
+                    .ok()
+                    .map(|it| (index, auth::strip_credentials(&it)))
+            })
+            .collect::<Vec<_>>();
+        let index = index_urls
+            .iter()
+            .find(|(_, it)| stripped.as_str().starts_with(it.as_str()))
+            .or_else(|| {
+                index_urls
+                    .iter()
+                    .find(|(_, it)| it.origin() == url.origin())
+            })
+            .map(|(index, _)| index);
+        let request = self.http.get(stripped);
+        let index = match index {
+            Some(it) => it,
+            None => return Ok(request),
+        };
+        let env_prefix = auth::index_env_prefix(index.name());
+        Ok(
+            match self.credentials.credentials(&url, Some(&env_prefix))? {
+                Some(credentials) => credentials.authenticate(request),
+                None => request,
+            },
+        )
+    }
+
+    /// Get a project's page, fetching it from the indexes only once.
+    fn project(&mut self, name: &str) -> HuakResult<&SimpleProject> {
+        let name = canonical_package_name(name);
+        if !self.projects.contains_key(&name) {
+            let found = self.find_project(&name)?;
+            self.projects.insert(name.clone(), found);
+        }
+        Ok(&self.projects[&name].1)
+    }
+
+    /// Get the best file the index serves for a version of a package.
+    fn installable_file(&mut self, name: &str, version: &Version) -> HuakResult<SimpleFile> {
+        let files = self
+            .project(name)?
+            .files()
+            .iter()
+            .filter(|it| it.version().as_ref() == Some(version))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.best_file(&files).cloned().ok_or_else(|| {
+            HuakError::PackageIndexError(format!("no compatible wheel found for {name}=={version}"))
         })
+    }
+
+    /// Check if a file is a wheel that can be installed, or a source distribution
+    /// that can be built.
+    fn is_installable(&self, filename: &str) -> bool {
+        let wheel = match self.tags.as_ref() {
+            Some(tags) => tags.is_compatible(filename),
+            None => WheelFilename::from_str(filename).is_ok(),
+        };
+        wheel || (self.build_python.is_some() && pep517::is_sdist(filename))
+    }
+
+    /// Pick the best file to install out of some file names. Wheels are preferred
+    /// over source distributions, and without tags universal wheels are preferred.
+    fn best_filename<'a>(&self, filenames: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+        let filenames = filenames
+            .into_iter()
+            .filter(|it| self.is_installable(it))
+            .collect::<Vec<_>>();
+        let wheel = match self.tags.as_ref() {
+            Some(tags) => tags.best_wheel(filenames.iter().copied()),
+            None => filenames
+                .iter()
+                .copied()
+                .find(|it| is_universal_wheel(it))
+                .or_else(|| filenames.iter().copied().find(|it| it.ends_with(".whl"))),
+        };
+        wheel.or_else(|| filenames.into_iter().find(|it| pep517::is_sdist(it)))
+    }
+
+    /// Pick the best file to install out of some files. Yanked files are skipped.
+    fn best_file<'a>(&self, files: &'a [SimpleFile]) -> Option<&'a SimpleFile> {
+        let files = files
+            .iter()
+            .filter(|it| !it.is_yanked())
+            .collect::<Vec<_>>();
+        let best = self.best_filename(files.iter().map(|it| it.filename()))?;
+        files.into_iter().find(|it| it.filename() == best)
+    }
+
+    /// Get the core metadata of a file. Metadata served separately by the index (PEP
+    /// 658) is preferred over downloading the file.
+    /// The metadata is cached by the file's hash, or by its URL if the index doesn't
+    /// serve a hash.
+    fn core_metadata(&self, file: &SimpleFile) -> HuakResult<PackageMetadata> {
+        let key = match file.hashes().get("sha256") {
+            Some(it) => format!("sha256:{it}"),
+            None => file.url().to_string(),
+        };
+        if let Some(metadata) = self.cache.metadata(&key)? {
+            return PackageMetadata::from_str(&metadata);
+        }
+        if self.offline {
+            return Err(HuakError::CacheMissError(format!(
+                "the metadata of {}",
+                file.filename()
+            )));
+        }
+        let metadata = match file.core_metadata_url() {
+            Some(url) => {
+                let bytes = self.get(&url)?.send()?.error_for_status()?.bytes()?;
+                let artifact = Artifact {
+                    name: format!("{}.metadata", file.filename()),
+                    url,
+                    hash: file
+                        .core_metadata_hashes()
+                        .and_then(|it| it.get("sha256"))
+                        .map(|it| format!("sha256:{it}")),
+                };
+                artifact.verify(&bytes)?;
+                std::str::from_utf8(&bytes)?.to_string()
+            }
+            None => {
+                let dir = create_temp_dir("metadata")?;
+                let metadata = self
+                    .download_wheel_artifact(&file.artifact(), &dir)
+                    .and_then(|it| wheel::read_wheel_metadata(&it));
+                let _ = std::fs::remove_dir_all(&dir);
+                metadata?
+            }
+        };
+        self.cache.store_metadata(&key, &metadata)?;
+        PackageMetadata::from_str(&metadata)
+    }
+
+    /// Download the best wheel for a package to a directory. The latest release
+    /// satisfying the package's version specifiers is used. Pre-releases are only
+    /// considered if a specifier requests one. Packages without a compatible wheel are
+    /// built from their source distribution if the client can build them.
+    pub fn download_wheel(&self, package: &Package, dir: impl AsRef<Path>) -> HuakResult<PathBuf> {
+        let project = self.simple_project(package.name())?;
+        let allow_prereleases = package
+            .version_specifiers()
+            .iter()
+            .any(|it| it.version().any_prerelease());
+        let version = project
+            .files()
+            .iter()
+            .filter(|it| !it.is_yanked() && self.is_installable(it.filename()))
+            .filter_map(|it| it.version())
+            .filter(|version| allow_prereleases || !version.any_prerelease())
+            .filter(|version| {
+                package
+                    .version_specifiers()
+                    .iter()
+                    .all(|it| it.contains(version))
+            })
+            .max();
+        let files = project
+            .files()
+            .iter()
+            .filter(|it| version.is_some() && it.version() == version)
+            .cloned()
+            .collect::<Vec<_>>();
+        let file = self.best_file(&files).ok_or_else(|| {
+            HuakError::PackageInstallationError(format!(
+                "no compatible wheel found for {}",
+                package.dependency_string()
+            ))
+        })?;
+        self.download_wheel_artifact(&file.artifact(), dir)
+    }
+
+    /// Download an artifact to a directory as a wheel. Source distributions are built
+    /// into a wheel in a directory of their own, and the wheel is then copied to the
+    /// directory.
+    pub fn download_wheel_artifact(
+        &self,
+        artifact: &Artifact,
+        dir: impl AsRef<Path>,
+    ) -> HuakResult<PathBuf> {
+        let path = self.download_artifact(artifact, dir.as_ref())?;
+        let python_path = match self.build_python.as_ref() {
+            Some(it) if pep517::is_sdist(&artifact.name) => it,
+            _ => return Ok(path),
+        };
+        let mut installer_config = InstallerConfig::new();
+        installer_config
+            .with_index_config(self.config.clone())
+            .with_offline(self.offline)
+            .with_require_hashes(self.require_hashes);
+        let build_dir = create_temp_dir("wheel")?;
+        let result =
+            pep517::build_wheel_from_sdist(&path, python_path, &installer_config, &build_dir)
+                .and_then(|wheel_path| {
+                    let path = dir
+                        .as_ref()
+                        .join(wheel_path.file_name().unwrap_or_default());
+                    std::fs::copy(&wheel_path, &path)?;
+                    Ok(path)
+                });
+        let _ = std::fs::remove_dir_all(&build_dir);
+        result
+    }
+
+    /// Download an artifact to a directory. The download is rejected if it doesn't
+    /// match the artifact's hash, or if it has no hash and hashes are required.
+    /// Artifacts are copied from the cache if they were downloaded before, found by
+    /// their sha256 hash or by their URL if they don't have one.
+    pub fn download_artifact(
+        &self,
+        artifact: &Artifact,
+        dir: impl AsRef<Path>,
+    ) -> HuakResult<PathBuf> {
+        if self.require_hashes && artifact.hash.is_none() {
+            return Err(HuakError::HashMissingError(artifact.name.clone()));
+        }
+        if !index::is_plain_file_name(&artifact.name) {
+            return Err(HuakError::PackageInstallationError(format!(
+                "invalid file name {:?}",
+                artifact.name
+            )));
+        }
+        let path = dir.as_ref().join(&artifact.name);
+        let url_key = match reqwest::Url::parse(&artifact.url) {
+            Ok(it) => auth::strip_credentials(&it).to_string(),
+            Err(_) => artifact.url.clone(),
+        };
+        let sha256 = match artifact.sha256() {
+            Some(it) => Some(it.to_lowercase()),
+            None => self.cache.artifact_sha256(&url_key)?,
+        };
+        if let Some(cached) = sha256
+            .as_deref()
+            .and_then(|it| self.cache.artifact_path(it))
+        {
+            // Cached artifacts are checked too in case the cache was modified.
+            let actual = artifact.copy_verified(
+                &mut std::fs::File::open(&cached)?,
+                &mut std::io::sink(),
+                |_| (),
+            );
+            if actual.ok() == sha256 {
+                let _ = std::fs::remove_file(&path);
+                fs::link_or_copy_file(&cached, &path)?;
+                return Ok(path);
+            }
+        }
+        if self.offline {
+            return Err(HuakError::CacheMissError(artifact.name.clone()));
+        }
+        let mut response = self.get(&artifact.url)?.send()?.error_for_status()?;
+        let bar = self.progress.add_bar(
+            "Downloading",
+            &artifact.name,
+            response.content_length(),
+            ProgressUnit::Bytes,
+        );
+        // The download is written next to its destination and only moved into place
+        // once it's verified.
+        let temp_path = dir.as_ref().join(format!(".{}.part", artifact.name));
+        let result = std::fs::File::create(&temp_path)
+            .map_err(HuakError::from)
+            .and_then(|mut file| artifact.copy_verified(&mut response, &mut file, |n| bar.inc(n)));
+        bar.finish();
+        let sha256 = match result {
+            Ok(it) => it,
+            Err(e) => {
+                let _ = std::fs::remove_file(&temp_path);
+                return Err(e);
+            }
+        };
+        std::fs::rename(&temp_path, &path)?;
+        self.cache.store_artifact(&path, &sha256)?;
+        if artifact.sha256().is_none() {
+            self.cache.store_artifact_sha256(&url_key, &sha256)?;
+        }
+        Ok(path)
+    }
+}
+
+/// The maximum number of artifacts downloaded at once.
+const MAX_CONCURRENT_DOWNLOADS: usize = 8;
+
+/// Download a file for each item on up to `MAX_CONCURRENT_DOWNLOADS` threads. The
+/// paths are returned in the order of the items. No more downloads are started once
+/// one fails.
+fn download_concurrently<T: Sync>(
+    items: &[T],
+    download: impl Fn(&T) -> HuakResult<PathBuf> + Sync,
+) -> HuakResult<Vec<PathBuf>> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((0..items.len()).map(|_| None).collect::<Vec<_>>());
+    std::thread::scope(|scope| {
+        for _ in 0..items.len().min(MAX_CONCURRENT_DOWNLOADS) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                if i >= items.len() {
+                    break;
+                }
+                let result = download(&items[i]);
+                if result.is_err() {
+                    next.store(items.len(), Ordering::SeqCst);
+                }
+                results.lock().unwrap_or_else(|e| e.into_inner())[i] = Some(result);
+            });
+        }
     });
+    results
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .into_iter()
+        .flatten()
+        .collect()
+}
 
-    interpreters
+impl PackageSource for PackageIndexClient {
+    /// Get the versions of a package with a wheel that can be installed.
+    fn versions(&mut self, name: &str) -> HuakResult<Vec<Version>> {
+        let files = self.project(name)?.files().to_vec();
+        let mut versions = files
+            .iter()
+            .filter(|it| !it.is_yanked() && self.is_installable(it.filename()))
+            .filter_map(|it| it.version())
+            .collect::<Vec<_>>();
+        versions.sort();
+        versions.dedup();
+        Ok(versions)
+    }
+
+    fn dependencies(&mut self, name: &str, version: &Version) -> HuakResult<Vec<Package>> {
+        let file = self.installable_file(name, version)?;
+        Ok(self.core_metadata(&file)?.requires_dist().to_vec())
+    }
+
+    fn artifacts(&mut self, name: &str, version: &Version) -> HuakResult<Vec<Artifact>> {
+        let mut artifacts = self
+            .project(name)?
+            .files()
+            .iter()
+            .filter(|it| !it.is_yanked() && it.version().as_ref() == Some(version))
+            .map(|it| it.artifact())
+            .collect::<Vec<_>>();
+        artifacts.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(artifacts)
+    }
+
+    /// Get the URL of the index a package was found on.
+    fn index_url(&self, name: &str) -> String {
+        match self.projects.get(&canonical_package_name(name)) {
+            Some((index, _)) => index.url().to_string(),
+            None => self.config.indexes_for(name)[0].url().to_string(),
+        }
+    }
+
+    /// Get the `Requires-Python` of the file that would be installed for a version of
+    /// a package. Invalid specifiers are ignored.
+    fn requires_python(
+        &mut self,
+        name: &str,
+        version: &Version,
+    ) -> HuakResult<Option<PythonRequest>> {
+        let file = self.installable_file(name, version)?;
+        Ok(file
+            .requires_python()
+            .and_then(|it| PythonRequest::from_str(it).ok()))
+    }
 }
 
-/// Parse a Python interpreter's version from its path if one exists.
-fn python_version_from_path(path: impl AsRef<Path>) -> Option<Version> {
-    path.as_ref()
-        .file_name()
-        .or(None)
-        .and_then(|raw_file_name| raw_file_name.to_str().or(None))
-        .and_then(|file_name| {
-            if valid_python_interpreter_file_name(file_name) {
-                Version::from_str(&file_name["python".len()..]).ok()
-            } else {
-                None
-            }
-        })
+/// Check if a wheel file name is for any platform and Python 3.
+/// See https://packaging.python.org/en/latest/specifications/binary-distribution-format/#file-name-convention
+fn is_universal_wheel(file_name: &str) -> bool {
+    let tags = match file_name.strip_suffix(".whl") {
+        Some(stem) => stem.rsplitn(4, '-').collect::<Vec<_>>(),
+        None => return false,
+    };
+    match tags.as_slice() {
+        [platform, abi, python, _] => {
+            *platform == "any" && *abi == "none" && python.split('.').any(|it| it == "py3")
+        }
+        _ => false,
+    }
 }
 
-fn valid_python_interpreter_file_name(file_name: &str) -> bool {
-    file_name.len() >= "python3.0".len() && file_name.starts_with("python")
+/// Get a hashmap of Python interpreters. Each entry is stored with the interpreter's
+/// implementation and version as its key and the absolute path the the interpreter as
+/// the value, so that CPython and PyPy interpreters of the same version are both kept.
+/// When more than one interpreter has an implementation and version the one with the
+/// highest priority is used.
+pub fn find_python_interpreter_paths() -> HashMap<(PythonImplementation, Version), PathBuf> {
+    let mut interpreters = HashMap::new();
+    for interpreter in find_python_interpreters() {
+        interpreters
+            .entry((
+                interpreter.implementation().clone(),
+                interpreter.version().clone(),
+            ))
+            .or_insert_with(|| interpreter.path().clone());
+    }
+    interpreters
 }
 
 #[cfg(test)]
 mod tests {
     use std::ops::Deref;
 
+    use sha2::{Digest, Sha256};
     use tempfile::tempdir;
 
     use super::*;
@@ -812,8 +2833,8 @@ mod tests {
 name = ""
 version = "0.0.1"
 description = ""
-
-[project.dependencies]
+requires-python = ">=3.8"
+dependencies = []
 
 [build-system]
 requires = ["hatchling"]
@@ -825,7 +2846,7 @@ build-backend = "hatchling.build"
     #[test]
     fn toml_dependencies() {
         let path = test_resources_dir_path()
-            .join("mock-projet")
+            .join("mock-project")
             .join("pyproject.toml");
         let ptoml = PyProjectToml::from_path(path).unwrap();
 
@@ -838,7 +2859,7 @@ build-backend = "hatchling.build"
     #[test]
     fn toml_optional_dependencies() {
         let path = test_resources_dir_path()
-            .join("mock-projet")
+            .join("mock-project")
             .join("pyproject.toml");
         let ptoml = PyProjectToml::from_path(path).unwrap();
 
@@ -851,15 +2872,14 @@ build-backend = "hatchling.build"
     #[test]
     fn toml_add_dependency() {
         let path = test_resources_dir_path()
-            .join("mock-projet")
+            .join("mock-project")
             .join("pyproject.toml");
         let mut ptoml = PyProjectToml::from_path(path).unwrap();
 
-        ptoml.add_dependency("test");
+        ptoml.add_dependency("test").unwrap();
         assert_eq!(
             ptoml.to_string_pretty().unwrap(),
             r#"[project]
-[project]
 name = "mock_project"
 version = "0.0.1"
 description = ""
@@ -882,24 +2902,22 @@ build-backend = "hatchling.build"
     #[test]
     fn toml_add_optional_dependency() {
         let path = test_resources_dir_path()
-            .join("mock-projet")
+            .join("mock-project")
             .join("pyproject.toml");
         let mut ptoml = PyProjectToml::from_path(path).unwrap();
 
-        ptoml.add_optional_dependency("test", "test");
-        ptoml.add_optional_dependency("new", "test");
+        ptoml.add_optional_dependency("test", "test").unwrap();
+        ptoml.add_optional_dependency("new", "test").unwrap();
         assert_eq!(
             ptoml.to_string_pretty().unwrap(),
             r#"[project]
-[project]
 name = "mock_project"
 version = "0.0.1"
 description = ""
 dependencies = ["click==8.1.3", "black==22.8.0", "isort==5.12.0"]
 
 [project.optional-dependencies]
-test = ["pytest>=6", "mock", "test"]
-new = ["test"]
+test = ["pytest>=6", "mock", "test", "new"]
 
 [[project.authors]]
 name = "Chris Pryer"
@@ -915,15 +2933,14 @@ build-backend = "hatchling.build"
     #[test]
     fn toml_remove_dependency() {
         let path = test_resources_dir_path()
-            .join("mock-projet")
+            .join("mock-project")
             .join("pyproject.toml");
         let mut ptoml = PyProjectToml::from_path(path).unwrap();
 
-        ptoml.remove_dependency("isort");
+        ptoml.remove_dependency("isort").unwrap();
         assert_eq!(
             ptoml.to_string_pretty().unwrap(),
             r#"[project]
-[project]
 name = "mock_project"
 version = "0.0.1"
 description = ""
@@ -946,19 +2963,18 @@ build-backend = "hatchling.build"
     #[test]
     fn toml_remove_optional_dependency() {
         let path = test_resources_dir_path()
-            .join("mock-projet")
+            .join("mock-project")
             .join("pyproject.toml");
         let mut ptoml = PyProjectToml::from_path(path).unwrap();
 
-        ptoml.remove_optional_dependency("test", "mock");
+        ptoml.remove_optional_dependency("mock", "test").unwrap();
         assert_eq!(
             ptoml.to_string_pretty().unwrap(),
             r#"[project]
-[project]
 name = "mock_project"
 version = "0.0.1"
 description = ""
-dependencies = ["click==8.1.3", "black==22.8.0"]
+dependencies = ["click==8.1.3", "black==22.8.0", "isort==5.12.0"]
 
 [project.optional-dependencies]
 test = ["pytest>=6"]
@@ -974,6 +2990,93 @@ build-backend = "hatchling.build"
         )
     }
 
+    #[test]
+    fn toml_edits_preserve_formatting() {
+        let contents = r#"[project]
+name = "mock_project" # the distribution name
+version = "0.0.1"
+dependencies = [
+    "click==8.1.3",
+    # Formatting
+    "black==22.8.0",
+]
+
+[tool.ruff]
+line-length = 88
+select = ["E", "F"]
+"#;
+        let mut ptoml = PyProjectToml::from_contents(contents).unwrap();
+
+        ptoml.add_dependency("isort==5.12.0").unwrap();
+        ptoml.add_dependency("Black>=23").unwrap();
+        ptoml.set_project_name("mock-project").unwrap();
+        assert_eq!(
+            ptoml.to_string_pretty().unwrap(),
+            r#"[project]
+name = "mock-project" # the distribution name
+version = "0.0.1"
+dependencies = [
+    "click==8.1.3",
+    # Formatting
+    "Black>=23",
+    "isort==5.12.0",
+]
+
+[tool.ruff]
+line-length = 88
+select = ["E", "F"]
+"#
+        );
+        assert_eq!(ptoml.project_name().unwrap(), "mock-project");
+
+        ptoml.remove_dependency("click").unwrap();
+        assert!(ptoml.to_string_pretty().unwrap().contains(
+            r#"dependencies = [
+    # Formatting
+    "Black>=23",
+    "isort==5.12.0",
+]"#
+        ));
+        ptoml.remove_dependency("isort").unwrap();
+        ptoml.remove_dependency("black").unwrap();
+        assert!(ptoml.dependencies().unwrap().is_empty());
+        assert!(ptoml.to_string_pretty().unwrap().contains("[tool.ruff]"));
+    }
+
+    #[test]
+    fn toml_index_config() {
+        let contents = r#"[project]
+name = "mock_project"
+version = "0.0.1"
+
+[[tool.huak.index]]
+name = "internal"
+url = "https://pkgs.example.com/simple/"
+
+[tool.huak.index-pins]
+internal-lib = "internal"
+"#;
+        let ptoml = PyProjectToml::from_contents(contents).unwrap();
+        let config = ptoml.index_config().unwrap();
+
+        assert_eq!(config.primary().name(), "pypi");
+        assert_eq!(config.indexes()[1].url(), "https://pkgs.example.com/simple");
+        assert_eq!(
+            config.pinned_index("Internal_Lib").unwrap().name(),
+            "internal"
+        );
+        assert_eq!(
+            PyProjectToml::default().index_config().unwrap(),
+            IndexConfig::default()
+        );
+        assert!(PyProjectToml::from_contents(
+            "[tool.huak.index-pins]\ninternal-lib = \"internal\"\n"
+        )
+        .unwrap()
+        .index_config()
+        .is_err());
+    }
+
     #[test]
     fn python_environment_default() {
         let python_environment = VirtualEnvironment::default();
@@ -1026,6 +3129,287 @@ build-backend = "hatchling.build"
         );
     }
 
+    #[test]
+    /// NOTE: This test depends on a Python interpreter being available on the PATH.
+    fn python_environment_write_venv() {
+        let dir = tempdir().unwrap().into_path();
+        let interpreters = find_python_interpreter_paths();
+        let (_, python_path) = interpreters
+            .iter()
+            .max_by_key(|((_, version), _)| version)
+            .unwrap();
+        let venv =
+            VirtualEnvironment::from_base_python_path(dir.join(".venv"), python_path).unwrap();
+
+        venv.write_venv().unwrap();
+
+        let cfg = std::fs::read_to_string(venv.root().join("pyvenv.cfg")).unwrap();
+        let output = Command::new(venv.python_path())
+            .args(["-c", "import sys; print(sys.prefix != sys.base_prefix)"])
+            .output()
+            .unwrap();
+
+        assert!(cfg.contains("include-system-site-packages = false"));
+        assert!(venv.site_packages_dir_path().exists());
+        assert!(venv.executables_dir_path().join("activate").exists());
+        assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), "True");
+    }
+
+    #[test]
+    /// NOTE: This test depends on a Python interpreter being available on the PATH.
+    fn python_environment_requires_python() {
+        let dir = tempdir().unwrap().into_path();
+        let python_path = find_python_path(Some(&PythonRequest::from_str(">=3").unwrap())).unwrap();
+        let venv =
+            VirtualEnvironment::from_base_python_path(dir.join(".venv"), python_path).unwrap();
+        let version = venv.python_version().unwrap();
+
+        venv.check_requires_python(&PythonRequest::from_str(&format!("=={version}")).unwrap())
+            .unwrap();
+        assert!(matches!(
+            venv.check_requires_python(&PythonRequest::from_str(">=99").unwrap()),
+            Err(HuakError::RequiresPythonMismatchError(..))
+        ));
+        let error = find_python_path(Some(&PythonRequest::from_str(">=99").unwrap())).unwrap_err();
+        assert!(matches!(error, HuakError::RequiresPythonNotFoundError(..)));
+        assert!(error.to_string().contains(&format!("{version} at ")));
+
+        // Without a version in pyvenv.cfg the environment's interpreter is queried.
+        venv.write_venv().unwrap();
+        let cfg_path = venv.root().join("pyvenv.cfg");
+        let cfg = std::fs::read_to_string(&cfg_path).unwrap();
+        let cfg = cfg
+            .lines()
+            .filter(|it| !it.starts_with("version"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        std::fs::write(&cfg_path, cfg).unwrap();
+        let venv = VirtualEnvironment::from_path(venv.root()).unwrap();
+
+        assert!(venv.python_version().is_none());
+        venv.check_requires_python(&PythonRequest::from_str(&format!("=={version}")).unwrap())
+            .unwrap();
+        assert!(matches!(
+            venv.check_requires_python(&PythonRequest::from_str(">=99").unwrap()),
+            Err(HuakError::RequiresPythonMismatchError(..))
+        ));
+    }
+
+    #[test]
+    fn python_environment_install_wheel() {
+        let dir = tempdir().unwrap().into_path();
+        let interpreters = find_python_interpreter_paths();
+        let (_, python_path) = interpreters
+            .iter()
+            .max_by_key(|((_, version), _)| version)
+            .unwrap();
+        let mut venv =
+            VirtualEnvironment::from_base_python_path(dir.join(".venv"), python_path).unwrap();
+        venv.write_venv().unwrap();
+
+        venv.install_wheel(wheel::write_test_wheel(&dir)).unwrap();
+
+        let output = Command::new(venv.executables_dir_path().join("demo"))
+            .output()
+            .unwrap();
+        let package = venv.find_site_packages_package("Demo").unwrap().unwrap();
+
+        assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), "demo");
+        assert_eq!(package.dependency_string(), "demo==1.0");
+        assert!(venv.installed_packages().unwrap() == vec![package]);
+
+        venv.with_installer_config(InstallerConfig::new().with_dry_run(true));
+        let dry_run = venv.uninstall_packages(&["demo"]).unwrap();
+
+        assert!(venv.find_site_packages_package("demo").unwrap().is_some());
+
+        venv.with_installer_config(&InstallerConfig::new());
+        let removed = venv.uninstall_packages(&["demo"]).unwrap();
+
+        assert_eq!(dry_run, removed);
+        assert!(venv.installed_packages().unwrap().is_empty());
+        assert!(!venv.executables_dir_path().join("demo").exists());
+    }
+
+    #[test]
+    fn python_environment_install_downloaded_wheels() {
+        let dir = tempdir().unwrap().into_path();
+        let interpreters = find_python_interpreter_paths();
+        let (_, python_path) = interpreters
+            .iter()
+            .max_by_key(|((_, version), _)| version)
+            .unwrap();
+        let mut venv =
+            VirtualEnvironment::from_base_python_path(dir.join(".venv"), python_path).unwrap();
+        venv.write_venv().unwrap();
+        let wheel_path = wheel::write_test_wheel(&dir);
+        venv.install_wheel(&wheel_path).unwrap();
+        let broken_path = dir.join("broken-1.0-py3-none-any.whl");
+        std::fs::write(&broken_path, "not a wheel").unwrap();
+        let packages = vec![
+            Package::from_str("demo==1.0").unwrap(),
+            Package::from_str("broken==1.0").unwrap(),
+        ];
+
+        assert!(venv
+            .install_downloaded_wheels(&packages, vec![wheel_path.clone(), broken_path], &[])
+            .is_err());
+        assert!(venv.find_site_packages_package("demo").unwrap().is_some());
+
+        venv.install_downloaded_wheels(&packages[..1], vec![wheel_path], &[])
+            .unwrap();
+        let dist_info_path = venv.site_packages_dir_path().join("demo-1.0.dist-info");
+
+        assert!(dist_info_path.join("INSTALLER").exists());
+        assert!(!dist_info_path.join("REQUESTED").exists());
+    }
+
+    #[test]
+    fn python_environment_uninstall_unlocked_packages() {
+        let dir = tempdir().unwrap().into_path();
+        let interpreters = find_python_interpreter_paths();
+        let (_, python_path) = interpreters
+            .iter()
+            .max_by_key(|((_, version), _)| version)
+            .unwrap();
+        let mut venv =
+            VirtualEnvironment::from_base_python_path(dir.join(".venv"), python_path).unwrap();
+        venv.write_venv().unwrap();
+        venv.install_wheel(wheel::write_test_wheel(&dir)).unwrap();
+        let package = venv.find_site_packages_package("demo").unwrap().unwrap();
+
+        let uninstalled = venv.uninstall_unlocked_packages(&[]).unwrap();
+
+        assert!(uninstalled == vec![package]);
+        assert!(venv.installed_packages().unwrap().is_empty());
+    }
+
+    #[test]
+    fn venv_search() {
+        let dir = tempdir().unwrap().into_path();
+        let project_root = dir.join("workspace").join("mock-project");
+        let src = project_root.join("src").join("mock_project");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(project_root.join("pyproject.toml"), "").unwrap();
+        let cfg = "home = /usr/bin\ninclude-system-site-packages = false\n";
+        for venv_root in [dir.join(".venv"), project_root.join("venv")] {
+            std::fs::create_dir_all(&venv_root).unwrap();
+            std::fs::write(venv_root.join("pyvenv.cfg"), cfg).unwrap();
+        }
+
+        let venv = search_venv(&src).unwrap();
+        assert_eq!(venv.root(), project_root.join("venv"));
+
+        let venv = search_venv(project_root.join("venv").join("bin")).unwrap();
+        assert_eq!(venv.root(), project_root.join("venv"));
+
+        // The search stops at the project root.
+        std::fs::remove_dir_all(project_root.join("venv")).unwrap();
+        assert!(search_venv(&src).is_err());
+        let venv = search_venv(dir.join("workspace")).unwrap();
+        assert_eq!(venv.root(), dir.join(".venv"));
+    }
+
+    #[test]
+    fn venv_config_round_trip() {
+        let contents = "home = /usr/bin
+include-system-site-packages = false
+version = 3.11.2
+executable = /usr/bin/python3.11
+command = /usr/bin/python3 -m venv /project/.venv
+";
+        let config = VirtualEnvironmentConfig::from_str(contents).unwrap();
+
+        assert_eq!(config.home, PathBuf::from("/usr/bin"));
+        assert_eq!(config.version, Some(Version::from_str("3.11.2").unwrap()));
+        assert_eq!(config.to_string(), contents);
+    }
+
+    #[test]
+    fn venv_config_virtualenv_entries() {
+        let contents = "home = /usr/bin
+implementation = CPython
+version_info = 3.10.6.final.0
+virtualenv = 20.16.5
+include-system-site-packages = TRUE
+base-prefix = /usr
+";
+        let config = VirtualEnvironmentConfig::from_str(contents).unwrap();
+
+        assert!(config.include_system_site_packages);
+        assert_eq!(config.version, Some(Version::from_str("3.10.6").unwrap()));
+        assert_eq!(config.get("implementation"), Some("CPython"));
+        assert_eq!(config.get("base-prefix"), Some("/usr"));
+        assert!(config
+            .to_string()
+            .contains("version_info = 3.10.6.final.0\n"));
+        assert!(!config.to_string().contains("version = "));
+
+        let contents = "home = /usr/bin\nversion = 3.10.6\nversion_info = 3.10.6.final.0\n";
+        let config = VirtualEnvironmentConfig::from_str(contents).unwrap();
+
+        assert!(config.to_string().contains("version = 3.10.6\n"));
+        assert!(config
+            .to_string()
+            .contains("version_info = 3.10.6.final.0\n"));
+    }
+
+    #[test]
+    fn venv_config_invalid() {
+        let err = VirtualEnvironmentConfig::from_str("home = /usr/bin\n\nversion 3.11\n")
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("line 3"));
+
+        let err = VirtualEnvironmentConfig::from_str(
+            "home = /usr/bin\ninclude-system-site-packages = maybe\n",
+        )
+        .err()
+        .unwrap();
+        assert!(err.to_string().contains("line 2"));
+
+        assert!(VirtualEnvironmentConfig::from_str("version = 3.11.2\n").is_err());
+    }
+
+    #[test]
+    fn python_environment_from_path() {
+        let dir = tempdir().unwrap().into_path();
+        let root = dir.join(".venv");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("pyvenv.cfg"),
+            "home = /opt/python/bin\ninclude-system-site-packages = false\nversion = 3.11.2\n",
+        )
+        .unwrap();
+        let venv = VirtualEnvironment::from_path(&root).unwrap();
+
+        assert!(venv.is_isolated());
+        assert_eq!(
+            venv.base_python_version(),
+            Some(&Version::from_str("3.11.2").unwrap())
+        );
+        assert_eq!(
+            venv.base_executables_dir_path(),
+            &PathBuf::from("/opt/python/bin")
+        );
+        #[cfg(unix)]
+        {
+            assert_eq!(
+                venv.site_packages_dir_path(),
+                root.join("lib").join("python3.11").join("site-packages")
+            );
+            assert_eq!(
+                venv.base_site_packages_dir_path(),
+                PathBuf::from("/opt/python/lib/python3.11/site-packages")
+            );
+            assert_eq!(
+                venv.base_python_path(),
+                PathBuf::from("/opt/python/bin/python3.11")
+            );
+        }
+        assert!(VirtualEnvironment::from_path(&dir).is_err());
+    }
+
     #[test]
     fn package_display_str() {
         let package = Package::from_str("package==0.0.0").unwrap();
@@ -1037,7 +3421,10 @@ build-backend = "hatchling.build"
     fn package_version_operator() {
         let package = Package::from_str("package==0.0.0").unwrap();
 
-        assert_eq!(package.version_operator, pep440_rs::Operator::Equal);
+        assert_eq!(
+            *package.version_operator().unwrap(),
+            pep440_rs::Operator::Equal
+        );
     }
 
     #[test]
@@ -1047,47 +3434,637 @@ build-backend = "hatchling.build"
         assert_eq!(package.dependency_string(), "package==0.0.0");
         assert_eq!(package.name(), "package");
         assert_eq!(
-            package.version_operator().to_string(),
+            package.version_operator().unwrap().to_string(),
             pep440_rs::Operator::Equal.to_string()
         );
-        assert_eq!(package.version().to_string(), "0.0.0");
+        assert_eq!(package.version().unwrap().to_string(), "0.0.0");
     }
 
-    #[ignore = "currently untestable"]
     #[test]
     fn package_with_multiple_version_specifiers() {
-        todo!()
+        let package = Package::from_str("package>=1.0,<2").unwrap();
+
+        assert_eq!(package.version_specifiers().len(), 2);
+        assert!(package
+            .version_specifiers()
+            .iter()
+            .all(|it| it.contains(&Version::from_str("1.5").unwrap())));
+        assert_eq!(package.dependency_string(), "package>=1.0,<2");
+    }
+
+    #[test]
+    fn package_dependency_string_round_trip() {
+        let strs = [
+            "ruff",
+            "requests[socks]",
+            "requests[security,socks]>=2.8.1,==2.8.*",
+            "pkg @ https://example.com/pkg-1.0.tar.gz",
+            "pkg @ https://example.com/pkg-1.0.tar.gz ; sys_platform == \"linux\"",
+            "black==22.8.0; python_version < \"3.9\"",
+        ];
+
+        for s in strs {
+            let package = Package::from_str(s).unwrap();
+            let round_tripped = Package::from_str(&package.dependency_string()).unwrap();
+
+            assert_eq!(package.dependency_string(), s);
+            assert!(package == round_tripped);
+        }
+    }
+
+    #[test]
+    fn package_applies_to() {
+        let environment = MarkerEnvironment {
+            python_version: "3.8".to_string(),
+            sys_platform: "linux".to_string(),
+            ..Default::default()
+        };
+        let unmarked = Package::from_str("ruff").unwrap();
+        let linux = Package::from_str("uvloop; sys_platform == \"linux\"").unwrap();
+        let legacy = Package::from_str("importlib-metadata; python_version < \"3.8\"").unwrap();
+        let extra = Package::from_str("pytest; extra == \"test\"").unwrap();
+
+        assert!(unmarked.applies_to(&environment, &[]));
+        assert!(linux.applies_to(&environment, &[]));
+        assert!(!legacy.applies_to(&environment, &[]));
+        assert!(!extra.applies_to(&environment, &[]));
+        assert!(extra.applies_to(&environment, &["test"]));
+    }
+
+    #[test]
+    fn package_canonical_name() {
+        let package = Package::from_str("Friendly-Bard__Tools.Extra").unwrap();
+
+        assert_eq!(package.name(), "Friendly-Bard__Tools.Extra");
+        assert_eq!(package.canonical_name(), "friendly-bard-tools-extra");
     }
 
     #[test]
     fn package_platform_tags() {
-        todo!()
+        let dir = tempdir().unwrap().into_path();
+        let dist_info_path = dir.join("numpy-1.26.0.dist-info");
+        std::fs::create_dir_all(&dist_info_path).unwrap();
+        std::fs::write(
+            dist_info_path.join("METADATA"),
+            "Metadata-Version: 2.1\nName: numpy\nVersion: 1.26.0\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dist_info_path.join("WHEEL"),
+            "Wheel-Version: 1.0\nRoot-Is-Purelib: false\n\
+             Tag: cp311-cp311-manylinux_2_17_x86_64\n\
+             Tag: cp311-cp311-manylinux2014_x86_64\n",
+        )
+        .unwrap();
+
+        let package = DistInfo::from_path(&dist_info_path).unwrap().package();
+
+        assert_eq!(
+            package.platform_tags(),
+            [
+                PlatformTag::Manylinux {
+                    major: 2,
+                    minor: 17,
+                    arch: "x86_64".to_string()
+                },
+                PlatformTag::Manylinux2014("x86_64".to_string())
+            ]
+        );
+        assert!(Package::from_str("numpy")
+            .unwrap()
+            .platform_tags()
+            .is_empty());
+
+        let wheel = WheelFilename::from_str(
+            "numpy-1.26.0-cp311-cp311-manylinux_2_17_x86_64.manylinux2014_x86_64.whl",
+        )
+        .unwrap();
+        assert_eq!(wheel.platform_tags(), package.platform_tags());
     }
 
     #[test]
     fn package_core_metadata() {
-        todo!()
+        let contents = r#"Metadata-Version: 2.1
+Name: Mock_Package
+Version: 1.2.0
+Summary: A mock package.
+Keywords: mock,test
+Author-email: Mock <mock@email.com>
+Requires-Python: >=3.8,<4
+Classifier: Programming Language :: Python :: 3
+Classifier: License :: OSI Approved :: MIT License
+Project-URL: Source, https://github.com/mock/mock
+Provides-Extra: test
+Requires-Dist: click>=8
+Requires-Dist: pytest; extra == "test"
+Description-Content-Type: text/markdown
+
+# Mock Package
+"#;
+
+        let metadata = PackageMetadata::from_str(contents).unwrap();
+
+        assert_eq!(metadata.name(), "Mock_Package");
+        assert_eq!(metadata.version().unwrap().to_string(), "1.2.0");
+        assert_eq!(metadata.summary(), Some("A mock package."));
+        assert_eq!(metadata.keywords(), ["mock", "test"]);
+        assert_eq!(metadata.requires_python().len(), 2);
+        assert_eq!(metadata.classifiers().len(), 2);
+        assert_eq!(
+            metadata.project_urls(),
+            [(
+                "Source".to_string(),
+                "https://github.com/mock/mock".to_string()
+            )]
+        );
+        assert_eq!(metadata.provides_extra(), ["test"]);
+        assert_eq!(
+            metadata
+                .requires_dist()
+                .iter()
+                .map(|it| it.dependency_string())
+                .collect::<Vec<_>>(),
+            ["click>=8", "pytest; extra == \"test\""]
+        );
+        assert_eq!(metadata.description(), Some("# Mock Package"));
+        assert!(PackageMetadata::from_str("Metadata-Version: 3.0\nName: mock\n").is_err());
+        assert!(PackageMetadata::from_str("Metadata-Version: 2.1\n").is_err());
     }
 
     #[test]
     fn package_dist_info() {
-        todo!();
+        let dir = tempdir().unwrap().into_path();
+        let root = dir.join(".venv");
+        let scheme = wheel::InstallScheme {
+            purelib: root.join("site-packages"),
+            platlib: root.join("site-packages"),
+            scripts: root.join("bin"),
+            headers: root.join("include"),
+            data: root.clone(),
+        };
+        let dist_info_path = wheel::install_wheel(
+            &wheel::write_test_wheel(&dir),
+            &scheme,
+            &root.join("bin").join("python"),
+            "huak",
+            true,
+        )
+        .unwrap();
+
+        let dist_info = DistInfo::from_path(&dist_info_path).unwrap();
+        let package = dist_info.package();
+
+        assert_eq!(dist_info.installer(), Some("huak"));
+        assert!(dist_info.requested());
+        assert!(dist_info.wheel().unwrap().root_is_purelib());
+        assert_eq!(dist_info.entry_points()[0].name(), "demo");
+        assert!(dist_info
+            .record()
+            .iter()
+            .any(|it| it.path() == "demo/__init__.py" && it.hash().is_some()));
+        assert_eq!(package.dependency_string(), "demo==1.0");
+        assert_eq!(package.core_metadata().name(), "demo");
+        assert!(find_dist_info_path(&scheme.purelib, "DEMO").is_some());
+
+        let broken_path = scheme.purelib.join("broken-1.0.dist-info");
+        std::fs::create_dir_all(&broken_path).unwrap();
+
+        assert!(matches!(
+            installed_packages_in(&scheme.purelib),
+            Err(HuakError::BrokenDistInfoError(path, _)) if path == broken_path
+        ));
     }
 
     #[test]
-    fn python_search() {
+    fn package_index_client_simple_api() {
         let dir = tempdir().unwrap().into_path();
-        std::fs::write(dir.join("python3.11"), "").unwrap();
-        let path_vals = vec![dir.to_str().unwrap().to_string()];
-        std::env::set_var("PATH", path_vals.join(":"));
-        let interpreter_paths = find_python_interpreter_paths();
+        let wheel = std::fs::read(wheel::write_test_wheel(&dir)).unwrap();
+        let wheel_hash = format!("{:x}", Sha256::digest(&wheel));
+        let metadata =
+            b"Metadata-Version: 2.1\nName: demo\nVersion: 1.0\nRequires-Dist: click>=8\n";
+        let metadata_hash = format!("{:x}", Sha256::digest(metadata));
+        let json = format!(
+            r#"{{"meta": {{"api-version": "1.0"}}, "name": "demo", "files": [
+                {{"filename": "demo-1.0-py3-none-any.whl", "url": "/files/demo-1.0-py3-none-any.whl", "hashes": {{"sha256": "{wheel_hash}"}}, "core-metadata": {{"sha256": "{metadata_hash}"}}}},
+                {{"filename": "demo-0.9-py3-none-any.whl", "url": "/files/demo-0.9-py3-none-any.whl", "hashes": {{}}, "yanked": true}}
+            ]}}"#
+        );
+        let html = format!(
+            r#"<a href="/files/demo-1.0-py3-none-any.whl#sha256={wheel_hash}">demo-1.0-py3-none-any.whl</a>"#
+        );
+        let url = index::serve(vec![
+            (
+                "/json/demo/".to_string(),
+                index::SIMPLE_JSON_CONTENT_TYPE.to_string(),
+                json.into_bytes(),
+            ),
+            (
+                "/html/demo/".to_string(),
+                "text/html".to_string(),
+                html.into_bytes(),
+            ),
+            (
+                "/files/demo-1.0-py3-none-any.whl".to_string(),
+                "application/octet-stream".to_string(),
+                wheel.clone(),
+            ),
+            (
+                "/files/demo-1.0-py3-none-any.whl.metadata".to_string(),
+                "application/octet-stream".to_string(),
+                metadata.to_vec(),
+            ),
+        ]);
+        let version = Version::from_str("1.0").unwrap();
+
+        let mut client = PackageIndexClient::new();
+        client
+            .with_url(&format!("{url}/json/"))
+            .with_cache(Cache::from_path(dir.join("cache")));
+
+        assert_eq!(client.index_url("demo"), format!("{url}/json"));
+        assert_eq!(client.versions("Demo").unwrap(), vec![version.clone()]);
+        assert_eq!(
+            client.dependencies("demo", &version).unwrap()[0].dependency_string(),
+            "click>=8"
+        );
+        assert_eq!(
+            client.artifacts("demo", &version).unwrap()[0].hash,
+            Some(format!("sha256:{wheel_hash}"))
+        );
+        let download_dir = dir.join("downloads");
+        std::fs::create_dir_all(&download_dir).unwrap();
+        let path = client
+            .download_wheel(&Package::from_str("demo").unwrap(), &download_dir)
+            .unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), wheel);
+
+        client
+            .with_url(&format!("{url}/html"))
+            .with_cache(Cache::from_path(dir.join("html-cache")));
+
+        assert_eq!(client.versions("demo").unwrap(), vec![version.clone()]);
+        assert!(client.dependencies("demo", &version).unwrap().is_empty());
+    }
+
+    #[test]
+    fn package_index_client_concurrent_downloads() {
+        let dir = tempdir().unwrap().into_path();
+        let url = index::serve(vec![
+            (
+                "/files/a-1.0-py3-none-any.whl".to_string(),
+                "application/octet-stream".to_string(),
+                b"a".to_vec(),
+            ),
+            (
+                "/files/b-1.0-py3-none-any.whl".to_string(),
+                "application/octet-stream".to_string(),
+                b"b".to_vec(),
+            ),
+        ]);
+        let artifact = |name: &str| Artifact {
+            name: format!("{name}-1.0-py3-none-any.whl"),
+            url: format!("{url}/files/{name}-1.0-py3-none-any.whl"),
+            hash: None,
+        };
+        let mut client = PackageIndexClient::new();
+        client.with_cache(Cache::from_path(dir.join("cache")));
+        let download_dir = create_temp_dir("downloads").unwrap();
+
+        let paths = download_concurrently(&[artifact("a"), artifact("b")], |it| {
+            client.download_artifact(it, &download_dir)
+        })
+        .unwrap();
+        assert_eq!(
+            paths,
+            [
+                download_dir.join("a-1.0-py3-none-any.whl"),
+                download_dir.join("b-1.0-py3-none-any.whl")
+            ]
+        );
+        assert_eq!(std::fs::read(&paths[1]).unwrap(), b"b");
+
+        assert!(
+            download_concurrently(&[artifact("a"), artifact("missing")], |it| {
+                client.download_artifact(it, &download_dir)
+            })
+            .is_err()
+        );
+        assert_ne!(create_temp_dir("downloads").unwrap(), download_dir);
+    }
+
+    #[test]
+    fn package_index_client_index_priority() {
+        let page = |version: &str| {
+            format!(
+                r#"<a href="/files/demo-{version}-py3-none-any.whl">demo-{version}-py3-none-any.whl</a>"#
+            )
+            .into_bytes()
+        };
+        let url = index::serve(vec![
+            (
+                "/primary/demo/".to_string(),
+                "text/html".to_string(),
+                page("2.0"),
+            ),
+            (
+                "/extra/demo/".to_string(),
+                "text/html".to_string(),
+                page("1.0"),
+            ),
+            (
+                "/extra/other/".to_string(),
+                "text/html".to_string(),
+                page("1.0"),
+            ),
+        ]);
+        let mut primary = PackageIndex::new("primary", &format!("{url}/primary"));
+        primary.with_default(true);
+        let extra = PackageIndex::new("extra", &format!("{url}/extra"));
+        let indexes = vec![primary, extra];
+
+        let mut client = PackageIndexClient::new();
+        client
+            .with_index_config(IndexConfig::new(indexes.clone(), BTreeMap::new()).unwrap())
+            .with_cache(Cache::from_path(tempdir().unwrap().into_path()));
 
         assert_eq!(
-            interpreter_paths
-                .get(&Version::from_str("3.11").unwrap())
+            client.versions("demo").unwrap(),
+            vec![Version::from_str("2.0").unwrap()]
+        );
+        assert_eq!(client.index_url("demo"), format!("{url}/primary"));
+        assert!(!client.versions("other").unwrap().is_empty());
+        assert_eq!(client.index_url("other"), format!("{url}/extra"));
+        assert!(client.versions("missing").is_err());
+
+        let pins = BTreeMap::from([("demo".to_string(), "extra".to_string())]);
+        client.with_index_config(IndexConfig::new(indexes, pins).unwrap());
+
+        assert_eq!(
+            client.versions("demo").unwrap(),
+            vec![Version::from_str("1.0").unwrap()]
+        );
+        assert_eq!(client.index_url("demo"), format!("{url}/extra"));
+    }
+
+    #[test]
+    fn package_index_client_authentication() {
+        let page = br#"<a href="/files/demo-1.0-py3-none-any.whl">demo-1.0-py3-none-any.whl</a>"#;
+        let url = index::serve_authenticated(
+            vec![(
+                "/simple/demo/".to_string(),
+                "text/html".to_string(),
+                page.to_vec(),
+            )],
+            Some("Bearer client-test-token".to_string()),
+        );
+        std::env::set_var("HUAK_INDEX_AUTH_TEST_TOKEN", "client-test-token");
+        let mut index = PackageIndex::new("auth-test", &format!("{url}/simple"));
+        index.with_default(true);
+        let config = IndexConfig::new(vec![index], BTreeMap::new()).unwrap();
+
+        let cache = Cache::from_path(tempdir().unwrap().into_path());
+        let mut client = PackageIndexClient::new();
+        client
+            .with_index_config(config.clone())
+            .with_cache(cache.clone());
+
+        assert!(client.versions("other").is_err());
+        assert_eq!(client.versions("demo").unwrap().len(), 1);
+
+        std::env::remove_var("HUAK_INDEX_AUTH_TEST_TOKEN");
+        let mut client = PackageIndexClient::new();
+        client.with_index_config(config).with_cache(cache);
+        let error = client.versions("demo").unwrap_err().to_string();
+
+        assert!(error.contains("401"));
+        assert!(!auth::redact("token client-test-token").contains("client-test-token"));
+
+        let netrc_path = tempdir().unwrap().into_path().join(".netrc");
+        std::fs::write(
+            &netrc_path,
+            "machine pkgs.example.com login user password secret\nmachine other.example.com login user password secret\n",
+        )
+        .unwrap();
+        let mut provider = CredentialProvider::default();
+        provider.with_netrc_path(&netrc_path);
+        let mut client = PackageIndexClient::new();
+        client
+            .with_url("https://pkgs.example.com/simple")
+            .with_credential_provider(provider);
+        let is_authenticated = |url: &str| {
+            client
+                .get(url)
+                .unwrap()
+                .build()
                 .unwrap()
-                .deref(),
-            dir.join("python3.11")
+                .headers()
+                .contains_key(reqwest::header::AUTHORIZATION)
+        };
+
+        assert!(is_authenticated("https://pkgs.example.com/simple/demo/"));
+        assert!(is_authenticated(
+            "https://pkgs.example.com/files/demo-1.0.tar.gz"
+        ));
+        assert!(!is_authenticated(
+            "https://other.example.com/files/demo-1.0.tar.gz"
+        ));
+    }
+
+    #[test]
+    fn package_index_client_offline() {
+        let dir = tempdir().unwrap().into_path();
+        let wheel = std::fs::read(wheel::write_test_wheel(&dir)).unwrap();
+        let wheel_hash = format!("{:x}", Sha256::digest(&wheel));
+        let html = format!(
+            r#"<a href="/files/demo-1.0-py3-none-any.whl#sha256={wheel_hash}">demo-1.0-py3-none-any.whl</a>"#
+        );
+        let url = index::serve(vec![
+            (
+                "/simple/demo/".to_string(),
+                "text/html".to_string(),
+                html.into_bytes(),
+            ),
+            (
+                "/files/demo-1.0-py3-none-any.whl".to_string(),
+                "application/octet-stream".to_string(),
+                wheel.clone(),
+            ),
+        ]);
+        let version = Version::from_str("1.0").unwrap();
+        let cache = Cache::from_path(dir.join("cache"));
+        let download_dir = dir.join("downloads");
+        std::fs::create_dir_all(&download_dir).unwrap();
+
+        let mut client = PackageIndexClient::new();
+        client
+            .with_url(&format!("{url}/simple"))
+            .with_cache(cache.clone())
+            .with_offline(true);
+
+        assert!(matches!(
+            client.versions("demo"),
+            Err(HuakError::CacheMissError(_))
+        ));
+
+        client.with_offline(false);
+        client.versions("demo").unwrap();
+        client.dependencies("demo", &version).unwrap();
+
+        // A new client with the same cache works without the index.
+        let mut client = PackageIndexClient::new();
+        client
+            .with_url(&format!("{url}/simple"))
+            .with_cache(cache.clone())
+            .with_offline(true);
+
+        assert_eq!(client.versions("demo").unwrap(), vec![version.clone()]);
+        assert!(client.dependencies("demo", &version).is_ok());
+        let artifact = client.artifacts("demo", &version).unwrap().remove(0);
+        let path = client.download_artifact(&artifact, &download_dir).unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), wheel);
+        assert!(matches!(
+            client.versions("other"),
+            Err(HuakError::CacheMissError(_))
+        ));
+
+        // An index searched before the one serving a package must be cached too.
+        let mut primary = PackageIndex::new("primary", &format!("{url}/primary"));
+        primary.with_default(true);
+        let extra = PackageIndex::new("extra", &format!("{url}/simple"));
+        let config = IndexConfig::new(vec![primary, extra], BTreeMap::new()).unwrap();
+        let mut client = PackageIndexClient::new();
+        client
+            .with_index_config(config.clone())
+            .with_cache(cache.clone())
+            .with_offline(true);
+
+        assert!(matches!(
+            client.versions("demo"),
+            Err(HuakError::CacheMissError(_))
+        ));
+
+        client.with_offline(false);
+        client.versions("demo").unwrap();
+        let mut client = PackageIndexClient::new();
+        client
+            .with_index_config(config)
+            .with_cache(cache)
+            .with_offline(true);
+
+        assert_eq!(client.versions("demo").unwrap(), vec![version]);
+    }
+
+    #[test]
+    fn package_index_client_hash_verification() {
+        let dir = tempdir().unwrap().into_path();
+        let wheel = std::fs::read(wheel::write_test_wheel(&dir)).unwrap();
+        let url = index::serve(vec![(
+            "/files/demo-1.0-py3-none-any.whl".to_string(),
+            "application/octet-stream".to_string(),
+            wheel.clone(),
+        )]);
+        let artifact = |hash: Option<String>| Artifact {
+            name: "demo-1.0-py3-none-any.whl".to_string(),
+            url: format!("{url}/files/demo-1.0-py3-none-any.whl"),
+            hash,
+        };
+        let download_dir = dir.join("downloads");
+        std::fs::create_dir_all(&download_dir).unwrap();
+        let mut client = PackageIndexClient::new();
+        client.with_cache(Cache::from_path(dir.join("cache")));
+
+        let error = client
+            .download_artifact(
+                &artifact(Some(format!("sha256:{}", "0".repeat(64)))),
+                &download_dir,
+            )
+            .unwrap_err();
+        assert!(matches!(error, HuakError::HashMismatchError(..)));
+        assert_eq!(std::fs::read_dir(&download_dir).unwrap().count(), 0);
+
+        let hash = format!("sha512:{:x}", sha2::Sha512::digest(&wheel));
+        let path = client
+            .download_artifact(&artifact(Some(hash)), &download_dir)
+            .unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), wheel);
+
+        let hash = format!("sha256:{:X}", Sha256::digest(&wheel));
+        let path = client
+            .download_artifact(&artifact(Some(hash)), &download_dir)
+            .unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), wheel);
+        assert!(client
+            .download_artifact(&artifact(None), &download_dir)
+            .is_ok());
+
+        // Artifacts without a hash are found in the cache by their URL.
+        let mut offline_client = PackageIndexClient::new();
+        offline_client
+            .with_cache(Cache::from_path(dir.join("cache")))
+            .with_offline(true);
+        let path = offline_client
+            .download_artifact(&artifact(None), &download_dir)
+            .unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), wheel);
+        let mut other = artifact(None);
+        other.url = format!("{url}/other/demo-1.0-py3-none-any.whl");
+        assert!(matches!(
+            offline_client.download_artifact(&other, &download_dir),
+            Err(HuakError::CacheMissError(_))
+        ));
+
+        client.with_require_hashes(true);
+        let error = client
+            .download_artifact(&artifact(None), &download_dir)
+            .unwrap_err();
+        assert!(matches!(error, HuakError::HashMissingError(_)));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn python_search() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap().into_path();
+        let executable = dir.join("python3.11");
+        std::fs::write(
+            &executable,
+            format!(
+                "#!/bin/sh\necho '{}'\n",
+                serde_json::json!({
+                    "executable": executable,
+                    "implementation": "cpython",
+                    "version": "3.11.4",
+                })
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&executable, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::os::unix::fs::symlink(&executable, dir.join("python3")).unwrap();
+        std::fs::write(dir.join("python3.10"), "").unwrap();
+        std::fs::write(dir.join("pip3"), "").unwrap();
+        let cache = Cache::from_path(dir.join("cache"));
+
+        let candidates = python::candidates_in_dirs([dir.clone()]);
+        let interpreters = python::python_interpreters_in_paths(candidates.clone(), &cache);
+
+        assert_eq!(
+            candidates,
+            [
+                dir.join("python3"),
+                dir.join("python3.10"),
+                dir.join("python3.11")
+            ]
+        );
+        assert_eq!(interpreters.len(), 1);
+        assert_eq!(interpreters[0].path().deref(), executable);
+        assert_eq!(
+            interpreters[0].version(),
+            &Version::from_str("3.11.4").unwrap()
+        );
+        assert_eq!(
+            interpreters[0].implementation(),
+            &PythonImplementation::CPython
         );
     }
 }
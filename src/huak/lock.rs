@@ -0,0 +1,443 @@
+///! This module implements huak.lock, the file recording the exact packages a project
+///! resolves to so that its environments can be reproduced.
+///!
+///! The lock is resolved for every environment at once. Each locked package records
+///! the markers of the environments it applies to, and the dependencies of optional
+///! groups are gated by `extra == "<group>"` markers.
+use crate::{
+    canonical_package_name,
+    error::{HuakError, HuakResult},
+    index::is_plain_file_name,
+    resolve::{Artifact, PackageSource, Resolver},
+    MarkerEnvironment, MarkerTree, Package, Progress, Project,
+};
+use pep440_rs::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, path::Path, str::FromStr};
+
+/// The name of the lock file written to a project's root.
+pub const LOCK_FILE_NAME: &str = "huak.lock";
+
+/// The version of the lock file format.
+const LOCK_FILE_VERSION: u32 = 1;
+
+const LOCK_FILE_HEADER: &str =
+    "# This file is generated by huak. It should not be edited by hand.\n\n";
+
+/// The packages a project's dependencies resolve to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct LockFile {
+    /// The version of the lock file format.
+    version: u32,
+    /// A hash of the project's requirements the lock was resolved from.
+    content_hash: String,
+    /// The locked packages sorted by name.
+    #[serde(rename = "package", default)]
+    packages: Vec<LockedPackage>,
+}
+
+impl LockFile {
+    /// Resolve a project's dependencies and every optional dependency group into a
+    /// lock.
+    pub fn resolve<S: PackageSource>(project: &Project, source: S) -> HuakResult<LockFile> {
+        LockFile::resolve_with_progress(project, source, Progress::hidden())
+    }
+
+    /// Resolve a project's lock, reporting the packages fetched while resolving to a
+    /// progress display.
+    pub fn resolve_with_progress<S: PackageSource>(
+        project: &Project,
+        source: S,
+        progress: Progress,
+    ) -> HuakResult<LockFile> {
+        resolve_lock(project, source, progress, Vec::new())
+    }
+
+    /// Resolve a project's lock again after its requirements changed. Packages keep
+    /// the versions this lock pins while those still satisfy the requirements, except
+    /// for the freed packages, which are resolved like they were never locked.
+    pub fn update<S: PackageSource>(
+        &self,
+        project: &Project,
+        source: S,
+        progress: Progress,
+        free: &[&str],
+    ) -> HuakResult<LockFile> {
+        let free = free
+            .iter()
+            .map(|it| canonical_package_name(it))
+            .collect::<Vec<_>>();
+        let mut preferences = Vec::new();
+        for package in self.packages.iter() {
+            if free.contains(&canonical_package_name(&package.name)) {
+                continue;
+            }
+            let version = Version::from_str(&package.version).map_err(|it| {
+                HuakError::LockFileError(format!("{} has an invalid version: {it}", package.name))
+            })?;
+            preferences.push((package.name.clone(), version));
+        }
+        resolve_lock(project, source, progress, preferences)
+    }
+
+    /// Read a lock from a huak.lock file.
+    pub fn from_path(path: impl AsRef<Path>) -> HuakResult<LockFile> {
+        let contents = std::fs::read_to_string(path)?;
+        let lock: LockFile = toml::from_str(&contents)?;
+        if lock.version != LOCK_FILE_VERSION {
+            return Err(HuakError::LockFileError(format!(
+                "unsupported lock file version {}",
+                lock.version
+            )));
+        }
+        // Locked files are downloaded under their names.
+        let mut files = lock.packages.iter().flat_map(|it| it.files.iter());
+        if let Some(file) = files.find(|it| !is_plain_file_name(&it.name)) {
+            return Err(HuakError::LockFileError(format!(
+                "invalid file name {:?}",
+                file.name
+            )));
+        }
+        Ok(lock)
+    }
+
+    /// Write the lock to a file.
+    pub fn write_file(&self, path: impl AsRef<Path>) -> HuakResult<()> {
+        std::fs::write(path, self.to_string_pretty()?)?;
+        Ok(())
+    }
+
+    /// Serialize the lock. The output only depends on the lock's contents so the same
+    /// lock is always written byte-for-byte the same.
+    pub fn to_string_pretty(&self) -> HuakResult<String> {
+        Ok(format!(
+            "{LOCK_FILE_HEADER}{}",
+            toml::to_string_pretty(self)?
+        ))
+    }
+
+    /// Get the locked packages.
+    pub fn packages(&self) -> &[LockedPackage] {
+        &self.packages
+    }
+
+    /// Check if the lock was resolved from the project's current requirements.
+    pub fn is_up_to_date(&self, project: &Project) -> HuakResult<bool> {
+        Ok(self.content_hash == content_hash(project)?)
+    }
+
+    /// Get the locked packages that apply to an environment when some optional
+    /// dependency groups are installed.
+    pub fn packages_for(
+        &self,
+        environment: &MarkerEnvironment,
+        groups: &[&str],
+    ) -> HuakResult<Vec<&LockedPackage>> {
+        let mut packages = Vec::new();
+        for package in self.packages.iter() {
+            let applies = match package.markers.as_ref() {
+                Some(it) => MarkerTree::from_str(it)?.evaluate(environment, groups),
+                None => true,
+            };
+            if applies {
+                packages.push(package);
+            }
+        }
+        Ok(packages)
+    }
+}
+
+/// A package pinned by a lock.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LockedPackage {
+    /// The name of the package.
+    name: String,
+    /// The exact version of the package.
+    version: String,
+    /// The URL of the index the package is served from.
+    source: String,
+    /// The markers of the environments the package applies to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    markers: Option<String>,
+    /// The files that can be installed for the package.
+    #[serde(default)]
+    files: Vec<Artifact>,
+}
+
+impl LockedPackage {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn markers(&self) -> Option<&str> {
+        self.markers.as_deref()
+    }
+
+    pub fn files(&self) -> &[Artifact] {
+        &self.files
+    }
+
+    /// Get the package pinned to its locked version.
+    pub fn package(&self) -> HuakResult<Package> {
+        Package::from_str(&format!("{}=={}", self.name, self.version))
+    }
+}
+
+/// Resolve a project's dependencies and every optional dependency group into a lock,
+/// trying the preferred versions of packages first.
+fn resolve_lock<S: PackageSource>(
+    project: &Project,
+    source: S,
+    progress: Progress,
+    preferences: Vec<(String, Version)>,
+) -> HuakResult<LockFile> {
+    let mut requirements = project.dependencies()?;
+    let groups = optional_dependency_groups(project);
+    for group in groups.iter() {
+        let markers = MarkerTree::from_str(&format!("extra == \"{group}\""))?;
+        for mut package in project.optional_dependencey_group(group)? {
+            package.markers = MarkerTree::and([Some(markers.clone()), package.markers]);
+            requirements.push(package);
+        }
+    }
+    let groups = groups.iter().map(|it| it.as_str()).collect::<Vec<_>>();
+
+    let mut resolver = Resolver::new(source, MarkerEnvironment::default());
+    let resolved = resolver
+        .with_universal(true)
+        .with_progress(progress)
+        .with_preferences(preferences)
+        .with_requires_python(project.pyproject_toml().requires_python()?)
+        .resolve(&requirements, &groups)?;
+    let source = resolver.source();
+    let mut packages = Vec::new();
+    for package in resolved {
+        let version = match package.version() {
+            Some(it) => it.clone(),
+            None => continue,
+        };
+        packages.push(LockedPackage {
+            name: package.name().to_string(),
+            version: version.to_string(),
+            source: source.index_url(package.name()),
+            markers: package.markers().map(|it| it.to_string()),
+            files: source.artifacts(package.name(), &version)?,
+        });
+    }
+
+    Ok(LockFile {
+        version: LOCK_FILE_VERSION,
+        content_hash: content_hash(project)?,
+        packages,
+    })
+}
+
+/// Get the names of a project's optional dependency groups.
+fn optional_dependency_groups(project: &Project) -> Vec<String> {
+    let mut groups = project
+        .pyproject_toml()
+        .project
+        .as_ref()
+        .and_then(|it| it.optional_dependencies.as_ref())
+        .map(|it| it.keys().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+    groups.sort();
+    groups
+}
+
+/// Hash the requirements of a project that a lock is resolved from.
+fn content_hash(project: &Project) -> HuakResult<String> {
+    let mut dependencies = project
+        .pyproject_toml()
+        .dependencies()
+        .cloned()
+        .unwrap_or_default();
+    dependencies.sort();
+    let mut optional_dependencies = BTreeMap::new();
+    for group in optional_dependency_groups(project) {
+        let mut group_dependencies = project
+            .pyproject_toml()
+            .optional_dependencey_group(&group)
+            .cloned()
+            .unwrap_or_default();
+        group_dependencies.sort();
+        optional_dependencies.insert(group, group_dependencies);
+    }
+    let requires_python = project
+        .pyproject_toml()
+        .project
+        .as_ref()
+        .and_then(|it| it.requires_python.clone());
+    let requirements = serde_json::json!({
+        "dependencies": dependencies,
+        "optional-dependencies": optional_dependencies,
+        "requires-python": requires_python,
+        "indexes": project.pyproject_toml().index_config()?,
+    });
+    let digest = Sha256::digest(serde_json::to_string(&requirements)?.as_bytes());
+    Ok(format!("sha256:{digest:x}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_resources_dir_path;
+    use tempfile::tempdir;
+
+    /// A package source serving `<name>==<version>` packages without dependencies.
+    struct MockSource(Vec<&'static str>);
+
+    impl PackageSource for MockSource {
+        fn versions(&mut self, name: &str) -> HuakResult<Vec<Version>> {
+            Ok(self
+                .0
+                .iter()
+                .filter_map(|it| it.split_once("=="))
+                .filter(|(it, _)| canonical_package_name(it) == name)
+                .map(|(_, version)| Version::from_str(version).unwrap())
+                .collect())
+        }
+
+        fn dependencies(&mut self, _name: &str, _version: &Version) -> HuakResult<Vec<Package>> {
+            Ok(Vec::new())
+        }
+
+        fn artifacts(&mut self, name: &str, version: &Version) -> HuakResult<Vec<Artifact>> {
+            let file_name = format!("{name}-{version}-py3-none-any.whl");
+            Ok(vec![Artifact {
+                url: format!("https://example.com/{file_name}"),
+                name: file_name,
+                hash: Some("sha256:0000".to_string()),
+            }])
+        }
+
+        fn index_url(&self, _name: &str) -> String {
+            "https://example.com/simple".to_string()
+        }
+    }
+
+    fn mock_project() -> Project {
+        Project::from_manifest(test_resources_dir_path().join("mock-project/pyproject.toml"))
+            .unwrap()
+    }
+
+    fn mock_source() -> MockSource {
+        MockSource(vec![
+            "click==8.1.3",
+            "black==22.8.0",
+            "isort==5.12.0",
+            "pytest==7.2.1",
+            "mock==5.0.1",
+        ])
+    }
+
+    #[test]
+    fn lock_resolve_and_round_trip() {
+        let dir = tempdir().unwrap().into_path();
+        let project = mock_project();
+        let lock = LockFile::resolve(&project, mock_source()).unwrap();
+        let path = dir.join(LOCK_FILE_NAME);
+
+        lock.write_file(&path).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let read = LockFile::from_path(&path).unwrap();
+
+        assert_eq!(read, lock);
+        assert_eq!(read.to_string_pretty().unwrap(), contents);
+        assert!(read.is_up_to_date(&project).unwrap());
+        assert!(lock.packages().iter().all(|it| it.files().len() == 1));
+    }
+
+    #[test]
+    fn lock_packages_for_groups() {
+        let lock = LockFile::resolve(&mock_project(), mock_source()).unwrap();
+        let environment = MarkerEnvironment::default();
+
+        let names = |groups: &[&str]| {
+            lock.packages_for(&environment, groups)
+                .unwrap()
+                .iter()
+                .map(|it| it.name().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(&[]), ["black", "click", "isort"]);
+        assert_eq!(
+            names(&["test"]),
+            ["black", "click", "isort", "mock", "pytest"]
+        );
+    }
+
+    #[test]
+    fn lock_rejects_file_names_outside_of_download_dir() {
+        let dir = tempdir().unwrap().into_path();
+        let path = dir.join(LOCK_FILE_NAME);
+        let lock = LockFile::resolve(&mock_project(), mock_source()).unwrap();
+        let contents = lock
+            .to_string_pretty()
+            .unwrap()
+            .replace("name = \"click-", "name = \"../../click-");
+        std::fs::write(&path, contents).unwrap();
+
+        assert!(matches!(
+            LockFile::from_path(&path),
+            Err(HuakError::LockFileError(..))
+        ));
+    }
+
+    #[test]
+    fn lock_drift() {
+        let dir = tempdir().unwrap().into_path();
+        let manifest_path = dir.join("pyproject.toml");
+        std::fs::copy(
+            test_resources_dir_path().join("mock-project/pyproject.toml"),
+            &manifest_path,
+        )
+        .unwrap();
+        let lock = LockFile::resolve(&mock_project(), mock_source()).unwrap();
+
+        let mut project = Project::from_manifest(&manifest_path).unwrap();
+        assert!(lock.is_up_to_date(&project).unwrap());
+
+        project.add_dependency("requests").unwrap();
+        assert!(!lock.is_up_to_date(&project).unwrap());
+    }
+
+    #[test]
+    fn lock_update_keeps_locked_versions() {
+        let project = mock_project();
+        let lock = LockFile::resolve(&project, mock_source()).unwrap();
+        let mut source = mock_source();
+        source.0.extend(["pytest==7.3.0", "mock==5.1.0"]);
+
+        let updated = lock
+            .update(&project, source, Progress::hidden(), &["pytest"])
+            .unwrap();
+        let packages = updated
+            .packages()
+            .iter()
+            .map(|it| format!("{}=={}", it.name(), it.version()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            packages,
+            [
+                "black==22.8.0",
+                "click==8.1.3",
+                "isort==5.12.0",
+                "mock==5.0.1",
+                "pytest==7.3.0"
+            ]
+        );
+    }
+}
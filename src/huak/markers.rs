@@ -0,0 +1,692 @@
+///! This module implements parsing and evaluation of PEP 508 environment markers.
+///! See https://peps.python.org/pep-0508/#environment-markers
+use crate::error::{HuakError, HuakResult};
+use pep440_rs::{Version, VersionSpecifier};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, path::Path, process::Command, str::FromStr};
+
+/// A Python script printing the values of every marker variable as JSON.
+const MARKER_ENVIRONMENT_SCRIPT: &str = r#"import json, os, platform, sys
+
+def format_full_version(info):
+    version = "{0.major}.{0.minor}.{0.micro}".format(info)
+    if info.releaselevel != "final":
+        version += info.releaselevel[0] + str(info.serial)
+    return version
+
+print(json.dumps({
+    "implementation_name": sys.implementation.name,
+    "implementation_version": format_full_version(sys.implementation.version),
+    "os_name": os.name,
+    "platform_machine": platform.machine(),
+    "platform_python_implementation": platform.python_implementation(),
+    "platform_release": platform.release(),
+    "platform_system": platform.system(),
+    "platform_version": platform.version(),
+    "python_full_version": platform.python_version(),
+    "python_version": ".".join(platform.python_version_tuple()[:2]),
+    "sys_platform": sys.platform,
+}))
+"#;
+
+/// The values of the marker variables for a target Python environment.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct MarkerEnvironment {
+    pub implementation_name: String,
+    pub implementation_version: String,
+    pub os_name: String,
+    pub platform_machine: String,
+    pub platform_python_implementation: String,
+    pub platform_release: String,
+    pub platform_system: String,
+    pub platform_version: String,
+    pub python_full_version: String,
+    pub python_version: String,
+    pub sys_platform: String,
+}
+
+impl MarkerEnvironment {
+    /// Create a marker environment by querying a Python interpreter.
+    pub fn from_python_path(path: impl AsRef<Path>) -> HuakResult<MarkerEnvironment> {
+        let output = Command::new(path.as_ref())
+            .args(["-c", MARKER_ENVIRONMENT_SCRIPT])
+            .output()?;
+        if !output.status.success() {
+            return Err(HuakError::CommandError(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(serde_json::from_slice(&output.stdout)?)
+    }
+
+    /// Get the value of a marker variable.
+    fn get(&self, variable: &MarkerVariable) -> &str {
+        match variable {
+            MarkerVariable::ImplementationName => &self.implementation_name,
+            MarkerVariable::ImplementationVersion => &self.implementation_version,
+            MarkerVariable::OsName => &self.os_name,
+            MarkerVariable::PlatformMachine => &self.platform_machine,
+            MarkerVariable::PlatformPythonImplementation => &self.platform_python_implementation,
+            MarkerVariable::PlatformRelease => &self.platform_release,
+            MarkerVariable::PlatformSystem => &self.platform_system,
+            MarkerVariable::PlatformVersion => &self.platform_version,
+            MarkerVariable::PythonFullVersion => &self.python_full_version,
+            MarkerVariable::PythonVersion => &self.python_version,
+            MarkerVariable::SysPlatform => &self.sys_platform,
+            MarkerVariable::Extra => "",
+        }
+    }
+}
+
+/// A parsed marker expression tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarkerTree {
+    Expression(MarkerExpression),
+    And(Vec<MarkerTree>),
+    Or(Vec<MarkerTree>),
+}
+
+impl MarkerTree {
+    /// Evaluate the markers for an environment with a set of requested extras.
+    pub fn evaluate(&self, environment: &MarkerEnvironment, extras: &[&str]) -> bool {
+        match self {
+            MarkerTree::Expression(expression) => expression.evaluate(environment, extras),
+            MarkerTree::And(trees) => trees.iter().all(|it| it.evaluate(environment, extras)),
+            MarkerTree::Or(trees) => trees.iter().any(|it| it.evaluate(environment, extras)),
+        }
+    }
+
+    /// Evaluate only the markers' `extra` comparisons, treating every other
+    /// comparison as true. This checks if the markers could apply to some
+    /// environment with a set of requested extras.
+    pub(crate) fn evaluate_extras(&self, extras: &[&str]) -> bool {
+        match self {
+            MarkerTree::Expression(expression) => {
+                !expression.is_extra() || expression.evaluate(&MarkerEnvironment::default(), extras)
+            }
+            MarkerTree::And(trees) => trees.iter().all(|it| it.evaluate_extras(extras)),
+            MarkerTree::Or(trees) => trees.iter().any(|it| it.evaluate_extras(extras)),
+        }
+    }
+
+    /// Get the markers without their `extra` comparisons, which are treated as
+    /// true. `None` is returned if the markers always apply without them.
+    pub(crate) fn without_extras(&self) -> Option<MarkerTree> {
+        match self {
+            MarkerTree::Expression(expression) if expression.is_extra() => None,
+            MarkerTree::Expression(_) => Some(self.clone()),
+            MarkerTree::And(trees) => MarkerTree::and(trees.iter().map(|it| it.without_extras())),
+            MarkerTree::Or(trees) => MarkerTree::or(trees.iter().map(|it| it.without_extras())),
+        }
+    }
+
+    /// Combine optional markers with `and`, where `None` always applies.
+    pub(crate) fn and(trees: impl IntoIterator<Item = Option<MarkerTree>>) -> Option<MarkerTree> {
+        let mut parts = Vec::new();
+        for tree in trees.into_iter().flatten() {
+            match tree {
+                MarkerTree::And(trees) => parts.extend(trees),
+                tree => parts.push(tree),
+            }
+        }
+        parts.dedup();
+        match parts.len() {
+            0 => None,
+            1 => parts.pop(),
+            _ => Some(MarkerTree::And(parts)),
+        }
+    }
+
+    /// Combine optional markers with `or`, where `None` always applies.
+    pub(crate) fn or(trees: impl IntoIterator<Item = Option<MarkerTree>>) -> Option<MarkerTree> {
+        let mut parts: Vec<MarkerTree> = Vec::new();
+        for tree in trees {
+            match tree {
+                None => return None,
+                Some(MarkerTree::Or(trees)) => parts.extend(trees),
+                Some(tree) => parts.push(tree),
+            }
+        }
+        let mut unique: Vec<MarkerTree> = Vec::new();
+        for part in parts {
+            if !unique.contains(&part) {
+                unique.push(part);
+            }
+        }
+        match unique.len() {
+            0 => None,
+            1 => unique.pop(),
+            _ => Some(MarkerTree::Or(unique)),
+        }
+    }
+}
+
+impl FromStr for MarkerTree {
+    type Err = HuakError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser {
+            input: s,
+            tokens,
+            pos: 0,
+        };
+        let tree = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(tree),
+            Some(token) => Err(parser.error(&format!("unexpected token {token}"))),
+        }
+    }
+}
+
+impl Display for MarkerTree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MarkerTree::Expression(expression) => write!(f, "{expression}"),
+            MarkerTree::And(trees) => {
+                let parts = trees
+                    .iter()
+                    .map(|it| match it {
+                        MarkerTree::Or(_) => format!("({it})"),
+                        _ => it.to_string(),
+                    })
+                    .collect::<Vec<_>>();
+                write!(f, "{}", parts.join(" and "))
+            }
+            MarkerTree::Or(trees) => {
+                let parts = trees.iter().map(|it| it.to_string()).collect::<Vec<_>>();
+                write!(f, "{}", parts.join(" or "))
+            }
+        }
+    }
+}
+
+/// A single `<value> <op> <value>` comparison.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkerExpression {
+    pub l_value: MarkerValue,
+    pub operator: MarkerOperator,
+    pub r_value: MarkerValue,
+}
+
+impl MarkerExpression {
+    /// Check if the expression compares the `extra` variable.
+    fn is_extra(&self) -> bool {
+        matches!(
+            (&self.l_value, &self.r_value),
+            (MarkerValue::Variable(MarkerVariable::Extra), _)
+                | (_, MarkerValue::Variable(MarkerVariable::Extra))
+        )
+    }
+
+    fn evaluate(&self, environment: &MarkerEnvironment, extras: &[&str]) -> bool {
+        // Extras are compared by their normalized names against every requested extra.
+        let extra_side = match (&self.l_value, &self.r_value) {
+            (MarkerValue::Variable(MarkerVariable::Extra), MarkerValue::String(s))
+            | (MarkerValue::String(s), MarkerValue::Variable(MarkerVariable::Extra)) => Some(s),
+            _ => None,
+        };
+        if let Some(value) = extra_side {
+            let value = crate::canonical_package_name(value);
+            let requested = extras
+                .iter()
+                .any(|it| crate::canonical_package_name(it) == value);
+            return match self.operator {
+                MarkerOperator::Equal => requested,
+                MarkerOperator::NotEqual => !requested,
+                _ => false,
+            };
+        }
+        let resolve = |value: &MarkerValue| -> String {
+            match value {
+                MarkerValue::Variable(variable) => environment.get(variable).to_string(),
+                MarkerValue::String(s) => s.clone(),
+            }
+        };
+        compare(
+            &resolve(&self.l_value),
+            &self.operator,
+            &resolve(&self.r_value),
+        )
+    }
+}
+
+impl Display for MarkerExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.l_value, self.operator, self.r_value)
+    }
+}
+
+/// Compare two marker values using PEP 440 semantics when both sides are versions,
+/// falling back to Python's string comparison.
+fn compare(l_value: &str, operator: &MarkerOperator, r_value: &str) -> bool {
+    if let Some(op) = operator.version_operator() {
+        if let (Ok(specifier), Ok(version)) = (
+            VersionSpecifier::from_str(&format!("{op}{r_value}")),
+            Version::from_str(l_value),
+        ) {
+            return specifier.contains(&version);
+        }
+    }
+    match operator {
+        MarkerOperator::Equal => l_value == r_value,
+        MarkerOperator::NotEqual => l_value != r_value,
+        MarkerOperator::LessThan => l_value < r_value,
+        MarkerOperator::LessEqual => l_value <= r_value,
+        MarkerOperator::GreaterThan => l_value > r_value,
+        MarkerOperator::GreaterEqual => l_value >= r_value,
+        MarkerOperator::In => r_value.contains(l_value),
+        MarkerOperator::NotIn => !r_value.contains(l_value),
+        // There is no defined string behavior for these operators.
+        MarkerOperator::TildeEqual | MarkerOperator::ExactEqual => false,
+    }
+}
+
+/// Either side of a marker expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarkerValue {
+    Variable(MarkerVariable),
+    String(String),
+}
+
+impl Display for MarkerValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MarkerValue::Variable(variable) => write!(f, "{variable}"),
+            MarkerValue::String(s) if s.contains('"') => write!(f, "'{s}'"),
+            MarkerValue::String(s) => write!(f, "\"{s}\""),
+        }
+    }
+}
+
+/// The environment variables available to markers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerVariable {
+    ImplementationName,
+    ImplementationVersion,
+    OsName,
+    PlatformMachine,
+    PlatformPythonImplementation,
+    PlatformRelease,
+    PlatformSystem,
+    PlatformVersion,
+    PythonFullVersion,
+    PythonVersion,
+    SysPlatform,
+    Extra,
+}
+
+impl MarkerVariable {
+    fn from_name(name: &str) -> Option<MarkerVariable> {
+        let variable = match name {
+            "implementation_name" => MarkerVariable::ImplementationName,
+            "implementation_version" => MarkerVariable::ImplementationVersion,
+            "os_name" | "os.name" => MarkerVariable::OsName,
+            "platform_machine" | "platform.machine" => MarkerVariable::PlatformMachine,
+            "platform_python_implementation"
+            | "platform.python_implementation"
+            | "python_implementation" => MarkerVariable::PlatformPythonImplementation,
+            "platform_release" => MarkerVariable::PlatformRelease,
+            "platform_system" => MarkerVariable::PlatformSystem,
+            "platform_version" | "platform.version" => MarkerVariable::PlatformVersion,
+            "python_full_version" => MarkerVariable::PythonFullVersion,
+            "python_version" => MarkerVariable::PythonVersion,
+            "sys_platform" | "sys.platform" => MarkerVariable::SysPlatform,
+            "extra" => MarkerVariable::Extra,
+            _ => return None,
+        };
+        Some(variable)
+    }
+}
+
+impl Display for MarkerVariable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            MarkerVariable::ImplementationName => "implementation_name",
+            MarkerVariable::ImplementationVersion => "implementation_version",
+            MarkerVariable::OsName => "os_name",
+            MarkerVariable::PlatformMachine => "platform_machine",
+            MarkerVariable::PlatformPythonImplementation => "platform_python_implementation",
+            MarkerVariable::PlatformRelease => "platform_release",
+            MarkerVariable::PlatformSystem => "platform_system",
+            MarkerVariable::PlatformVersion => "platform_version",
+            MarkerVariable::PythonFullVersion => "python_full_version",
+            MarkerVariable::PythonVersion => "python_version",
+            MarkerVariable::SysPlatform => "sys_platform",
+            MarkerVariable::Extra => "extra",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Comparison operators available to markers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkerOperator {
+    Equal,
+    NotEqual,
+    LessThan,
+    LessEqual,
+    GreaterThan,
+    GreaterEqual,
+    TildeEqual,
+    ExactEqual,
+    In,
+    NotIn,
+}
+
+impl MarkerOperator {
+    /// The PEP 440 operator string for operators with version semantics.
+    fn version_operator(&self) -> Option<&'static str> {
+        match self {
+            MarkerOperator::Equal => Some("=="),
+            MarkerOperator::NotEqual => Some("!="),
+            MarkerOperator::LessThan => Some("<"),
+            MarkerOperator::LessEqual => Some("<="),
+            MarkerOperator::GreaterThan => Some(">"),
+            MarkerOperator::GreaterEqual => Some(">="),
+            MarkerOperator::TildeEqual => Some("~="),
+            MarkerOperator::ExactEqual => Some("==="),
+            MarkerOperator::In | MarkerOperator::NotIn => None,
+        }
+    }
+}
+
+impl Display for MarkerOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MarkerOperator::In => write!(f, "in"),
+            MarkerOperator::NotIn => write!(f, "not in"),
+            _ => write!(f, "{}", self.version_operator().unwrap_or_default()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    LeftParen,
+    RightParen,
+    String(String),
+    Word(String),
+    Operator(String),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::LeftParen => write!(f, "`(`"),
+            Token::RightParen => write!(f, "`)`"),
+            Token::String(s) => write!(f, "string \"{s}\""),
+            Token::Word(s) | Token::Operator(s) => write!(f, "`{s}`"),
+        }
+    }
+}
+
+fn tokenize(s: &str) -> HuakResult<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => (),
+            '(' => tokens.push(Token::LeftParen),
+            ')' => tokens.push(Token::RightParen),
+            '"' | '\'' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, it)) if it == c => break,
+                        Some((_, it)) => value.push(it),
+                        None => {
+                            return Err(HuakError::MarkerParseError(format!(
+                                "unterminated string starting at position {i} in `{s}`"
+                            )))
+                        }
+                    }
+                }
+                tokens.push(Token::String(value));
+            }
+            '<' | '>' | '=' | '!' | '~' => {
+                let mut op = c.to_string();
+                while let Some((_, it)) = chars.peek() {
+                    if matches!(it, '<' | '>' | '=' | '!' | '~') {
+                        op.push(*it);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Operator(op));
+            }
+            c if c.is_ascii_alphanumeric() || c == '_' => {
+                let mut word = c.to_string();
+                while let Some((_, it)) = chars.peek() {
+                    if it.is_ascii_alphanumeric() || matches!(it, '_' | '.') {
+                        word.push(*it);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Word(word));
+            }
+            _ => {
+                return Err(HuakError::MarkerParseError(format!(
+                    "unexpected character `{c}` at position {i} in `{s}`"
+                )))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// A recursive descent parser over marker tokens.
+struct Parser<'a> {
+    input: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> HuakError {
+        HuakError::MarkerParseError(format!("{message} in `{}`", self.input))
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(it)) if it == word => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn parse_or(&mut self) -> HuakResult<MarkerTree> {
+        let mut trees = vec![self.parse_and()?];
+        while self.eat_word("or") {
+            trees.push(self.parse_and()?);
+        }
+        match trees.len() {
+            1 => Ok(trees.remove(0)),
+            _ => Ok(MarkerTree::Or(trees)),
+        }
+    }
+
+    fn parse_and(&mut self) -> HuakResult<MarkerTree> {
+        let mut trees = vec![self.parse_atom()?];
+        while self.eat_word("and") {
+            trees.push(self.parse_atom()?);
+        }
+        match trees.len() {
+            1 => Ok(trees.remove(0)),
+            _ => Ok(MarkerTree::And(trees)),
+        }
+    }
+
+    fn parse_atom(&mut self) -> HuakResult<MarkerTree> {
+        if self.peek() == Some(&Token::LeftParen) {
+            self.next();
+            let tree = self.parse_or()?;
+            return match self.next() {
+                Some(Token::RightParen) => Ok(tree),
+                _ => Err(self.error("expected `)`")),
+            };
+        }
+        let l_value = self.parse_value()?;
+        let operator = self.parse_operator()?;
+        let r_value = self.parse_value()?;
+        Ok(MarkerTree::Expression(MarkerExpression {
+            l_value,
+            operator,
+            r_value,
+        }))
+    }
+
+    fn parse_value(&mut self) -> HuakResult<MarkerValue> {
+        match self.next() {
+            Some(Token::String(s)) => Ok(MarkerValue::String(s)),
+            Some(Token::Word(word)) => match MarkerVariable::from_name(&word) {
+                Some(variable) => Ok(MarkerValue::Variable(variable)),
+                None => Err(self.error(&format!("unknown marker variable `{word}`"))),
+            },
+            Some(token) => Err(self.error(&format!("expected a marker value, found {token}"))),
+            None => Err(self.error("expected a marker value")),
+        }
+    }
+
+    fn parse_operator(&mut self) -> HuakResult<MarkerOperator> {
+        let operator = match self.next() {
+            Some(Token::Operator(op)) => match op.as_str() {
+                "==" => MarkerOperator::Equal,
+                "!=" => MarkerOperator::NotEqual,
+                "<" => MarkerOperator::LessThan,
+                "<=" => MarkerOperator::LessEqual,
+                ">" => MarkerOperator::GreaterThan,
+                ">=" => MarkerOperator::GreaterEqual,
+                "~=" => MarkerOperator::TildeEqual,
+                "===" => MarkerOperator::ExactEqual,
+                _ => return Err(self.error(&format!("invalid marker operator `{op}`"))),
+            },
+            Some(Token::Word(word)) if word == "in" => MarkerOperator::In,
+            Some(Token::Word(word)) if word == "not" => {
+                if !self.eat_word("in") {
+                    return Err(self.error("expected `in` after `not`"));
+                }
+                MarkerOperator::NotIn
+            }
+            Some(token) => {
+                return Err(self.error(&format!("expected a marker operator, found {token}")))
+            }
+            None => return Err(self.error("expected a marker operator")),
+        };
+        Ok(operator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linux_environment() -> MarkerEnvironment {
+        MarkerEnvironment {
+            implementation_name: "cpython".to_string(),
+            implementation_version: "3.10.4".to_string(),
+            os_name: "posix".to_string(),
+            platform_machine: "x86_64".to_string(),
+            platform_python_implementation: "CPython".to_string(),
+            platform_release: "5.15.0".to_string(),
+            platform_system: "Linux".to_string(),
+            platform_version: "#1 SMP".to_string(),
+            python_full_version: "3.10.4".to_string(),
+            python_version: "3.10".to_string(),
+            sys_platform: "linux".to_string(),
+        }
+    }
+
+    #[test]
+    fn marker_evaluation() {
+        let env = linux_environment();
+        let cases = [
+            (
+                r#"sys_platform == "linux" and python_version >= "3.8""#,
+                true,
+            ),
+            (r#"python_version < "3.9""#, false),
+            // Version comparison, not string comparison ("3.10" < "3.9" as strings).
+            (r#"python_version > "3.9""#, true),
+            (r#""3.11" > python_version"#, true),
+            (
+                r#"os_name == "nt" or platform_machine in "x86_64 amd64""#,
+                true,
+            ),
+            (r#"platform_system not in "Windows Darwin""#, true),
+            (r#"python_full_version == "3.10.*""#, true),
+            (
+                r#"(os_name == "nt" or sys_platform == "win32") and python_version > "2""#,
+                false,
+            ),
+        ];
+
+        for (s, expected) in cases {
+            let tree = MarkerTree::from_str(s).unwrap();
+            assert_eq!(tree.evaluate(&env, &[]), expected, "{s}");
+        }
+    }
+
+    #[test]
+    fn marker_extras() {
+        let env = linux_environment();
+        let tree = MarkerTree::from_str(r#"extra == "Socks_Proxy""#).unwrap();
+
+        assert!(tree.evaluate(&env, &["socks-proxy"]));
+        assert!(!tree.evaluate(&env, &["security"]));
+        assert!(!tree.evaluate(&env, &[]));
+
+        let tree = MarkerTree::from_str(r#"sys_platform == "win32" and extra == "socks""#).unwrap();
+
+        assert!(tree.evaluate_extras(&["socks"]));
+        assert!(!tree.evaluate_extras(&[]));
+        assert_eq!(
+            tree.without_extras().unwrap().to_string(),
+            r#"sys_platform == "win32""#
+        );
+        assert_eq!(
+            MarkerTree::from_str(r#"extra == "socks""#)
+                .unwrap()
+                .without_extras(),
+            None
+        );
+    }
+
+    #[test]
+    fn marker_canonical_display() {
+        let tree = MarkerTree::from_str(
+            "python_version>='3.8' and (sys_platform=='linux' or os.name == \"nt\")",
+        )
+        .unwrap();
+
+        assert_eq!(
+            tree.to_string(),
+            r#"python_version >= "3.8" and (sys_platform == "linux" or os_name == "nt")"#
+        );
+        assert_eq!(MarkerTree::from_str(&tree.to_string()).unwrap(), tree);
+    }
+
+    #[test]
+    fn marker_invalid() {
+        assert!(MarkerTree::from_str(r#"python_version"#).is_err());
+        assert!(MarkerTree::from_str(r#"python_version >= "3.8"#).is_err());
+        assert!(MarkerTree::from_str(r#"unknown == "1""#).is_err());
+        assert!(MarkerTree::from_str(r#"os_name == "nt" and"#).is_err());
+        assert!(MarkerTree::from_str(r#"(os_name == "nt""#).is_err());
+    }
+}
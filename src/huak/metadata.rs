@@ -0,0 +1,268 @@
+///! This module implements parsing for the files of installed distributions' .dist-info
+///! directories.
+///! See https://packaging.python.org/en/latest/specifications/recording-installed-packages/
+use crate::error::{HuakError, HuakResult};
+use std::str::FromStr;
+
+/// RFC 822 style headers as `(key, value)` pairs.
+pub(crate) type Headers = Vec<(String, String)>;
+
+/// Parse RFC 822 style `Key: Value` headers like those of METADATA and WHEEL files.
+/// Headers are returned in the order they were read, so repeated keys are kept. Any
+/// message body following the first blank line is returned with them.
+pub(crate) fn parse_headers(contents: &str) -> HuakResult<(Headers, Option<String>)> {
+    let mut headers = Headers::new();
+    let mut lines = contents.lines().enumerate();
+    for (i, line) in lines.by_ref() {
+        if line.trim().is_empty() {
+            break;
+        }
+        // Continuation lines start with whitespace. Metadata 1.x descriptions also
+        // prefix them with `|` to preserve leading whitespace.
+        if line.starts_with([' ', '\t']) {
+            let (_, value) = headers.last_mut().ok_or_else(|| {
+                HuakError::PackageMetadataError(format!(
+                    "line {}: continuation without a header",
+                    i + 1
+                ))
+            })?;
+            let line = line.trim_start();
+            value.push('\n');
+            value.push_str(line.strip_prefix('|').unwrap_or(line));
+            continue;
+        }
+        let (key, value) = line.split_once(':').ok_or_else(|| {
+            HuakError::PackageMetadataError(format!("line {}: expected `Key: Value`", i + 1))
+        })?;
+        headers.push((key.trim().to_string(), value.trim().to_string()));
+    }
+    let body = lines.map(|(_, line)| line).collect::<Vec<_>>().join("\n");
+    let body = match body.trim().is_empty() {
+        true => None,
+        false => Some(body),
+    };
+    Ok((headers, body))
+}
+
+/// Metadata about a wheel archive from its WHEEL file.
+/// See https://packaging.python.org/en/latest/specifications/binary-distribution-format/#the-dist-info-directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WheelMetadata {
+    /// The version of the wheel specification the archive follows.
+    wheel_version: String,
+    /// The tool that built the archive.
+    generator: Option<String>,
+    /// Whether the archive's root is installed to purelib rather than platlib.
+    root_is_purelib: bool,
+    /// The archive's compatibility tags such as `py3-none-any`.
+    tags: Vec<String>,
+    /// The archive's build number.
+    build: Option<String>,
+}
+
+impl WheelMetadata {
+    /// Get the version of the wheel specification the archive follows.
+    pub fn wheel_version(&self) -> &str {
+        &self.wheel_version
+    }
+
+    /// Get the tool that built the archive.
+    pub fn generator(&self) -> Option<&str> {
+        self.generator.as_deref()
+    }
+
+    /// Check if the archive's root is installed to purelib rather than platlib.
+    pub fn root_is_purelib(&self) -> bool {
+        self.root_is_purelib
+    }
+
+    /// Get the archive's compatibility tags.
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Get the archive's build number.
+    pub fn build(&self) -> Option<&str> {
+        self.build.as_deref()
+    }
+}
+
+impl FromStr for WheelMetadata {
+    type Err = HuakError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (headers, _) = parse_headers(s)?;
+        let mut wheel_version = None;
+        let mut generator = None;
+        let mut root_is_purelib = false;
+        let mut tags = Vec::new();
+        let mut build = None;
+        for (key, value) in headers {
+            match key.as_str() {
+                "Wheel-Version" => wheel_version = Some(value),
+                "Generator" => generator = Some(value),
+                "Root-Is-Purelib" => root_is_purelib = value == "true",
+                "Tag" => tags.push(value),
+                "Build" => build = Some(value),
+                _ => (),
+            }
+        }
+        let wheel_version = wheel_version.ok_or_else(|| {
+            HuakError::PackageMetadataError("WHEEL is missing Wheel-Version".to_string())
+        })?;
+        Ok(WheelMetadata {
+            wheel_version,
+            generator,
+            root_is_purelib,
+            tags,
+            build,
+        })
+    }
+}
+
+/// An entry point advertised by a distribution in its entry_points.txt file.
+/// See https://packaging.python.org/en/latest/specifications/entry-points/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryPoint {
+    /// The group the entry point belongs to such as `console_scripts`.
+    group: String,
+    /// The name of the entry point within its group.
+    name: String,
+    /// The object reference as `module:attr [extras]`.
+    value: String,
+}
+
+impl EntryPoint {
+    /// Get the group the entry point belongs to.
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+
+    /// Get the name of the entry point.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the object reference of the entry point.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Get the module of the entry point's object reference.
+    pub fn module(&self) -> &str {
+        self.object_reference().0
+    }
+
+    /// Get the attribute of the entry point's object reference if it has one.
+    pub fn attr(&self) -> Option<&str> {
+        self.object_reference().1
+    }
+
+    /// Check if the entry point is a console or GUI script.
+    pub fn is_script(&self) -> bool {
+        matches!(self.group.as_str(), "console_scripts" | "gui_scripts")
+    }
+
+    fn object_reference(&self) -> (&str, Option<&str>) {
+        let reference = self.value.split('[').next().unwrap_or_default().trim();
+        match reference.split_once(':') {
+            Some((module, attr)) => (module.trim(), Some(attr.trim())),
+            None => (reference, None),
+        }
+    }
+}
+
+/// Parse the entry points of an entry_points.txt file.
+pub(crate) fn parse_entry_points(contents: &str) -> HuakResult<Vec<EntryPoint>> {
+    let mut entry_points = Vec::new();
+    let mut group = None;
+    for (i, line) in contents.lines().map(|it| it.trim()).enumerate() {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            group = Some(line[1..line.len() - 1].trim().to_string());
+            continue;
+        }
+        let error = |message: &str| {
+            HuakError::PackageMetadataError(format!("entry points line {}: {message}", i + 1))
+        };
+        let group = group
+            .clone()
+            .ok_or_else(|| error("entry point outside of a group"))?;
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| error("expected `name = value`"))?;
+        entry_points.push(EntryPoint {
+            group,
+            name: name.trim().to_string(),
+            value: value.trim().to_string(),
+        });
+    }
+    Ok(entry_points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headers_with_continuations_and_body() {
+        let contents = r#"Metadata-Version: 1.1
+Name: demo
+Description: first line
+        |  indented line
+Classifier: A
+Classifier: B
+
+a body
+"#;
+
+        let (headers, body) = parse_headers(contents).unwrap();
+
+        assert_eq!(
+            headers,
+            vec![
+                ("Metadata-Version".to_string(), "1.1".to_string()),
+                ("Name".to_string(), "demo".to_string()),
+                (
+                    "Description".to_string(),
+                    "first line\n  indented line".to_string()
+                ),
+                ("Classifier".to_string(), "A".to_string()),
+                ("Classifier".to_string(), "B".to_string()),
+            ]
+        );
+        assert_eq!(body.as_deref(), Some("a body"));
+    }
+
+    #[test]
+    fn wheel_metadata() {
+        let wheel = WheelMetadata::from_str(
+            "Wheel-Version: 1.0\nGenerator: bdist_wheel (0.40.0)\nRoot-Is-Purelib: false\nTag: cp311-cp311-manylinux_2_17_x86_64\nTag: cp311-cp311-manylinux2014_x86_64\n",
+        )
+        .unwrap();
+
+        assert_eq!(wheel.wheel_version(), "1.0");
+        assert_eq!(wheel.generator(), Some("bdist_wheel (0.40.0)"));
+        assert!(!wheel.root_is_purelib());
+        assert_eq!(wheel.tags().len(), 2);
+        assert!(WheelMetadata::from_str("Generator: test\n").is_err());
+    }
+
+    #[test]
+    fn entry_points() {
+        let entry_points = parse_entry_points(
+            "[console_scripts]\nblack = black:patched_main [d]\n\n[pytest11]\nxdist = xdist.plugin\n",
+        )
+        .unwrap();
+
+        assert_eq!(entry_points.len(), 2);
+        assert!(entry_points[0].is_script());
+        assert_eq!(entry_points[0].module(), "black");
+        assert_eq!(entry_points[0].attr(), Some("patched_main"));
+        assert_eq!(entry_points[1].group(), "pytest11");
+        assert_eq!(entry_points[1].attr(), None);
+        assert!(parse_entry_points("black = black:main\n").is_err());
+    }
+}
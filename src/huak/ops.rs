@@ -1,24 +1,34 @@
 ///! This module implements various operations to interact with valid workspaces
 ///! existing on a system.
 use crate::{
+    auth,
     error::{HuakError, HuakResult},
-    sys::{self, Terminal, Verbosity},
-    Package, Project, ProjectType, PyProjectToml,
+    git,
+    sys::{self, OutputFormat, Terminal, Verbosity},
+    CredentialProvider, Event, InstallerConfig, LockFile, Package, PackageIndexClient, Project,
+    ProjectType, PyProjectToml, Resolver, SourceBuild, SupportedTags, VirtualEnvironment,
+    LOCK_FILE_NAME,
 };
 use std::{
     path::{Path, PathBuf},
     process::Command,
 };
 
+/// The repository projects are published to unless they configure another.
+const DEFAULT_PUBLISH_URL: &str = "https://upload.pypi.org/legacy/";
+
 #[derive(Default)]
 pub struct OperationConfig {
     root: PathBuf,
     build_options: Option<BuildOptions>,
+    clean_options: Option<CleanOptions>,
     format_options: Option<FormatOptions>,
     lint_options: Option<LintOptions>,
     publish_options: Option<PublishOptions>,
     installer_options: Option<InstallerOptions>,
     terminal_options: Option<TerminalOptions>,
+    test_options: Option<TestOptions>,
+    workspace_options: Option<WorkspaceOptions>,
 }
 
 impl OperationConfig {
@@ -47,6 +57,15 @@ impl OperationConfig {
         self
     }
 
+    pub fn clean_options(&self) -> Option<&CleanOptions> {
+        self.clean_options.as_ref()
+    }
+
+    pub fn with_clean_options(&mut self, options: CleanOptions) -> &mut OperationConfig {
+        self.clean_options = Some(options);
+        self
+    }
+
     pub fn format_options(&self) -> Option<&FormatOptions> {
         self.format_options.as_ref()
     }
@@ -91,21 +110,74 @@ impl OperationConfig {
         self.terminal_options = Some(options);
         self
     }
+
+    pub fn test_options(&self) -> Option<&TestOptions> {
+        self.test_options.as_ref()
+    }
+
+    pub fn with_test_options(&mut self, options: TestOptions) -> &mut OperationConfig {
+        self.test_options = Some(options);
+        self
+    }
+
+    pub fn workspace_options(&self) -> Option<&WorkspaceOptions> {
+        self.workspace_options.as_ref()
+    }
+
+    pub fn with_workspace_options(&mut self, options: WorkspaceOptions) -> &mut OperationConfig {
+        self.workspace_options = Some(options);
+        self
+    }
 }
 
-pub struct BuildOptions;
-pub struct FormatOptions;
-pub struct LintOptions;
-pub struct PublishOptions;
-pub struct InstallerOptions;
+pub struct BuildOptions {
+    /// Config settings passed to the build backend as `KEY=VALUE`.
+    pub args: Option<Vec<String>>,
+}
+pub struct CleanOptions {
+    /// Remove __pycache__ directories from the project.
+    pub include_pycache: bool,
+    /// Remove compiled bytecode (.pyc) files from the project.
+    pub include_compiled_bytecode: bool,
+}
+pub struct FormatOptions {
+    /// Extra arguments passed to the formatter.
+    pub args: Option<Vec<String>>,
+}
+pub struct LintOptions {
+    /// Extra arguments passed to the linter.
+    pub args: Option<Vec<String>>,
+    /// Auto-fix fixable lint conflicts.
+    pub fix: bool,
+}
+pub struct PublishOptions {
+    /// Extra arguments passed to the publishing tool.
+    pub args: Option<Vec<String>>,
+}
+pub struct InstallerOptions {
+    /// Resolve and install packages only from the cache.
+    pub offline: bool,
+    /// Only install packages pinned by huak.lock with a hash to verify them with.
+    pub require_hashes: bool,
+}
 pub struct TerminalOptions {
     pub verbosity: Verbosity,
+    /// How the terminal's output is formatted.
+    pub format: OutputFormat,
+}
+pub struct TestOptions {
+    /// Extra arguments passed to the test runner.
+    pub args: Option<Vec<String>>,
+}
+pub struct WorkspaceOptions {
+    /// Initialize new projects as git repositories.
+    pub uses_git: bool,
 }
 
 /// Activate a Python virtual environment.
 pub fn activate_venv(config: &OperationConfig) -> HuakResult<()> {
-    let venv = crate::find_venv()?;
-    let mut terminal = Terminal::new();
+    let venv = crate::find_venv(config.root())?;
+    let mut terminal = terminal_from_config(config);
     venv.activate_with_terminal(&mut terminal)
 }
 
@@ -114,16 +186,22 @@ pub fn add_project_dependencies(
     config: &OperationConfig,
     dependencies: &[Package],
 ) -> HuakResult<()> {
-    let mut venv = crate::find_venv()?;
-    let mut packages = venv.installed_packages()?;
-    packages.extend_from_slice(dependencies);
-    // TODO: Propagate installer configuration (potentially per-package)
-    venv.install_packages(&packages)?;
     let manifest_path = config.root().join("pyproject.toml");
     let mut project = Project::from_manifest(&manifest_path)?;
-    for package in &packages {
-        project.add_dependency(&package.dependency_string());
+    let mut terminal = terminal_from_config(config);
+    let mut venv = find_or_create_venv(config, &project, &mut terminal)?;
+    configure_installer(config, &mut venv, &project, &terminal)?;
+    for package in dependencies {
+        project.add_dependency(&package.dependency_string())?;
     }
+    install_added_dependencies(
+        config,
+        &mut venv,
+        &project,
+        &[],
+        dependencies,
+        &mut terminal,
+    )?;
     project.pyproject_toml().write_file(&manifest_path)
 }
 
@@ -133,73 +211,136 @@ pub fn add_project_optional_dependencies(
     dependencies: &[Package],
     group: &str,
 ) -> HuakResult<()> {
-    let mut venv = crate::find_venv()?;
-    let mut packages = venv.installed_packages()?;
-    packages.extend_from_slice(dependencies);
-    // TODO: Propagate installer configuration (potentially per-package)
-    venv.install_packages(&packages)?;
     let manifest_path = config.root().join("pyproject.toml");
     let mut project = Project::from_manifest(&manifest_path)?;
-    for package in &packages {
-        project.add_optional_dependency(&package.dependency_string(), group);
+    let mut terminal = terminal_from_config(config);
+    let mut venv = find_or_create_venv(config, &project, &mut terminal)?;
+    configure_installer(config, &mut venv, &project, &terminal)?;
+    for package in dependencies {
+        project.add_optional_dependency(&package.dependency_string(), group)?;
     }
+    install_added_dependencies(
+        config,
+        &mut venv,
+        &project,
+        &[group],
+        dependencies,
+        &mut terminal,
+    )?;
     project.pyproject_toml().write_file(&manifest_path)
 }
 
-/// Build the Python project as installable package.
+/// Build the Python project's source distribution and wheel into its dist directory
+/// with the PEP 517 build backend from its pyproject.toml. The backend runs in an
+/// isolated environment, so the `build` package doesn't need to be installed.
 pub fn build_project(config: &OperationConfig) -> HuakResult<()> {
-    let venv = crate::find_venv()?;
-    let mut terminal = Terminal::new();
-    let mut paths = sys::env_path_values();
-    paths.insert(0, venv.root().to_path_buf());
-    let mut cmd = Command::new("build");
-    let mut cmd = cmd
-        .env(
-            "PATH",
-            std::env::join_paths(paths).map_err(|e| HuakError::InternalError(e.to_string()))?,
-        )
-        .current_dir(config.root());
-    // TODO: Propagate CLI config
-    terminal.run_command(cmd)
+    let venv = crate::find_venv(config.root())?;
+    let project = Project::from_manifest(config.root().join("pyproject.toml"))?;
+    let mut terminal = terminal_from_config(config);
+    let mut build = SourceBuild::new(
+        config.root(),
+        venv.python_path(),
+        &installer_config(config, &project)?,
+    )?;
+    if let Some(args) = config.build_options().and_then(|it| it.args.as_ref()) {
+        build.with_config_settings(
+            args.iter()
+                .map(|it| match it.split_once('=') {
+                    Some((key, value)) => (key.to_string(), value.to_string()),
+                    None => (it.to_string(), String::new()),
+                })
+                .collect(),
+        );
+    }
+    let dist_path = config.root().join("dist");
+    for path in [
+        build.build_sdist(&dist_path)?,
+        build.build_wheel(&dist_path)?,
+    ] {
+        terminal.emit(Event::project_built(&path))?;
+    }
+    Ok(())
 }
 
-/// Clean the dist directory.
+/// Clean the project's build outputs from its dist directory, and its bytecode caches
+/// if the clean options include them. Caches are only removed from the project's own
+/// directories: hidden directories like .git, virtual environments of any name, and
+/// symlinks are skipped.
 pub fn clean_project(config: &OperationConfig) -> HuakResult<()> {
-    todo!()
+    let dist_path = config.root().join("dist");
+    if dist_path.exists() {
+        for entry in std::fs::read_dir(&dist_path)? {
+            let entry = entry?;
+            let path = entry.path();
+            // Symlinked directories are unlinked rather than emptied.
+            if entry.file_type()?.is_dir() {
+                std::fs::remove_dir_all(&path)?;
+            } else {
+                std::fs::remove_file(&path)?;
+            }
+        }
+    }
+    let (include_pycache, include_compiled_bytecode) = match config.clean_options() {
+        Some(it) => (it.include_pycache, it.include_compiled_bytecode),
+        None => (false, false),
+    };
+    if include_pycache || include_compiled_bytecode {
+        let mut stack = vec![config.root().clone()];
+        while let Some(dir) = stack.pop() {
+            for entry in std::fs::read_dir(&dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                // Symlinks are never followed so nothing outside the project is removed.
+                if entry.file_type()?.is_symlink() {
+                    continue;
+                }
+                if path.is_dir() {
+                    let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+                    if is_hidden || path.join("pyvenv.cfg").exists() {
+                        continue;
+                    }
+                    if include_pycache && path.file_name() == Some("__pycache__".as_ref()) {
+                        std::fs::remove_dir_all(&path)?;
+                    } else {
+                        stack.push(path);
+                    }
+                } else if include_compiled_bytecode && path.extension() == Some("pyc".as_ref()) {
+                    std::fs::remove_file(&path)?;
+                }
+            }
+        }
+    }
+    Ok(())
 }
 
 /// Format the Python project's source code.
 pub fn format_project(config: &OperationConfig) -> HuakResult<()> {
-    let venv = crate::find_venv()?;
-    let mut terminal = Terminal::new();
-    let mut paths = sys::env_path_values();
-    paths.insert(0, venv.root().to_path_buf());
+    let venv = crate::find_venv(config.root())?;
+    let mut terminal = terminal_from_config(config);
     let mut cmd = Command::new("black");
-    let mut cmd = cmd
-        .env(
-            "PATH",
-            std::env::join_paths(paths).map_err(|e| HuakError::InternalError(e.to_string()))?,
-        )
-        .current_dir(config.root())
-        .arg(".");
-    // TODO: Propagate CLI config
-    terminal.run_command(cmd)
+    make_venv_command(&mut cmd, &venv)?;
+    cmd.arg(".");
+    if let Some(args) = config.format_options().and_then(|it| it.args.as_ref()) {
+        cmd.args(args);
+    }
+    cmd.current_dir(config.root());
+    terminal.run_command(&mut cmd)
 }
 
 /// Initilize an existing Python project.
 pub fn init_project(config: &OperationConfig) -> HuakResult<()> {
     let manifest_path = config.root().join("pyproject.toml");
+    if manifest_path.exists() {
+        return Err(HuakError::ProjectTomlExistsError);
+    }
     let pyproject_toml = PyProjectToml::default();
-    pyproject_toml.write_file(manifest_path)
+    pyproject_toml.write_file(manifest_path)?;
+    init_git(config)
 }
 
 /// Install a Python project's dependencies to an environment.
 pub fn install_project_dependencies(config: &OperationConfig) -> HuakResult<()> {
-    let mut venv = crate::find_venv()?;
-    let project = Project::from_manifest(config.root().join("pyproject.toml"))?;
-    let packages = project.dependencies()?;
-    // TODO: Propagate installer configuration (potentially per-package)
-    venv.install_packages(&packages)
+    install_project_groups(config, &[])
 }
 
 /// Install groups of a Python project's optional dependencies to an environment.
@@ -207,66 +348,76 @@ pub fn install_project_optional_dependencies(
     config: &OperationConfig,
     group: &str,
 ) -> HuakResult<()> {
-    let mut venv = crate::find_venv()?;
+    install_project_groups(config, &[group])
+}
+
+/// Resolve a Python project's dependencies and write them to its huak.lock file.
+/// Packages already locked keep their versions unless the project's requirements
+/// no longer allow them.
+pub fn lock_project(config: &OperationConfig) -> HuakResult<()> {
     let project = Project::from_manifest(config.root().join("pyproject.toml"))?;
-    let packages = project.optional_dependencey_group(group)?;
-    // TODO: Propagate installer configuration (potentially per-package)
-    venv.install_packages(&packages)
+    let mut terminal = terminal_from_config(config);
+    let lock = resolve_lock_file(config, &project, &[], &terminal)?;
+    for package in lock.packages() {
+        terminal.emit(Event::package_resolved(package))?;
+    }
+    lock.write_file(config.root().join(LOCK_FILE_NAME))
 }
 
 /// Lint a Python project's source code.
 pub fn lint_project(config: &OperationConfig) -> HuakResult<()> {
-    let venv = crate::find_venv()?;
-    let mut terminal = Terminal::new();
-    let mut paths = sys::env_path_values();
-    paths.insert(0, venv.root().to_path_buf());
+    let venv = crate::find_venv(config.root())?;
+    let mut terminal = terminal_from_config(config);
     let mut cmd = Command::new("ruff");
-    let mut cmd = cmd
-        .env(
-            "PATH",
-            std::env::join_paths(paths).map_err(|e| HuakError::InternalError(e.to_string()))?,
-        )
-        .current_dir(config.root())
-        .arg(".");
-    // TODO: Propagate CLI config (including --fix)
-    terminal.run_command(cmd)
+    make_venv_command(&mut cmd, &venv)?;
+    cmd.arg(".");
+    if let Some(options) = config.lint_options() {
+        if options.fix {
+            cmd.arg("--fix");
+        }
+        if let Some(args) = options.args.as_ref() {
+            cmd.args(args);
+        }
+    }
+    cmd.current_dir(config.root());
+    terminal.run_command(&mut cmd)
 }
 
 /// Create a new Python project with all defaults on the system.
 pub fn create_new_default_project(config: &OperationConfig) -> HuakResult<()> {
     let project = Project::default();
-    project.write_project(config.root())
+    project.write_project(config.root())?;
+    init_git(config)
 }
 
 /// Create a new library-like Python project on the system.
 pub fn create_new_lib_project(config: &OperationConfig) -> HuakResult<()> {
-    let project = Project::from(ProjectType::Application);
-    project.write_project(config.root())
+    let project = Project::from(ProjectType::Library);
+    project.write_project(config.root())?;
+    init_git(config)
 }
 
 /// Create a new application-like Python project on the system.
 pub fn create_new_app_project(config: &OperationConfig) -> HuakResult<()> {
-    let project = Project::from(ProjectType::Library);
-    project.write_project(config.root())
+    let project = Project::from(ProjectType::Application);
+    project.write_project(config.root())?;
+    init_git(config)
 }
 
 /// Publish the Python project as to a registry.
 pub fn publish_project(config: &OperationConfig) -> HuakResult<()> {
-    let venv = crate::find_venv()?;
-    let mut terminal = Terminal::new();
-    let mut paths = sys::env_path_values();
-    paths.insert(0, venv.root().to_path_buf());
+    let venv = crate::find_venv(config.root())?;
+    let mut terminal = terminal_from_config(config);
     let mut cmd = Command::new("twine");
-    let mut cmd = cmd
-        .env(
-            "PATH",
-            std::env::join_paths(paths).map_err(|e| HuakError::InternalError(e.to_string()))?,
-        )
-        .current_dir(config.root())
-        .arg("upload")
-        .arg("dist/*");
-    // TODO: Propagate CLI config
-    terminal.run_command(cmd)
+    make_venv_command(&mut cmd, &venv)?;
+    cmd.arg("upload").arg("dist/*");
+    if let Some(args) = config.publish_options().and_then(|it| it.args.as_ref()) {
+        cmd.args(args);
+    }
+    let project = Project::from_manifest(config.root().join("pyproject.toml"))?;
+    configure_publish_credentials(&mut cmd, &project)?;
+    cmd.current_dir(config.root());
+    terminal.run_command(&mut cmd)
 }
 
 /// Remove a dependency from a Python project.
@@ -274,12 +425,15 @@ pub fn remove_project_dependencies(
     config: &OperationConfig,
     dependency_names: &[&str],
 ) -> HuakResult<()> {
-    let mut venv = crate::find_venv()?;
-    let mut project = Project::from_manifest(config.root().join("pyproject.toml"))?;
+    let mut venv = crate::find_venv(config.root())?;
+    let manifest_path = config.root().join("pyproject.toml");
+    let mut project = Project::from_manifest(&manifest_path)?;
     for dependency in dependency_names {
-        project.remove_dependency(dependency);
+        project.remove_dependency(dependency)?;
     }
-    venv.uninstall_packages(dependency_names)
+    project.pyproject_toml().write_file(&manifest_path)?;
+    venv.uninstall_packages(dependency_names)?;
+    update_lock_file(config, &project, &[], &terminal_from_config(config))
 }
 
 /// Remove a dependency from a Python project.
@@ -288,59 +442,372 @@ pub fn remove_project_optional_dependencies(
     dependency_names: &[&str],
     group: &str,
 ) -> HuakResult<()> {
-    let mut venv = crate::find_venv()?;
-    let mut project = Project::from_manifest(config.root().join("pyproject.toml"))?;
+    let mut venv = crate::find_venv(config.root())?;
+    let manifest_path = config.root().join("pyproject.toml");
+    let mut project = Project::from_manifest(&manifest_path)?;
     for dependency in dependency_names {
-        project.remove_optional_dependency(dependency, group);
+        project.remove_optional_dependency(dependency, group)?;
     }
-    venv.uninstall_packages(dependency_names)
+    project.pyproject_toml().write_file(&manifest_path)?;
+    venv.uninstall_packages(dependency_names)?;
+    update_lock_file(config, &project, &[], &terminal_from_config(config))
 }
 
-/// Run a command from within a Python project's context.
-pub fn run_command_str_with_context(config: &OperationConfig, command: &str) -> HuakResult<()> {
-    let venv = crate::find_venv()?;
-    let mut terminal = Terminal::new();
-    let mut paths = sys::env_path_values();
-    paths.insert(0, venv.root().to_path_buf());
-    let mut cmd = Command::new("twine");
-    let cmd = cmd
-        .env(
-            "PATH",
-            std::env::join_paths(paths).map_err(|e| HuakError::InternalError(e.to_string()))?,
-        )
-        .current_dir(config.root())
-        .arg(command);
-    // TODO: Propagate CLI config
-    terminal.run_command(cmd)
+/// Run a command and its arguments from within a Python project's context. The
+/// arguments are passed to the command as they are, without a shell.
+pub fn run_command_with_context(config: &OperationConfig, command: &[String]) -> HuakResult<()> {
+    let (program, args) = command.split_first().ok_or_else(|| {
+        HuakError::HuakConfigurationError("no command was given to run".to_string())
+    })?;
+    let venv = crate::find_venv(config.root())?;
+    let mut terminal = terminal_from_config(config);
+    let mut cmd = Command::new(program);
+    make_venv_command(&mut cmd, &venv)?;
+    cmd.args(args).current_dir(config.root());
+    terminal.run_command(&mut cmd)
 }
 
 /// Run a Python project's tests.
 pub fn test_project(config: &OperationConfig) -> HuakResult<()> {
-    let venv = crate::find_venv()?;
-    let mut terminal = Terminal::new();
-    let mut paths = sys::env_path_values();
-    paths.insert(0, venv.root().to_path_buf());
+    let venv = crate::find_venv(config.root())?;
+    let mut terminal = terminal_from_config(config);
     let mut cmd = Command::new("pytest");
-    let cmd = cmd
-        .env(
-            "PATH",
-            std::env::join_paths(paths).map_err(|e| HuakError::InternalError(e.to_string()))?,
-        )
-        .current_dir(config.root());
-    // TODO: Propagate CLI config
-    terminal.run_command(cmd)
+    make_venv_command(&mut cmd, &venv)?;
+    if let Some(args) = config.test_options().and_then(|it| it.args.as_ref()) {
+        cmd.args(args);
+    }
+    cmd.current_dir(config.root());
+    terminal.run_command(&mut cmd)
 }
 
 /// Display the version of the Python project.
 pub fn display_project_version(config: &OperationConfig) -> HuakResult<()> {
     let project = Project::from_manifest(config.root().join("pyproject.toml"))?;
+    let mut terminal = terminal_from_config(config);
+    let version = project
+        .pyproject_toml()
+        .project_version()
+        .ok_or(HuakError::ProjectVersionNotFound)?;
+    terminal.emit(Event::ProjectVersion {
+        version: version.to_string(),
+    })
+}
+
+/// Initialize the project's root directory as a git repository if the workspace
+/// options ask for it.
+fn init_git(config: &OperationConfig) -> HuakResult<()> {
+    match config.workspace_options() {
+        Some(it) if it.uses_git && !config.root().join(".git").exists() => git::init(config.root()),
+        _ => Ok(()),
+    }
+}
+
+/// Create a terminal using the operation's terminal options.
+fn terminal_from_config(config: &OperationConfig) -> Terminal {
     let mut terminal = Terminal::new();
-    let verbosity = match config.terminal_options() {
-        Some(it) => it.verbosity,
-        None => Verbosity::default(),
+    let (verbosity, format) = match config.terminal_options() {
+        Some(it) => (it.verbosity, it.format),
+        None => (Verbosity::default(), OutputFormat::default()),
     };
     terminal.set_verbosity(verbosity);
-    todo!()
+    terminal.set_output_format(format);
+    terminal
+}
+
+/// Report the packages installed to an environment to the terminal.
+fn report_installed_packages(packages: &[Package], terminal: &mut Terminal) -> HuakResult<()> {
+    for package in packages {
+        terminal.emit(Event::package_installed(package))?;
+    }
+    Ok(())
+}
+
+/// Report the packages that were uninstalled from an environment.
+fn report_uninstalled_packages(packages: &[Package], terminal: &mut Terminal) -> HuakResult<()> {
+    for package in packages {
+        terminal.emit(Event::package_uninstalled(package))?;
+    }
+    Ok(())
+}
+
+/// Modify a command so that it runs with the virtual environment's executables
+/// directory first on its PATH.
+fn make_venv_command(cmd: &mut Command, venv: &VirtualEnvironment) -> HuakResult<()> {
+    let mut paths = sys::env_path_values();
+    paths.insert(0, venv.executables_dir_path());
+    cmd.env(
+        "PATH",
+        std::env::join_paths(paths).map_err(|e| HuakError::InternalError(e.to_string()))?,
+    )
+    .env("VIRTUAL_ENV", venv.root());
+    Ok(())
+}
+
+/// Find the project's virtual environment, creating one named `DEFAULT_VENV_NAME` at
+/// the project root if none exists. The environment's Python must satisfy the
+/// project's `requires-python`, and new environments use the latest interpreter that
+/// does.
+fn find_or_create_venv(
+    config: &OperationConfig,
+    project: &Project,
+    terminal: &mut Terminal,
+) -> HuakResult<VirtualEnvironment> {
+    let requires_python = project.pyproject_toml().requires_python()?;
+    let venv = match crate::find_venv(config.root()) {
+        Err(HuakError::VenvNotFoundError) => {
+            let dir = crate::find_project_root(config.root()).unwrap_or(config.root().clone());
+            terminal.status(
+                "Creating",
+                format!(
+                    "virtual environment at {}",
+                    dir.join(crate::DEFAULT_VENV_NAME).display()
+                ),
+            )?;
+            crate::create_default_venv(dir, requires_python.as_ref())
+        }
+        result => result,
+    }?;
+    if let Some(requires_python) = requires_python.as_ref() {
+        venv.check_requires_python(requires_python)?;
+    }
+    Ok(venv)
+}
+
+/// Install a project's dependencies and optional dependency groups to an environment.
+/// If the project has a huak.lock file the packages are installed strictly from it,
+/// and the lock must be up to date with the project's pyproject.toml. The environment
+/// is then synced with the lock: installed packages it doesn't lock for the groups
+/// are uninstalled.
+fn install_project_groups(config: &OperationConfig, groups: &[&str]) -> HuakResult<()> {
+    let project = Project::from_manifest(config.root().join("pyproject.toml"))?;
+    let lock_path = config.root().join(LOCK_FILE_NAME);
+    // Without a lock the only hashes are the ones served next to the files they're
+    // meant to verify.
+    if is_require_hashes(config) && !lock_path.exists() {
+        return Err(HuakError::RequireHashesUnlockedError(
+            project
+                .pyproject_toml()
+                .project_name()
+                .unwrap_or("the project")
+                .to_string(),
+        ));
+    }
+    let mut terminal = terminal_from_config(config);
+    let mut venv = find_or_create_venv(config, &project, &mut terminal)?;
+    configure_installer(config, &mut venv, &project, &terminal)?;
+    let requested = direct_dependency_names(&project, groups)?;
+    let requested = requested.iter().map(|it| it.as_str()).collect::<Vec<_>>();
+    if lock_path.exists() {
+        let lock = LockFile::from_path(&lock_path)?;
+        if !lock.is_up_to_date(&project)? {
+            return Err(HuakError::LockFileOutdatedError);
+        }
+        let packages = lock.packages_for(&venv.marker_environment()?, groups)?;
+        let installed_packages = venv.install_locked_packages(&packages, &requested)?;
+        report_installed_packages(&installed_packages, &mut terminal)?;
+        let uninstalled_packages = venv.uninstall_unlocked_packages(&packages)?;
+        return report_uninstalled_packages(&uninstalled_packages, &mut terminal);
+    }
+    let packages = resolve_project_dependencies(config, &venv, &project, groups, &terminal)?;
+    // TODO: Propagate installer configuration (potentially per-package)
+    let installed_packages = venv.install_packages(&packages, &requested)?;
+    report_installed_packages(&installed_packages, &mut terminal)
+}
+
+/// Install a project's dependencies after packages were added to its requirements.
+/// If the project has a huak.lock file, it's resolved once with the added packages
+/// freed, the packages are installed from the new lock, and the lock is written.
+/// Otherwise the project's requirements are resolved and installed directly.
+fn install_added_dependencies(
+    config: &OperationConfig,
+    venv: &mut VirtualEnvironment,
+    project: &Project,
+    groups: &[&str],
+    dependencies: &[Package],
+    terminal: &mut Terminal,
+) -> HuakResult<()> {
+    let requested = direct_dependency_names(project, groups)?;
+    let requested = requested.iter().map(|it| it.as_str()).collect::<Vec<_>>();
+    let lock_path = config.root().join(LOCK_FILE_NAME);
+    if lock_path.exists() {
+        let names = dependencies.iter().map(|it| it.name()).collect::<Vec<_>>();
+        let lock = resolve_lock_file(config, project, &names, terminal)?;
+        let packages = lock.packages_for(&venv.marker_environment()?, groups)?;
+        let installed_packages = venv.install_locked_packages(&packages, &requested)?;
+        report_installed_packages(&installed_packages, terminal)?;
+        return lock.write_file(lock_path);
+    }
+    let packages = resolve_project_dependencies(config, venv, project, groups, terminal)?;
+    // TODO: Propagate installer configuration (potentially per-package)
+    let installed_packages = venv.install_packages(&packages, &requested)?;
+    report_installed_packages(&installed_packages, terminal)
+}
+
+/// Update a project's huak.lock file if it has one. The freed packages are the ones
+/// whose requirements changed; every other package keeps its locked version if it can.
+fn update_lock_file(
+    config: &OperationConfig,
+    project: &Project,
+    free: &[&str],
+    terminal: &Terminal,
+) -> HuakResult<()> {
+    let lock_path = config.root().join(LOCK_FILE_NAME);
+    if !lock_path.exists() {
+        return Ok(());
+    }
+    resolve_lock_file(config, project, free, terminal)?.write_file(lock_path)
+}
+
+/// Resolve a project's lock from the indexes it's configured with. If the project is
+/// already locked, packages other than the freed ones keep their locked versions
+/// while those still satisfy the project's requirements.
+fn resolve_lock_file(
+    config: &OperationConfig,
+    project: &Project,
+    free: &[&str],
+    terminal: &Terminal,
+) -> HuakResult<LockFile> {
+    let progress = terminal.progress();
+    let mut client = index_client(config, project)?;
+    client.with_progress(progress.clone());
+    if let Some(python_path) = lock_build_python_path(config, project)? {
+        client.with_build_python(python_path);
+    }
+    let lock_path = config.root().join(LOCK_FILE_NAME);
+    if lock_path.exists() {
+        LockFile::from_path(lock_path)?.update(project, client, progress, free)
+    } else {
+        LockFile::resolve_with_progress(project, client, progress)
+    }
+}
+
+/// Get the interpreter source distributions are built with to read their metadata
+/// while locking a project. The project's environment is preferred over the latest
+/// interpreter satisfying its `requires-python`. Without either, packages only
+/// released as source distributions can't be locked.
+fn lock_build_python_path(
+    config: &OperationConfig,
+    project: &Project,
+) -> HuakResult<Option<PathBuf>> {
+    if let Ok(venv) = crate::find_venv(config.root()) {
+        return Ok(Some(venv.python_path()));
+    }
+    let requires_python = project.pyproject_toml().requires_python()?;
+    Ok(crate::find_python_path(requires_python.as_ref()).ok())
+}
+
+/// Resolve a project's dependencies and optional dependency groups into the full set
+/// of pinned packages to install.
+fn resolve_project_dependencies(
+    config: &OperationConfig,
+    venv: &VirtualEnvironment,
+    project: &Project,
+    groups: &[&str],
+    terminal: &Terminal,
+) -> HuakResult<Vec<Package>> {
+    let mut requirements = project.dependencies()?;
+    for group in groups {
+        requirements.extend(project.optional_dependencey_group(group)?);
+    }
+    let environment = venv.marker_environment()?;
+    let progress = terminal.progress();
+    let mut client = index_client(config, project)?;
+    client
+        .with_supported_tags(SupportedTags::from_marker_environment(&environment)?)
+        .with_build_python(venv.python_path())
+        .with_progress(progress.clone());
+    let mut resolver = Resolver::new(client, environment);
+    resolver
+        .with_requires_python(project.pyproject_toml().requires_python()?)
+        .with_progress(progress)
+        .resolve(&requirements, groups)
+}
+
+/// Get the names of the packages a project depends on directly with its optional
+/// dependency groups. These are the packages the user asked for.
+fn direct_dependency_names(project: &Project, groups: &[&str]) -> HuakResult<Vec<String>> {
+    let mut names = project
+        .dependencies()?
+        .iter()
+        .map(|it| it.name().to_string())
+        .collect::<Vec<_>>();
+    for group in groups {
+        names.extend(
+            project
+                .optional_dependencey_group(group)?
+                .iter()
+                .map(|it| it.name().to_string()),
+        );
+    }
+    Ok(names)
+}
+
+/// Configure a twine command to upload to the repository a project is published to
+/// using the credentials found for it.
+fn configure_publish_credentials(cmd: &mut Command, project: &Project) -> HuakResult<()> {
+    let pyproject_toml = project.pyproject_toml();
+    let publish_url = pyproject_toml.publish_url()?;
+    let url = reqwest::Url::parse(publish_url.as_deref().unwrap_or(DEFAULT_PUBLISH_URL))
+        .map_err(|e| HuakError::HuakConfigurationError(format!("invalid publish url: {e}")))?;
+    if publish_url.is_some() {
+        cmd.env(
+            "TWINE_REPOSITORY_URL",
+            auth::strip_credentials(&url).as_str(),
+        );
+    }
+    let provider = CredentialProvider::new();
+    if let Some(credentials) = provider.credentials(&url, Some(auth::PUBLISH_ENV_PREFIX))? {
+        let (username, password) = credentials.username_password();
+        cmd.env("TWINE_USERNAME", username);
+        if let Some(password) = password {
+            cmd.env("TWINE_PASSWORD", password);
+        }
+    }
+    Ok(())
+}
+
+/// Get a client for the package indexes a project is configured with.
+fn index_client(config: &OperationConfig, project: &Project) -> HuakResult<PackageIndexClient> {
+    let mut client = PackageIndexClient::new();
+    client
+        .with_index_config(project.pyproject_toml().index_config()?)
+        .with_offline(is_offline(config));
+    Ok(client)
+}
+
+/// Configure an environment's installer to install a project's packages from the
+/// indexes the project is configured with.
+fn configure_installer(
+    config: &OperationConfig,
+    venv: &mut VirtualEnvironment,
+    project: &Project,
+    terminal: &Terminal,
+) -> HuakResult<()> {
+    venv.with_installer_config(&installer_config(config, project)?)
+        .with_progress(terminal.progress());
+    Ok(())
+}
+
+/// Get the configuration packages for a project are installed with.
+fn installer_config(config: &OperationConfig, project: &Project) -> HuakResult<InstallerConfig> {
+    let mut installer_config = InstallerConfig::new();
+    installer_config
+        .with_index_config(project.pyproject_toml().index_config()?)
+        .with_offline(is_offline(config))
+        .with_require_hashes(is_require_hashes(config));
+    Ok(installer_config)
+}
+
+/// Check if an operation should only use the cache.
+fn is_offline(config: &OperationConfig) -> bool {
+    config.installer_options().is_some_and(|it| it.offline)
+}
+
+/// Check if an operation should only install locked packages with hashes.
+fn is_require_hashes(config: &OperationConfig) -> bool {
+    config
+        .installer_options()
+        .is_some_and(|it| it.require_hashes)
 }
 
 /// NOTE: Operations are meant to be executed on projects and environments.
@@ -377,7 +844,7 @@ mod tests {
         let ser_toml =
             PyProjectToml::from_path(dir.join("mock-project").join("pyproject.toml")).unwrap();
 
-        assert!(venv.find_site_packages_package("ruff").is_some());
+        assert!(venv.find_site_packages_package("ruff").unwrap().is_some());
         assert!(deps
             .iter()
             .all(|item| project.dependencies().unwrap().contains(item)));
@@ -392,6 +859,100 @@ mod tests {
             .contains(&item.dependency_string())));
     }
 
+    #[test]
+    fn test_add_project_dependencies_from_sdist() {
+        let dir = tempdir().unwrap().into_path();
+        let sdist = std::fs::read(crate::pep517::write_test_sdist(&dir)).unwrap();
+        let url = crate::index::serve(vec![
+            (
+                "/simple/demo/".to_string(),
+                "text/html".to_string(),
+                br#"<a href="/files/demo-1.0.tar.gz">demo-1.0.tar.gz</a>"#.to_vec(),
+            ),
+            (
+                "/files/demo-1.0.tar.gz".to_string(),
+                "application/octet-stream".to_string(),
+                sdist,
+            ),
+        ]);
+        let root = dir.join("project");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("pyproject.toml"),
+            format!(
+                "[project]\nname = \"mock-project\"\nversion = \"0.0.1\"\ndependencies = []\n\n[[tool.huak.index]]\nname = \"local\"\nurl = \"{url}/simple\"\ndefault = true\n"
+            ),
+        )
+        .unwrap();
+        let mut config = OperationConfig::new();
+        let config = config.with_root(&root);
+
+        add_project_dependencies(config, &[Package::from_str("demo").unwrap()]).unwrap();
+
+        let venv = VirtualEnvironment::from_path(root.join(".venv")).unwrap();
+        let project = Project::from_manifest(root.join("pyproject.toml")).unwrap();
+
+        assert_eq!(
+            venv.find_site_packages_package("demo")
+                .unwrap()
+                .unwrap()
+                .dependency_string(),
+            "demo==1.0"
+        );
+        assert!(project
+            .pyproject_toml()
+            .dependencies()
+            .unwrap()
+            .contains(&"demo".to_string()));
+    }
+
+    #[test]
+    fn test_add_and_sync_locked_project_dependencies() {
+        let dir = tempdir().unwrap().into_path();
+        let wheel = std::fs::read(crate::wheel::write_test_wheel(&dir)).unwrap();
+        let url = crate::index::serve(vec![
+            (
+                "/simple/demo/".to_string(),
+                "text/html".to_string(),
+                format!(
+                    r#"<a href="/files/demo-1.0-py3-none-any.whl#sha256={}">demo-1.0-py3-none-any.whl</a>"#,
+                    crate::cache::hex_digest(&wheel)
+                )
+                .into_bytes(),
+            ),
+            (
+                "/files/demo-1.0-py3-none-any.whl".to_string(),
+                "application/octet-stream".to_string(),
+                wheel,
+            ),
+        ]);
+        let root = dir.join("project");
+        std::fs::create_dir_all(&root).unwrap();
+        let manifest = format!(
+            "[project]\nname = \"mock-project\"\nversion = \"0.0.1\"\ndependencies = []\n\n[[tool.huak.index]]\nname = \"local\"\nurl = \"{url}/simple\"\ndefault = true\n"
+        );
+        std::fs::write(root.join("pyproject.toml"), &manifest).unwrap();
+        let mut config = OperationConfig::new();
+        let config = config.with_root(&root);
+        lock_project(config).unwrap();
+
+        add_project_dependencies(config, &[Package::from_str("demo").unwrap()]).unwrap();
+
+        let venv = VirtualEnvironment::from_path(root.join(".venv")).unwrap();
+        let lock = LockFile::from_path(root.join(LOCK_FILE_NAME)).unwrap();
+        let project = Project::from_manifest(root.join("pyproject.toml")).unwrap();
+
+        assert!(venv.find_site_packages_package("demo").unwrap().is_some());
+        assert!(lock.is_up_to_date(&project).unwrap());
+        assert_eq!(lock.packages()[0].name(), "demo");
+
+        std::fs::write(root.join("pyproject.toml"), &manifest).unwrap();
+        lock_project(config).unwrap();
+        install_project_dependencies(config).unwrap();
+
+        assert!(venv.installed_packages().unwrap().is_empty());
+    }
+
     #[test]
     fn test_add_optional_project_dependencies() {
         let dir = tempdir().unwrap().into_path();
@@ -408,7 +969,7 @@ mod tests {
         let ser_toml =
             PyProjectToml::from_path(dir.join("mock-project").join("pyproject.toml")).unwrap();
 
-        assert!(venv.find_site_packages_package("ruff").is_some());
+        assert!(venv.find_site_packages_package("ruff").unwrap().is_some());
         assert!(deps.iter().all(|item| project
             .optional_dependencey_group("test")
             .unwrap()
@@ -436,7 +997,67 @@ mod tests {
 
     #[test]
     fn test_clean_project() {
-        todo!()
+        let dir = tempdir().unwrap().into_path();
+        crate::fs::copy_dir(
+            &test_resources_dir_path().join("mock-project"),
+            &dir.join("mock-project"),
+        )
+        .unwrap();
+        let mut config = OperationConfig::new();
+        let config = config
+            .with_root(dir.join("mock-project"))
+            .with_clean_options(CleanOptions {
+                include_pycache: true,
+                include_compiled_bytecode: true,
+            });
+
+        clean_project(config).unwrap();
+
+        let dist = std::fs::read_dir(config.root().join("dist")).unwrap();
+        let pycaches = config.root().join("tests").join("__pycache__");
+        let bytecode = config
+            .root()
+            .join("src")
+            .join("mock_project")
+            .join("__init__.pyc");
+
+        assert_eq!(dist.count(), 0);
+        assert!(!pycaches.exists());
+        assert!(!bytecode.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_clean_project_skips_symlinks_and_venvs() {
+        let dir = tempdir().unwrap().into_path();
+        let outside = dir.join("outside");
+        std::fs::create_dir_all(outside.join("__pycache__")).unwrap();
+        std::fs::write(outside.join("module.pyc"), "").unwrap();
+        let root = dir.join("project");
+        std::fs::create_dir_all(root.join(".venv").join("__pycache__")).unwrap();
+        std::fs::create_dir_all(root.join(".git").join("__pycache__")).unwrap();
+        std::fs::create_dir_all(root.join(".tox").join("__pycache__")).unwrap();
+        std::fs::create_dir_all(root.join("env").join("lib").join("__pycache__")).unwrap();
+        std::fs::write(root.join("env").join("pyvenv.cfg"), "home = /usr/bin\n").unwrap();
+        std::fs::create_dir_all(root.join("src").join("__pycache__")).unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("linked")).unwrap();
+        let mut config = OperationConfig::new();
+        let config = config
+            .with_root(root.clone())
+            .with_clean_options(CleanOptions {
+                include_pycache: true,
+                include_compiled_bytecode: true,
+            });
+
+        clean_project(config).unwrap();
+
+        assert!(!root.join("src").join("__pycache__").exists());
+        assert!(root.join(".venv").join("__pycache__").exists());
+        assert!(root.join(".git").join("__pycache__").exists());
+        assert!(root.join(".tox").join("__pycache__").exists());
+        assert!(root.join("env").join("lib").join("__pycache__").exists());
+        assert!(outside.join("__pycache__").exists());
+        assert!(outside.join("module.pyc").exists());
     }
 
     #[test]
@@ -473,6 +1094,7 @@ def fn( ):
         let dir = tempdir().unwrap().into_path();
         let mut config = OperationConfig::new();
         let config = config.with_root(dir.join("mock-project"));
+        std::fs::create_dir(config.root()).unwrap();
 
         init_project(&config).unwrap();
 
@@ -494,12 +1116,12 @@ def fn( ):
         let config = config.with_root(dir.join("mock-project"));
         let mut venv = VirtualEnvironment::from_path(".venv").unwrap();
         venv.uninstall_packages(&["black"]).unwrap();
-        let had_black = venv.find_site_packages_package("black").is_some();
+        let had_black = venv.find_site_packages_package("black").unwrap().is_some();
 
         install_project_dependencies(&config).unwrap();
 
         assert!(!had_black);
-        assert!(venv.find_site_packages_package("black").is_some());
+        assert!(venv.find_site_packages_package("black").unwrap().is_some());
     }
 
     #[test]
@@ -510,12 +1132,31 @@ def fn( ):
         let config = config.with_root(dir.join("mock-project"));
         let mut venv = VirtualEnvironment::from_path(".venv").unwrap();
         venv.uninstall_packages(&["pytest"]).unwrap();
-        let had_pytest = venv.find_site_packages_package("pytest").is_some();
+        let had_pytest = venv.find_site_packages_package("pytest").unwrap().is_some();
 
         install_project_optional_dependencies(&config, "test").unwrap();
 
         assert!(!had_pytest);
-        assert!(venv.find_site_packages_package("pytest").is_some());
+        assert!(venv.find_site_packages_package("pytest").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_install_project_dependencies_require_hashes() {
+        let dir = tempdir().unwrap().into_path();
+        crate::fs::copy_dir(&test_resources_dir_path().join("mock-project"), &dir).unwrap();
+        let mut config = OperationConfig::new();
+        let config = config
+            .with_root(&dir)
+            .with_installer_options(InstallerOptions {
+                offline: false,
+                require_hashes: true,
+            });
+
+        assert!(matches!(
+            install_project_dependencies(config),
+            Err(HuakError::RequireHashesUnlockedError(..))
+        ));
+        assert!(!dir.join(".venv").exists());
     }
 
     #[test]
@@ -528,7 +1169,12 @@ def fn( ):
         let dir = tempdir().unwrap().into_path();
         crate::fs::copy_dir(&test_resources_dir_path().join("mock-project"), &dir).unwrap();
         let mut config = OperationConfig::new();
-        let config = config.with_root(dir.join("mock-project"));
+        let config = config
+            .with_root(dir.join("mock-project"))
+            .with_lint_options(LintOptions {
+                args: None,
+                fix: true,
+            });
         let project = Project::from_manifest(config.root().join("pyproject.toml")).unwrap();
         let lint_fix_filepath = project
             .root()
@@ -550,8 +1196,7 @@ def fn():
 "#;
         std::fs::write(&lint_fix_filepath, pre_fix_str).unwrap();
 
-        // TODO: Add --fix to LintOptions
-        lint_project(&config).unwrap();
+        lint_project(config).unwrap();
 
         let post_fix_str = std::fs::read_to_string(&lint_fix_filepath).unwrap();
 
@@ -582,19 +1227,16 @@ def fn():
         let project = Project::from_manifest(config.root().join("pyproject.toml")).unwrap();
         let test_file_filepath = project.root().join("tests").join("test_version.py");
         let test_file = std::fs::read_to_string(&test_file_filepath).unwrap();
-        let expected_test_file = format!(
-            r#"from {} import __version__
+        let expected_test_file = r#"from mock_project import __version__
 
 
 def test_version():
     __version__
-"#,
-            project.pyproject_toml().project_name().unwrap()
-        );
+"#;
         let init_file_filepath = project
             .root()
             .join("src")
-            .join("project")
+            .join("mock_project")
             .join("__init__.py");
         let init_file = std::fs::read_to_string(&init_file_filepath).unwrap();
         let expected_init_file = "__version__ = \"0.0.1\"
@@ -622,16 +1264,19 @@ def test_version():
 
         let project = Project::from_manifest(config.root().join("pyproject.toml")).unwrap();
         let ser_toml = project.pyproject_toml();
-        let main_file_filepath = project.root().join("src").join("project").join("main.py");
+        let main_file_filepath = project
+            .root()
+            .join("src")
+            .join("mock_project")
+            .join("main.py");
         let main_file = std::fs::read_to_string(&main_file_filepath).unwrap();
-        let expected_main_file = "\
-def main():
-print(\"Hello, World!\")
+        let expected_main_file = r#"def main():
+    print("Hello, World!")
 
 
-if __name__ == \"__main__\":
-main()
-";
+if __name__ == "__main__":
+    main()
+"#;
 
         assert_eq!(
             ser_toml
@@ -642,7 +1287,7 @@ main()
                 .scripts
                 .as_ref()
                 .unwrap()[ser_toml.project_name().unwrap()],
-            format!("{}.main:main", ser_toml.project_name().unwrap())
+            "mock_project.main:main"
         );
         assert_eq!(main_file, expected_main_file);
 
@@ -663,7 +1308,7 @@ main()
         let config = config.with_root(dir.join("mock-project"));
         let project = Project::from_manifest(config.root().join("pyproject.toml")).unwrap();
         let venv = VirtualEnvironment::from_path(PathBuf::from(".venv")).unwrap();
-        let black_package = venv.find_site_packages_package("black");
+        let black_package = venv.find_site_packages_package("black").unwrap();
         let venv_had_black = black_package.is_some();
         let black_package = black_package.unwrap();
         let toml_had_black = project
@@ -676,13 +1321,13 @@ main()
 
         let project = Project::from_manifest(config.root().join("pyproject.toml")).unwrap();
         let mut venv = VirtualEnvironment::from_path(PathBuf::from(".venv")).unwrap();
-        let venv_has_black = venv.find_site_packages_package("black").is_some();
+        let venv_has_black = venv.find_site_packages_package("black").unwrap().is_some();
         let toml_has_black = project
             .pyproject_toml()
             .dependencies()
             .unwrap()
             .contains(&black_package.dependency_string());
-        venv.install_packages(&[black_package]).unwrap();
+        venv.install_packages(&[black_package], &["black"]).unwrap();
 
         assert!(venv_had_black);
         assert!(toml_had_black);
@@ -698,7 +1343,7 @@ main()
         let config = config.with_root(dir.join("mock-project"));
         let project = Project::from_manifest(config.root().join("pyproject.toml")).unwrap();
         let venv = VirtualEnvironment::from_path(PathBuf::from(".venv")).unwrap();
-        let pytest_package = venv.find_site_packages_package("pytest");
+        let pytest_package = venv.find_site_packages_package("pytest").unwrap();
         let venv_had_pytest = pytest_package.is_some();
         let pytest_package = pytest_package.unwrap();
         let toml_had_pytest = project
@@ -711,13 +1356,14 @@ main()
 
         let project = Project::from_manifest(config.root().join("pyproject.toml")).unwrap();
         let mut venv = VirtualEnvironment::from_path(PathBuf::from(".venv")).unwrap();
-        let venv_has_pytest = venv.find_site_packages_package("pytest").is_some();
+        let venv_has_pytest = venv.find_site_packages_package("pytest").unwrap().is_some();
         let toml_has_pytest = project
             .pyproject_toml()
             .dependencies()
             .unwrap()
             .contains(&pytest_package.dependency_string());
-        venv.install_packages(&[pytest_package]).unwrap();
+        venv.install_packages(&[pytest_package], &["pytest"])
+            .unwrap();
 
         assert!(venv_had_pytest);
         assert!(toml_had_pytest);
@@ -732,12 +1378,13 @@ main()
         let mut config = OperationConfig::new();
         let config = config.with_root(dir.join("mock-project"));
         let venv = VirtualEnvironment::from_path(PathBuf::from(".venv")).unwrap();
-        let venv_had_xlcsv = venv.find_site_packages_package("xlcsv").is_some();
+        let venv_had_xlcsv = venv.find_site_packages_package("xlcsv").unwrap().is_some();
 
-        run_command_str_with_context(&config, "pip install xlcsv").unwrap();
+        let command = ["pip", "install", "xlcsv"].map(String::from);
+        run_command_with_context(&config, &command).unwrap();
 
         let mut venv = VirtualEnvironment::from_path(PathBuf::from(".venv")).unwrap();
-        let venv_has_xlcsv = venv.find_site_packages_package("xlcsv").is_some();
+        let venv_has_xlcsv = venv.find_site_packages_package("xlcsv").unwrap().is_some();
         venv.uninstall_packages(&["xlcsv"]).unwrap();
 
         assert!(!venv_had_xlcsv);
@@ -751,6 +1398,15 @@ main()
 
     #[test]
     fn test_display_project_version() {
-        todo!()
+        let dir = tempdir().unwrap().into_path();
+        crate::fs::copy_dir(
+            &test_resources_dir_path().join("mock-project"),
+            &dir.join("mock-project"),
+        )
+        .unwrap();
+        let mut config = OperationConfig::new();
+        let config = config.with_root(dir.join("mock-project"));
+
+        display_project_version(config).unwrap();
     }
 }
@@ -0,0 +1,568 @@
+///! This module implements building distributions with PEP 517 build backends.
+///! See https://peps.python.org/pep-0517/
+///!
+///! Each build runs in an isolated virtual environment with only the build system's
+///! requirements installed. The backend's hooks are called through a small Python
+///! shim run by the environment's interpreter, which writes each hook's result to a
+///! file so the backend's own output can't be mistaken for it.
+use crate::{
+    create_temp_dir,
+    error::{HuakError, HuakResult},
+    InstallerConfig, Package, PyProjectToml, Resolver, VirtualEnvironment,
+};
+use std::{
+    collections::BTreeMap,
+    path::{Component, Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+
+/// The build system of source trees without a `[build-system]` table.
+/// See https://peps.python.org/pep-0517/#source-trees
+const DEFAULT_BUILD_REQUIRES: [&str; 2] = ["setuptools>=40.8.0", "wheel"];
+const DEFAULT_BUILD_BACKEND: &str = "setuptools.build_meta:__legacy__";
+
+/// A Python script calling a hook of a build backend. It's run from the source tree
+/// with the arguments `<hook> <backend> <backend-path JSON> <config-settings JSON>
+/// <result path> [hook arguments...]`.
+const HOOK_SHIM: &str = r#"import importlib, json, os, sys
+
+hook, backend_spec, backend_path, config_settings, result_path, *args = sys.argv[1:]
+backend_path = [os.path.abspath(it) for it in json.loads(backend_path)]
+sys.path[:0] = backend_path
+module_name, _, object_path = backend_spec.partition(":")
+backend = importlib.import_module(module_name.strip())
+if backend_path:
+    module_path = os.path.abspath(getattr(backend, "__file__", "") or "")
+    if not any(module_path.startswith(it + os.sep) for it in backend_path):
+        sys.exit("backend {} is not in the backend-path".format(backend_spec))
+for name in filter(None, object_path.strip().split(".")):
+    backend = getattr(backend, name)
+config_settings = json.loads(config_settings) or None
+
+if hook in ("get_requires_for_build_wheel", "get_requires_for_build_sdist"):
+    function = getattr(backend, hook, None)
+    result = function(config_settings) if function else []
+elif hook == "build_wheel":
+    result = backend.build_wheel(args[0], config_settings, None)
+elif hook == "build_sdist":
+    result = backend.build_sdist(args[0], config_settings)
+else:
+    sys.exit("unsupported hook {}".format(hook))
+
+with open(result_path, "w") as f:
+    json.dump({"result": result}, f)
+"#;
+
+/// The `[build-system]` of a source tree.
+/// See https://peps.python.org/pep-0518/#build-system-table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildSystem {
+    /// The requirements needed to run the backend.
+    requires: Vec<String>,
+    /// The backend object as `module:object`.
+    build_backend: String,
+    /// Directories in the source tree the backend is imported from.
+    backend_path: Vec<String>,
+}
+
+impl BuildSystem {
+    /// Read the build system of a source tree. Source trees without a pyproject.toml
+    /// or `[build-system]` table are built with setuptools' legacy backend.
+    pub fn from_source_dir(dir: impl AsRef<Path>) -> HuakResult<BuildSystem> {
+        let path = dir.as_ref().join("pyproject.toml");
+        let build_system = match path.exists() {
+            true => PyProjectToml::from_path(path)?.build_system.clone(),
+            false => None,
+        };
+        Ok(match build_system {
+            Some(it) => BuildSystem {
+                requires: it.requires,
+                build_backend: it
+                    .build_backend
+                    .unwrap_or_else(|| DEFAULT_BUILD_BACKEND.to_string()),
+                backend_path: it.backend_path.unwrap_or_default(),
+            },
+            None => BuildSystem::default(),
+        })
+    }
+
+    pub fn requires(&self) -> &[String] {
+        &self.requires
+    }
+
+    pub fn build_backend(&self) -> &str {
+        &self.build_backend
+    }
+
+    pub fn backend_path(&self) -> &[String] {
+        &self.backend_path
+    }
+}
+
+impl Default for BuildSystem {
+    fn default() -> Self {
+        BuildSystem {
+            requires: DEFAULT_BUILD_REQUIRES.map(|it| it.to_string()).to_vec(),
+            build_backend: DEFAULT_BUILD_BACKEND.to_string(),
+            backend_path: Vec::new(),
+        }
+    }
+}
+
+/// A build of a source tree in an isolated environment.
+pub struct SourceBuild {
+    /// The source tree being built.
+    source_dir: PathBuf,
+    build_system: BuildSystem,
+    /// The isolated environment the backend runs in.
+    environment: VirtualEnvironment,
+    /// The temporary directory holding the environment.
+    temp_dir: PathBuf,
+    /// The config settings passed to the backend's hooks.
+    config_settings: BTreeMap<String, String>,
+}
+
+impl SourceBuild {
+    /// Create an isolated environment for building a source tree from a base Python
+    /// interpreter and install the build system's requirements to it. Packages are
+    /// installed as configured by the installer configuration.
+    pub fn new(
+        source_dir: impl AsRef<Path>,
+        python_path: impl AsRef<Path>,
+        installer_config: &InstallerConfig,
+    ) -> HuakResult<SourceBuild> {
+        let source_dir = source_dir.as_ref().canonicalize()?;
+        let build_system = BuildSystem::from_source_dir(&source_dir)?;
+        let temp_dir = create_temp_dir("build")?;
+        let mut environment =
+            VirtualEnvironment::from_base_python_path(temp_dir.join("env"), python_path)?;
+        environment.write_venv()?;
+        environment.with_installer_config(installer_config);
+        let mut build = SourceBuild {
+            source_dir,
+            build_system,
+            environment,
+            temp_dir,
+            config_settings: BTreeMap::new(),
+        };
+        let requires = build.build_system.requires.clone();
+        build.install_requirements(&requires)?;
+        Ok(build)
+    }
+
+    /// Get the build system of the source tree.
+    pub fn build_system(&self) -> &BuildSystem {
+        &self.build_system
+    }
+
+    /// Pass config settings to the backend's hooks.
+    pub fn with_config_settings(
+        &mut self,
+        config_settings: BTreeMap<String, String>,
+    ) -> &mut SourceBuild {
+        self.config_settings = config_settings;
+        self
+    }
+
+    /// Build a wheel into a directory and get its path.
+    pub fn build_wheel(&mut self, out_dir: impl AsRef<Path>) -> HuakResult<PathBuf> {
+        self.build("wheel", out_dir.as_ref())
+    }
+
+    /// Build a source distribution into a directory and get its path.
+    pub fn build_sdist(&mut self, out_dir: impl AsRef<Path>) -> HuakResult<PathBuf> {
+        self.build("sdist", out_dir.as_ref())
+    }
+
+    /// Install the extra requirements the backend asks for and run its build hook
+    /// for a kind of distribution.
+    fn build(&mut self, kind: &str, out_dir: &Path) -> HuakResult<PathBuf> {
+        let requires = self.call_hook(&format!("get_requires_for_build_{kind}"), &[])?;
+        let requires: Vec<String> = serde_json::from_value(requires)?;
+        self.install_requirements(&requires)?;
+
+        std::fs::create_dir_all(out_dir)?;
+        let out_dir = out_dir.canonicalize()?;
+        let name = self.call_hook(&format!("build_{kind}"), &[&out_dir])?;
+        match name.as_str() {
+            Some(name) => Ok(out_dir.join(name)),
+            None => Err(HuakError::BuildBackendError(format!(
+                "build_{kind} returned {name} instead of a file name"
+            ))),
+        }
+    }
+
+    /// Resolve and install requirements to the build environment.
+    fn install_requirements(&mut self, requirements: &[String]) -> HuakResult<()> {
+        if requirements.is_empty() {
+            return Ok(());
+        }
+        let requirements = requirements
+            .iter()
+            .map(|it| Package::from_str(it))
+            .collect::<HuakResult<Vec<_>>>()?;
+        let mut resolver = Resolver::new(
+            self.environment.index_client()?,
+            self.environment.marker_environment()?,
+        );
+        let packages = resolver.resolve(&requirements, &[])?;
+        let requested = requirements.iter().map(|it| it.name()).collect::<Vec<_>>();
+        self.environment.install_packages(&packages, &requested)?;
+        Ok(())
+    }
+
+    /// Call a hook of the backend from the source tree and get its result.
+    fn call_hook(&self, hook: &str, args: &[&Path]) -> HuakResult<serde_json::Value> {
+        let result_path = self.temp_dir.join(format!("{hook}.json"));
+        let output = Command::new(self.environment.python_path())
+            .arg("-I")
+            .args(["-c", HOOK_SHIM, hook, &self.build_system.build_backend])
+            .arg(serde_json::to_string(&self.build_system.backend_path)?)
+            .arg(serde_json::to_string(&self.config_settings)?)
+            .arg(&result_path)
+            .args(args)
+            .current_dir(&self.source_dir)
+            .output()?;
+        if !output.status.success() {
+            return Err(HuakError::BuildBackendError(format!(
+                "{hook} failed for {}:\n{}",
+                self.source_dir.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        let mut result: serde_json::Value = serde_json::from_slice(&std::fs::read(&result_path)?)?;
+        Ok(result["result"].take())
+    }
+}
+
+impl Drop for SourceBuild {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.temp_dir);
+    }
+}
+
+/// Build a wheel from a source distribution archive into a directory and get its
+/// path.
+pub fn build_wheel_from_sdist(
+    sdist_path: impl AsRef<Path>,
+    python_path: impl AsRef<Path>,
+    installer_config: &InstallerConfig,
+    out_dir: impl AsRef<Path>,
+) -> HuakResult<PathBuf> {
+    let unpack_dir = create_temp_dir("sdist")?;
+    let result = unpack_sdist(sdist_path.as_ref(), &unpack_dir).and_then(|source_dir| {
+        SourceBuild::new(source_dir, python_path.as_ref(), installer_config)?
+            .build_wheel(out_dir.as_ref())
+    });
+    let _ = std::fs::remove_dir_all(&unpack_dir);
+    result
+}
+
+/// Unpack a source distribution into a directory and get the path to its source
+/// tree, the archive's single top-level directory. Archives with members or links
+/// that would end up outside of the directory are rejected.
+fn unpack_sdist(sdist_path: &Path, dir: &Path) -> HuakResult<PathBuf> {
+    let file_name = sdist_path
+        .file_name()
+        .and_then(|it| it.to_str())
+        .unwrap_or_default();
+    std::fs::create_dir_all(dir)?;
+    if file_name.ends_with(".zip") {
+        unpack_zip(sdist_path, dir)?;
+    } else {
+        unpack_tar_gz(sdist_path, dir)?;
+    }
+    let entries = std::fs::read_dir(dir)?
+        .map(|it| it.map(|it| it.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    match entries.as_slice() {
+        [source_dir] if source_dir.is_dir() => Ok(source_dir.clone()),
+        _ => Err(HuakError::BuildBackendError(format!(
+            "{} doesn't contain a single source tree",
+            sdist_path.display()
+        ))),
+    }
+}
+
+/// Unpack a gzipped tar archive into a directory.
+fn unpack_tar_gz(sdist_path: &Path, dir: &Path) -> HuakResult<()> {
+    let file = std::fs::File::open(sdist_path)?;
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        if !is_contained(&path) {
+            return Err(unpack_error(sdist_path, &path));
+        }
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_symlink() || entry_type.is_hard_link() {
+            let link_name = entry.link_name()?.unwrap_or_default().into_owned();
+            // Symbolic links are relative to the link's directory and hard links are
+            // relative to the archive's root.
+            let target = match entry_type.is_symlink() {
+                true => path.parent().unwrap_or(Path::new("")).join(&link_name),
+                false => link_name,
+            };
+            if !is_contained(&target) {
+                return Err(unpack_error(sdist_path, &path));
+            }
+        } else if !(entry_type.is_file() || entry_type.is_dir()) {
+            continue;
+        }
+        // `unpack_in` also refuses to write through links unpacked earlier that lead
+        // outside of the directory.
+        if !entry.unpack_in(dir)? {
+            return Err(unpack_error(sdist_path, &path));
+        }
+    }
+    Ok(())
+}
+
+/// Unpack a zip archive into a directory.
+fn unpack_zip(sdist_path: &Path, dir: &Path) -> HuakResult<()> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(sdist_path)?)?;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let path = match entry.enclosed_name() {
+            Some(it) if is_contained(it) => it.to_path_buf(),
+            _ => return Err(unpack_error(sdist_path, Path::new(entry.name()))),
+        };
+        let destination = dir.join(path);
+        if entry.is_dir() {
+            std::fs::create_dir_all(&destination)?;
+            continue;
+        }
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::io::copy(&mut entry, &mut std::fs::File::create(&destination)?)?;
+        #[cfg(unix)]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&destination, std::fs::Permissions::from_mode(mode & 0o777))?;
+        }
+    }
+    Ok(())
+}
+
+/// Check if a relative path stays inside the directory it's relative to without
+/// following links.
+fn is_contained(path: &Path) -> bool {
+    let mut depth = 0usize;
+    for component in path.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir => match depth.checked_sub(1) {
+                Some(it) => depth = it,
+                None => return false,
+            },
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+/// Get the error for an archive member that would be unpacked outside of the
+/// directory the archive is unpacked into.
+fn unpack_error(sdist_path: &Path, member: &Path) -> HuakError {
+    HuakError::BuildBackendError(format!(
+        "failed to unpack {}: `{}` would be unpacked outside of its source tree",
+        sdist_path.display(),
+        member.display()
+    ))
+}
+
+/// Check if a file name is a source distribution archive.
+/// See https://packaging.python.org/en/latest/specifications/source-distribution-format/
+pub fn is_sdist(file_name: &str) -> bool {
+    file_name.ends_with(".tar.gz") || file_name.ends_with(".zip")
+}
+
+/// A source tree with a build backend in the tree writing a minimal `demo` wheel
+/// and sdist.
+#[cfg(test)]
+const TEST_SOURCE_TREE: [(&str, &str); 2] = [
+    (
+        "pyproject.toml",
+        "[build-system]\nrequires = []\nbuild-backend = \"backend\"\nbackend-path = [\".\"]\n",
+    ),
+    (
+        "backend.py",
+        r#"import base64, hashlib, os, tarfile, zipfile
+
+def get_requires_for_build_wheel(config_settings=None):
+    return []
+
+def build_wheel(wheel_directory, config_settings=None, metadata_directory=None):
+    version = (config_settings or {}).get("version", "1.0")
+    name = "demo-{}-py3-none-any.whl".format(version)
+    dist_info = "demo-{}.dist-info".format(version)
+    files = {
+        "demo/__init__.py": b"",
+        dist_info + "/METADATA": "Metadata-Version: 2.1\nName: demo\nVersion: {}\n".format(version).encode(),
+        dist_info + "/WHEEL": b"Wheel-Version: 1.0\nRoot-Is-Purelib: true\nTag: py3-none-any\n",
+    }
+    record = ""
+    with zipfile.ZipFile(os.path.join(wheel_directory, name), "w") as f:
+        for path, contents in files.items():
+            f.writestr(path, contents)
+            digest = base64.urlsafe_b64encode(hashlib.sha256(contents).digest()).rstrip(b"=")
+            record += "{},sha256={},{}\n".format(path, digest.decode(), len(contents))
+        f.writestr(dist_info + "/RECORD", record + dist_info + "/RECORD,,\n")
+    return name
+
+def build_sdist(sdist_directory, config_settings=None):
+    name = "demo-1.0.tar.gz"
+    with tarfile.open(os.path.join(sdist_directory, name), "w:gz") as f:
+        for path in ("pyproject.toml", "backend.py"):
+            f.add(path, arcname="demo-1.0/" + path)
+    return name
+"#,
+    ),
+];
+
+/// Write a source distribution of the test source tree for tests.
+#[cfg(test)]
+pub(crate) fn write_test_sdist(dir: &Path) -> PathBuf {
+    let path = dir.join("demo-1.0.tar.gz");
+    let file = std::fs::File::create(&path).unwrap();
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(file, Default::default()));
+    for (name, contents) in TEST_SOURCE_TREE {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, format!("demo-1.0/{name}"), contents.as_bytes())
+            .unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::find_python_interpreter_paths;
+    use tempfile::tempdir;
+
+    fn write_source_tree(dir: &Path) {
+        for (name, contents) in TEST_SOURCE_TREE {
+            std::fs::write(dir.join(name), contents).unwrap();
+        }
+    }
+
+    #[test]
+    fn build_system_defaults() {
+        let dir = tempdir().unwrap().into_path();
+
+        assert_eq!(
+            BuildSystem::from_source_dir(&dir).unwrap(),
+            BuildSystem::default()
+        );
+
+        write_source_tree(&dir);
+        let build_system = BuildSystem::from_source_dir(&dir).unwrap();
+
+        assert!(build_system.requires().is_empty());
+        assert_eq!(build_system.build_backend(), "backend");
+        assert_eq!(build_system.backend_path(), ["."]);
+        assert!(is_sdist("demo-1.0.tar.gz"));
+        assert!(!is_sdist("demo-1.0-py3-none-any.whl"));
+    }
+
+    /// Write a gzipped tar archive of the test sdist's members followed by a member
+    /// with a raw path, and a link target for links.
+    fn write_sdist_with_member(
+        dir: &Path,
+        name: &str,
+        entry_type: tar::EntryType,
+        link_name: &str,
+    ) -> PathBuf {
+        let path = write_test_sdist(dir);
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(
+            std::fs::File::open(&path).unwrap(),
+        ));
+        let file = std::fs::File::create(dir.join("unsafe-1.0.tar.gz")).unwrap();
+        let mut builder =
+            tar::Builder::new(flate2::write::GzEncoder::new(file, Default::default()));
+        for entry in archive.entries().unwrap() {
+            let entry = entry.unwrap();
+            let header = entry.header().clone();
+            builder.append(&header, entry).unwrap();
+        }
+        // Paths are written to the header directly since the builder rejects unsafe
+        // ones.
+        let mut header = tar::Header::new_gnu();
+        let gnu = header.as_gnu_mut().unwrap();
+        gnu.name[..name.len()].copy_from_slice(name.as_bytes());
+        gnu.linkname[..link_name.len()].copy_from_slice(link_name.as_bytes());
+        header.set_entry_type(entry_type);
+        header.set_size(0);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, std::io::empty()).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+        dir.join("unsafe-1.0.tar.gz")
+    }
+
+    #[test]
+    fn unpack_sdist_rejects_unsafe_members() {
+        let dir = tempdir().unwrap().into_path();
+        let source_dir = unpack_sdist(&write_test_sdist(&dir), &dir.join("safe")).unwrap();
+
+        assert_eq!(source_dir, dir.join("safe").join("demo-1.0"));
+        assert!(source_dir.join("pyproject.toml").exists());
+
+        for (name, entry_type, link_name) in [
+            ("demo-1.0/../../evil.txt", tar::EntryType::Regular, ""),
+            ("/tmp/evil.txt", tar::EntryType::Regular, ""),
+            ("demo-1.0/link", tar::EntryType::Symlink, "../../evil"),
+            ("demo-1.0/link", tar::EntryType::Symlink, "/etc"),
+            ("demo-1.0/link", tar::EntryType::Link, "../evil"),
+        ] {
+            let sdist_dir = dir.join(format!("{entry_type:?}-{}", link_name.len()));
+            std::fs::create_dir_all(&sdist_dir).unwrap();
+            let sdist = write_sdist_with_member(&sdist_dir, name, entry_type, link_name);
+            let unpack_dir = sdist_dir.join("unpacked");
+
+            assert!(matches!(
+                unpack_sdist(&sdist, &unpack_dir),
+                Err(HuakError::BuildBackendError(..))
+            ));
+            assert!(!sdist_dir.join("evil.txt").exists());
+            assert!(!dir.join("evil").exists());
+        }
+    }
+
+    #[test]
+    fn source_build() {
+        let interpreters = find_python_interpreter_paths();
+        let (_, python_path) = interpreters
+            .iter()
+            .max_by_key(|((_, version), _)| version)
+            .unwrap();
+        let dir = tempdir().unwrap().into_path();
+        let source_dir = dir.join("source");
+        let out_dir = dir.join("dist");
+        std::fs::create_dir_all(&source_dir).unwrap();
+        write_source_tree(&source_dir);
+
+        let mut build =
+            SourceBuild::new(&source_dir, python_path, &InstallerConfig::new()).unwrap();
+        build.with_config_settings(BTreeMap::from([("version".to_string(), "2.0".to_string())]));
+        let wheel = build.build_wheel(&out_dir).unwrap();
+        let sdist = build.build_sdist(&out_dir).unwrap();
+
+        assert_eq!(wheel, out_dir.join("demo-2.0-py3-none-any.whl"));
+        assert!(wheel.exists() && sdist.exists());
+
+        let wheel =
+            build_wheel_from_sdist(&sdist, python_path, &InstallerConfig::new(), &out_dir).unwrap();
+
+        assert_eq!(wheel, out_dir.join("demo-1.0-py3-none-any.whl"));
+        assert!(wheel.exists());
+    }
+}
@@ -0,0 +1,397 @@
+///! This module implements progress reporting for long-running work such as
+///! downloading artifacts, resolving dependencies and installing packages.
+///!
+///! A `Progress` is a display of bars that handles on many threads can report to.
+///! Live displays redraw every bar in place on an interactive terminal. Plain
+///! displays write a single line when each bar starts so logs stay readable.
+use std::{
+    fmt::Display,
+    io::Write,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+use termcolor::{Buffer, Color, ColorSpec, WriteColor};
+
+/// The number of characters between the brackets of a bar.
+const BAR_WIDTH: usize = 25;
+
+/// Messages longer than this many characters are shortened so bars fit on a line.
+const MAX_MESSAGE_CHARS: usize = 40;
+
+/// The minimum time between redraws of a live display caused by progress updates.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(80);
+
+/// What a bar's position and length count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressUnit {
+    Bytes,
+    Items,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProgressMode {
+    Hidden,
+    Plain,
+    Live,
+}
+
+/// A display of progress bars. Cloned displays share their bars and output.
+#[derive(Clone)]
+pub struct Progress {
+    state: Arc<Mutex<ProgressState>>,
+}
+
+impl Progress {
+    /// Create a display that doesn't draw anything.
+    pub fn hidden() -> Progress {
+        Progress::with_mode(ProgressMode::Hidden, None)
+    }
+
+    /// Create a display writing a line to `output` when each bar starts.
+    pub fn plain(output: Box<dyn Write + Send>) -> Progress {
+        Progress::with_mode(ProgressMode::Plain, Some(output))
+    }
+
+    /// Create a display redrawing its bars in place. `output` must be an interactive
+    /// terminal supporting ANSI escape sequences.
+    pub fn live(output: Box<dyn Write + Send>) -> Progress {
+        Progress::with_mode(ProgressMode::Live, Some(output))
+    }
+
+    fn with_mode(mode: ProgressMode, output: Option<Box<dyn Write + Send>>) -> Progress {
+        Progress {
+            state: Arc::new(Mutex::new(ProgressState {
+                mode,
+                output,
+                bars: Vec::new(),
+                next_id: 0,
+                drawn_lines: 0,
+                drawn_at: None,
+            })),
+        }
+    }
+
+    /// Add a bar with a status such as `Downloading`, a message naming what it
+    /// tracks, and the length of the work if it's known.
+    pub fn add_bar(
+        &self,
+        status: impl Display,
+        message: impl Display,
+        length: Option<u64>,
+        unit: ProgressUnit,
+    ) -> ProgressBar {
+        let mut state = self.lock();
+        let id = state.next_id;
+        state.next_id += 1;
+        let bar = BarState {
+            id,
+            status: status.to_string(),
+            message: message.to_string(),
+            position: 0,
+            length,
+            unit,
+        };
+        if state.mode == ProgressMode::Plain {
+            let line = bar.render_status(false);
+            state.write(&line);
+        }
+        state.bars.push(bar);
+        state.draw(true);
+        ProgressBar {
+            progress: self.clone(),
+            id,
+        }
+    }
+
+    /// Clear a live display while `f` writes to the terminal below it, then redraw
+    /// the bars under whatever was written. Bars aren't redrawn while `f` runs.
+    pub fn suspend<T>(&self, f: impl FnOnce() -> T) -> T {
+        let mut state = self.lock();
+        state.clear();
+        let result = f();
+        state.draw(true);
+        result
+    }
+
+    /// Update a bar and redraw the display if it's due.
+    fn update(&self, id: usize, f: impl FnOnce(&mut BarState)) {
+        let mut state = self.lock();
+        if let Some(bar) = state.bars.iter_mut().find(|it| it.id == id) {
+            f(bar);
+        }
+        state.draw(false);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ProgressState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Progress::hidden()
+    }
+}
+
+/// A handle to a bar of a `Progress` display. The bar is removed from the display
+/// when it's finished or dropped.
+pub struct ProgressBar {
+    progress: Progress,
+    id: usize,
+}
+
+impl ProgressBar {
+    /// Advance the bar's position.
+    pub fn inc(&self, delta: u64) {
+        self.progress.update(self.id, |it| it.position += delta);
+    }
+
+    /// Set the bar's position.
+    pub fn set_position(&self, position: u64) {
+        self.progress.update(self.id, |it| it.position = position);
+    }
+
+    /// Set the length of the work the bar tracks.
+    pub fn set_length(&self, length: u64) {
+        self.progress.update(self.id, |it| it.length = Some(length));
+    }
+
+    /// Set the message naming what the bar tracks.
+    pub fn set_message(&self, message: impl Display) {
+        self.progress
+            .update(self.id, |it| it.message = message.to_string());
+    }
+
+    /// Remove the bar from the display.
+    pub fn finish(self) {}
+}
+
+impl Drop for ProgressBar {
+    fn drop(&mut self) {
+        let mut state = self.progress.lock();
+        state.bars.retain(|it| it.id != self.id);
+        state.draw(true);
+    }
+}
+
+struct ProgressState {
+    mode: ProgressMode,
+    output: Option<Box<dyn Write + Send>>,
+    bars: Vec<BarState>,
+    next_id: usize,
+    /// The number of lines of the live display on the terminal.
+    drawn_lines: usize,
+    drawn_at: Option<Instant>,
+}
+
+impl ProgressState {
+    /// Redraw a live display. Unless forced, redraws happen at most once every
+    /// `REDRAW_INTERVAL`.
+    fn draw(&mut self, force: bool) {
+        if self.mode != ProgressMode::Live {
+            return;
+        }
+        let now = Instant::now();
+        if !force
+            && self
+                .drawn_at
+                .is_some_and(|it| now.duration_since(it) < REDRAW_INTERVAL)
+        {
+            return;
+        }
+        self.drawn_at = Some(now);
+        // Move to the top of the display, rewrite every line and clear whatever is
+        // left of bars that were removed.
+        let mut frame = Vec::new();
+        if self.drawn_lines > 0 {
+            frame.extend(format!("\x1b[{}A", self.drawn_lines).bytes());
+        }
+        for bar in self.bars.iter() {
+            frame.extend(b"\r\x1b[2K");
+            frame.extend(bar.render_status(true));
+            frame.extend(bar.render_progress().bytes());
+            frame.push(b'\n');
+        }
+        frame.extend(b"\x1b[J");
+        self.drawn_lines = self.bars.len();
+        self.write(&frame);
+    }
+
+    /// Erase a live display from the terminal.
+    fn clear(&mut self) {
+        if self.mode != ProgressMode::Live || self.drawn_lines == 0 {
+            return;
+        }
+        let frame = format!("\x1b[{}A\x1b[J", self.drawn_lines);
+        self.drawn_lines = 0;
+        self.write(frame.as_bytes());
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        // Progress is only informational, so failing to draw it doesn't fail the
+        // work it tracks.
+        if let Some(output) = self.output.as_mut() {
+            output.write_all(bytes).ok();
+            output.flush().ok();
+        }
+    }
+}
+
+struct BarState {
+    id: usize,
+    status: String,
+    message: String,
+    position: u64,
+    length: Option<u64>,
+    unit: ProgressUnit,
+}
+
+impl BarState {
+    /// Render the bar's right-aligned status and its message. Plain lines end with a
+    /// newline.
+    fn render_status(&self, color: bool) -> Vec<u8> {
+        let mut buffer = match color {
+            true => Buffer::ansi(),
+            false => Buffer::no_color(),
+        };
+        buffer
+            .set_color(ColorSpec::new().set_bold(true).set_fg(Some(Color::Cyan)))
+            .ok();
+        write!(buffer, "{:>12}", self.status).ok();
+        buffer.reset().ok();
+        write!(buffer, " {}", shorten(&self.message)).ok();
+        if !color {
+            writeln!(buffer).ok();
+        }
+        buffer.into_inner()
+    }
+
+    /// Render the bar and its counts, like ` [=====>    ] 1.2 MiB/3.4 MiB`.
+    fn render_progress(&self) -> String {
+        let count = |it: u64| match self.unit {
+            ProgressUnit::Bytes => format_bytes(it),
+            ProgressUnit::Items => it.to_string(),
+        };
+        match self.length {
+            Some(length) => {
+                let filled = match length {
+                    0 => BAR_WIDTH,
+                    _ => (self.position.min(length) as usize * BAR_WIDTH) / length as usize,
+                };
+                let head = if filled < BAR_WIDTH { ">" } else { "" };
+                format!(
+                    " [{}{head}{}] {}/{}",
+                    "=".repeat(filled),
+                    " ".repeat(BAR_WIDTH - filled - head.len()),
+                    count(self.position),
+                    count(length)
+                )
+            }
+            None => format!(" {}", count(self.position)),
+        }
+    }
+}
+
+/// Shorten a message to at most `MAX_MESSAGE_CHARS` characters.
+fn shorten(message: &str) -> String {
+    if message.chars().count() <= MAX_MESSAGE_CHARS {
+        return message.to_string();
+    }
+    let mut shortened = message
+        .chars()
+        .take(MAX_MESSAGE_CHARS - 3)
+        .collect::<String>();
+    shortened.push_str("...");
+    shortened
+}
+
+/// Format a number of bytes using binary units, like `1.2 MiB`.
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CapturedOutput;
+
+    #[test]
+    fn progress_plain_lines() {
+        let output = CapturedOutput::new();
+        let progress = Progress::plain(Box::new(output.clone()));
+
+        let download = progress.add_bar(
+            "Downloading",
+            "click-8.1.3-py3-none-any.whl",
+            Some(4096),
+            ProgressUnit::Bytes,
+        );
+        download.inc(1024);
+        let install = progress.add_bar("Installing", "click", Some(1), ProgressUnit::Items);
+        install.inc(1);
+        download.finish();
+        install.finish();
+
+        assert_eq!(
+            output.contents().lines().collect::<Vec<_>>(),
+            [
+                " Downloading click-8.1.3-py3-none-any.whl",
+                "  Installing click",
+            ]
+        );
+    }
+
+    #[test]
+    fn progress_live_redraw() {
+        let output = CapturedOutput::new();
+        let progress = Progress::live(Box::new(output.clone()));
+
+        let bar = progress.add_bar("Resolving", "click", Some(4), ProgressUnit::Items);
+        let contents = output.contents();
+        bar.finish();
+        let bar = BarState {
+            id: 0,
+            status: "Downloading".to_string(),
+            message: "click".to_string(),
+            position: 2048,
+            length: Some(4096),
+            unit: ProgressUnit::Bytes,
+        };
+
+        assert!(contents.contains(" click [>                        ] 0/4\n"));
+        assert!(output.contents().ends_with("\x1b[1A\x1b[J"));
+        assert_eq!(
+            bar.render_progress(),
+            " [============>            ] 2.0 KiB/4.0 KiB"
+        );
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(3 * 1024 * 1024 + 512 * 1024), "3.5 MiB");
+    }
+
+    #[test]
+    fn progress_live_suspend() {
+        let output = CapturedOutput::new();
+        let progress = Progress::live(Box::new(output.clone()));
+
+        let _bar = progress.add_bar("Resolving", "click", None, ProgressUnit::Items);
+        let drawn = output.contents().len();
+        progress.suspend(|| {
+            let mut output = output.clone();
+            writeln!(output, "    Resolved click 8.1.3").unwrap();
+        });
+        let contents = output.contents()[drawn..].to_string();
+
+        assert!(contents.starts_with("\x1b[1A\x1b[J    Resolved click 8.1.3\n\r\x1b[2K"));
+        assert!(contents.ends_with(" click 0\n\x1b[J"));
+    }
+}
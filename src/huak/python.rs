@@ -0,0 +1,466 @@
+///! This module implements discovering the Python interpreters installed on the system.
+///! NOTE: This search implementation is inspired by brettcannon/python-launcher
+///!
+///! Candidates are searched for in, from highest to lowest priority:
+///!
+///! - The `PYTHON` environment variable.
+///! - The directories on the PATH.
+///! - pyenv's and asdf's shims and installed versions.
+///! - `/usr/local/bin`.
+///! - conda's base and named environments.
+///!
+///! Every candidate is run to confirm its version and implementation. Since that's
+///! slow, queries are cached by the executable's path and modification time.
+use crate::{
+    cache::Cache,
+    error::{HuakError, HuakResult},
+    fs, sys,
+};
+use pep440_rs::{Operator, Version, VersionSpecifier};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+    time::UNIX_EPOCH,
+};
+
+/// The environment variable naming an interpreter to prefer over all others.
+const PYTHON_ENV_VAR: &str = "PYTHON";
+
+/// A Python script printing an interpreter's executable, implementation, and version
+/// as JSON.
+const INTERPRETER_SCRIPT: &str = r#"import json, platform, sys
+print(json.dumps({
+    "executable": sys.executable,
+    "implementation": sys.implementation.name,
+    "version": platform.python_version(),
+}))
+"#;
+
+/// The implementation of a Python interpreter.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PythonImplementation {
+    CPython,
+    PyPy,
+    Other(String),
+}
+
+impl FromStr for PythonImplementation {
+    type Err = std::convert::Infallible;
+
+    /// Parse an implementation from its `sys.implementation.name`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "cpython" => PythonImplementation::CPython,
+            "pypy" => PythonImplementation::PyPy,
+            other => PythonImplementation::Other(other.to_string()),
+        })
+    }
+}
+
+impl Display for PythonImplementation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PythonImplementation::CPython => write!(f, "CPython"),
+            PythonImplementation::PyPy => write!(f, "PyPy"),
+            PythonImplementation::Other(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+/// A Python interpreter found on the system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PythonInterpreter {
+    /// Absolute path to the interpreter's executable.
+    path: PathBuf,
+    /// The version of Python the interpreter implements.
+    version: Version,
+    implementation: PythonImplementation,
+}
+
+impl PythonInterpreter {
+    /// Query an interpreter for its version and implementation.
+    pub fn from_path(path: impl AsRef<Path>) -> HuakResult<PythonInterpreter> {
+        query_interpreter(path.as_ref(), &Cache::new())
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    pub fn implementation(&self) -> &PythonImplementation {
+        &self.implementation
+    }
+}
+
+/// A request for a version of Python, either as a version prefix such as `3` or
+/// `3.11`, an exact version such as `3.11.4`, or version specifiers such as `>=3.10`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PythonRequest {
+    specifiers: Vec<VersionSpecifier>,
+}
+
+impl PythonRequest {
+    /// Check if a version of Python satisfies the request.
+    pub fn contains(&self, version: &Version) -> bool {
+        self.specifiers.iter().all(|it| it.contains(version))
+    }
+
+    /// Get the lowest version of Python the request allows, if it has a lower bound.
+    /// An exclusive bound such as `>3.8` is treated like `>=3.8`.
+    pub fn lower_bound(&self) -> Option<&Version> {
+        self.specifiers
+            .iter()
+            .filter(|it| {
+                matches!(
+                    it.operator(),
+                    Operator::Equal
+                        | Operator::EqualStar
+                        | Operator::ExactEqual
+                        | Operator::TildeEqual
+                        | Operator::GreaterThan
+                        | Operator::GreaterThanEqual
+                )
+            })
+            .map(|it| it.version())
+            .max()
+    }
+}
+
+impl FromStr for PythonRequest {
+    type Err = HuakError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let is_version = !s.is_empty()
+            && s.split('.')
+                .all(|it| !it.is_empty() && it.chars().all(|c| c.is_ascii_digit()));
+        let specifiers = match is_version {
+            true if s.split('.').count() >= 3 => format!("=={s}"),
+            true => format!("=={s}.*"),
+            false => s.to_string(),
+        };
+        let specifiers = specifiers
+            .split(',')
+            .map(|it| {
+                VersionSpecifier::from_str(it.trim())
+                    .map_err(|e| HuakError::InvalidVersionString(e.to_string()))
+            })
+            .collect::<HuakResult<_>>()?;
+        Ok(PythonRequest { specifiers })
+    }
+}
+
+impl Display for PythonRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let specifiers = self
+            .specifiers
+            .iter()
+            .map(|it| it.to_string())
+            .collect::<Vec<_>>();
+        write!(f, "{}", specifiers.join(","))
+    }
+}
+
+/// The output of `INTERPRETER_SCRIPT` as it's stored in the cache.
+#[derive(Serialize, Deserialize)]
+struct InterpreterOutput {
+    executable: PathBuf,
+    implementation: String,
+    version: String,
+}
+
+impl TryFrom<InterpreterOutput> for PythonInterpreter {
+    type Error = HuakError;
+
+    fn try_from(output: InterpreterOutput) -> HuakResult<PythonInterpreter> {
+        Ok(PythonInterpreter {
+            path: output.executable,
+            version: Version::from_str(&output.version).map_err(HuakError::InternalError)?,
+            implementation: PythonImplementation::from_str(&output.implementation)
+                .unwrap_or(PythonImplementation::CPython),
+        })
+    }
+}
+
+/// Find the Python interpreters installed on the system in order of priority.
+/// Candidates resolving to the same executable are only included once.
+pub fn find_python_interpreters() -> Vec<PythonInterpreter> {
+    let mut paths = Vec::new();
+    if let Some(path) = std::env::var_os(PYTHON_ENV_VAR).filter(|it| !it.is_empty()) {
+        paths.push(PathBuf::from(path));
+    }
+    let mut dirs = sys::env_path_values();
+    dirs.extend(version_manager_dirs());
+    #[cfg(unix)]
+    dirs.push(PathBuf::from("/usr/local/bin"));
+    dirs.extend(conda_dirs());
+    paths.extend(candidates_in_dirs(dirs));
+    python_interpreters_in_paths(paths, &Cache::new())
+}
+
+/// Query each of the candidate paths, skipping those that aren't working Python
+/// interpreters and those resolving to an executable already found.
+pub(crate) fn python_interpreters_in_paths(
+    paths: impl IntoIterator<Item = PathBuf>,
+    cache: &Cache,
+) -> Vec<PythonInterpreter> {
+    let mut candidates = HashSet::new();
+    let mut executables = HashSet::new();
+    paths
+        .into_iter()
+        .filter(|it| candidates.insert(canonical_path(it)))
+        .filter_map(|it| query_interpreter(&it, cache).ok())
+        .filter(|it| executables.insert(canonical_path(&it.path)))
+        .collect()
+}
+
+/// Get the candidate interpreter executables in directories. Each directory's
+/// candidates are sorted so the search order is stable.
+pub(crate) fn candidates_in_dirs(dirs: impl IntoIterator<Item = PathBuf>) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    for dir in dirs {
+        let mut paths: Vec<PathBuf> = fs::flatten_directories([dir])
+            .filter(|it| it.is_file() && is_python_executable_name(it))
+            .collect();
+        paths.sort();
+        candidates.extend(paths);
+    }
+    candidates
+}
+
+/// Check if a file is named like a Python interpreter: `python`, `python3`,
+/// `python3.11`, `pypy3`, and so on.
+fn is_python_executable_name(path: &Path) -> bool {
+    let file_name = match path.file_name().and_then(|it| it.to_str()) {
+        Some(it) => it,
+        None => return false,
+    };
+    #[cfg(windows)]
+    let file_name = match file_name.to_lowercase().strip_suffix(".exe") {
+        Some(it) => it.to_string(),
+        None => return false,
+    };
+    let version = match file_name
+        .strip_prefix("python")
+        .or_else(|| file_name.strip_prefix("pypy"))
+    {
+        Some(it) => it,
+        None => return false,
+    };
+    version.is_empty()
+        || version
+            .split('.')
+            .all(|it| !it.is_empty() && it.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Run an interpreter to get its version and implementation, or use the cached
+/// result if the executable hasn't been modified since it was queried. Version
+/// manager shims aren't cached since the interpreter they run depends on where
+/// they're run from.
+fn query_interpreter(path: &Path, cache: &Cache) -> HuakResult<PythonInterpreter> {
+    let is_shim = path
+        .parent()
+        .and_then(|it| it.file_name())
+        .is_some_and(|it| it == "shims");
+    let key = std::fs::metadata(path)
+        .ok()
+        .filter(|_| !is_shim)
+        .and_then(|it| it.modified().ok())
+        .and_then(|it| it.duration_since(UNIX_EPOCH).ok())
+        .map(|it| format!("{}:{}", path.display(), it.as_nanos()));
+    if let Some(key) = key.as_deref() {
+        if let Some(contents) = cache.interpreter(key)? {
+            if let Ok(output) = serde_json::from_str::<InterpreterOutput>(&contents) {
+                return PythonInterpreter::try_from(output);
+            }
+        }
+    }
+
+    let output = Command::new(path)
+        .args(["-I", "-c", INTERPRETER_SCRIPT])
+        .output()
+        .map_err(|_| HuakError::PythonNotFoundError)?;
+    if !output.status.success() {
+        return Err(HuakError::CommandError(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    let contents = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let interpreter =
+        PythonInterpreter::try_from(serde_json::from_str::<InterpreterOutput>(&contents)?)?;
+    if let Some(key) = key.as_deref() {
+        cache.store_interpreter(key, &contents)?;
+    }
+    Ok(interpreter)
+}
+
+/// Get the directories of pyenv's and asdf's shims and installed Python versions.
+fn version_manager_dirs() -> Vec<PathBuf> {
+    let home = sys::home_dir();
+    let mut dirs = Vec::new();
+    let pyenv_root = env_dir("PYENV_ROOT").or_else(|| home.as_ref().map(|it| it.join(".pyenv")));
+    if let Some(root) = pyenv_root {
+        dirs.push(root.join("shims"));
+        dirs.extend(subdirs(root.join("versions")).map(executables_dir));
+    }
+    let asdf_root = env_dir("ASDF_DATA_DIR").or_else(|| home.as_ref().map(|it| it.join(".asdf")));
+    if let Some(root) = asdf_root {
+        dirs.push(root.join("shims"));
+        dirs.extend(subdirs(root.join("installs").join("python")).map(executables_dir));
+    }
+    dirs
+}
+
+/// Get the executables directories of conda's base and named environments.
+fn conda_dirs() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    roots.extend(env_dir("CONDA_PREFIX"));
+    if let Some(exe) = env_dir("CONDA_EXE") {
+        // CONDA_EXE is <root>/bin/conda or <root>\Scripts\conda.exe.
+        roots.extend(
+            exe.parent()
+                .and_then(|it| it.parent())
+                .map(Path::to_path_buf),
+        );
+    }
+    if let Some(home) = sys::home_dir() {
+        roots.extend(
+            [
+                "miniconda3",
+                "miniconda",
+                "anaconda3",
+                "miniforge3",
+                "mambaforge",
+            ]
+            .map(|it| home.join(it)),
+        );
+        // conda records the environments it creates outside of its root.
+        if let Ok(contents) = std::fs::read_to_string(home.join(".conda").join("environments.txt"))
+        {
+            roots.extend(
+                contents
+                    .lines()
+                    .map(str::trim)
+                    .filter(|it| !it.is_empty())
+                    .map(PathBuf::from),
+            );
+        }
+    }
+    let envs: Vec<PathBuf> = roots
+        .iter()
+        .flat_map(|it| subdirs(it.join("envs")))
+        .collect();
+    roots.into_iter().chain(envs).map(executables_dir).collect()
+}
+
+/// Get the directory an installation or environment keeps its interpreter in.
+fn executables_dir(root: PathBuf) -> PathBuf {
+    #[cfg(unix)]
+    let dir = root.join("bin");
+    #[cfg(windows)]
+    let dir = root;
+    dir
+}
+
+/// Get a directory from an environment variable.
+fn env_dir(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .filter(|it| !it.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Get the subdirectories of a directory sorted by name.
+fn subdirs(dir: PathBuf) -> impl Iterator<Item = PathBuf> {
+    let mut dirs: Vec<PathBuf> = fs::flatten_directories([dir])
+        .filter(|it| it.is_dir())
+        .collect();
+    dirs.sort();
+    dirs.into_iter()
+}
+
+/// Resolve symlinks in a path if it exists.
+fn canonical_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn python_executable_names() {
+        #[cfg(unix)]
+        let names = ["python", "python3", "python3.11", "pypy3", "pypy3.9"];
+        #[cfg(windows)]
+        let names = ["python.exe", "python3.11.exe", "pypy3.exe"];
+        for name in names {
+            assert!(is_python_executable_name(Path::new(name)), "{name}");
+        }
+        for name in [
+            "python3-config",
+            "python3.11m",
+            "python3.",
+            "pythonw3",
+            "pip3",
+        ] {
+            assert!(!is_python_executable_name(Path::new(name)), "{name}");
+        }
+    }
+
+    #[test]
+    fn python_requests() {
+        let version = |it| Version::from_str(it).unwrap();
+        let request = PythonRequest::from_str("3.11").unwrap();
+
+        assert_eq!(request.to_string(), "==3.11.*");
+        assert!(request.contains(&version("3.11.4")));
+        assert!(!request.contains(&version("3.12.0")));
+        assert!(PythonRequest::from_str("3.11.4")
+            .unwrap()
+            .contains(&version("3.11.4")));
+        assert!(!PythonRequest::from_str("3.11.4")
+            .unwrap()
+            .contains(&version("3.11.5")));
+        let request = PythonRequest::from_str(">=3.10, <3.12").unwrap();
+        assert!(request.contains(&version("3.10.0")));
+        assert!(!request.contains(&version("3.12.1")));
+        assert_eq!(request.lower_bound(), Some(&version("3.10")));
+        assert_eq!(
+            PythonRequest::from_str("<3.12").unwrap().lower_bound(),
+            None
+        );
+        assert!(PythonRequest::from_str("latest").is_err());
+    }
+
+    #[test]
+    /// NOTE: This test depends on a Python interpreter being available on the PATH.
+    fn python_interpreter_query_cache() {
+        let interpreter = find_python_interpreters().into_iter().next().unwrap();
+        let cache = Cache::from_path(tempdir().unwrap().into_path());
+
+        let queried = query_interpreter(interpreter.path(), &cache).unwrap();
+
+        assert_eq!(queried.version(), interpreter.version());
+        assert_eq!(
+            python_interpreters_in_paths(
+                [interpreter.path().clone(), interpreter.path().clone()],
+                &cache
+            ),
+            std::slice::from_ref(&queried)
+        );
+        assert!(cache.root().join("interpreters-v1").exists());
+        assert_eq!(
+            query_interpreter(interpreter.path(), &cache).unwrap(),
+            queried
+        );
+    }
+}
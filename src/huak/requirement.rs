@@ -0,0 +1,290 @@
+///! This module implements parsing for PEP 508 dependency specifiers.
+///! See https://peps.python.org/pep-0508/
+use crate::{
+    error::{HuakError, HuakResult},
+    markers::MarkerTree,
+};
+use pep440_rs::VersionSpecifier;
+use std::str::FromStr;
+
+/// The parts of a PEP 508 dependency specifier such as
+/// `requests[socks]>=2.8.1,<3 ; python_version < "3.9"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement {
+    /// The name of the distribution as it was written.
+    pub name: String,
+    /// Optional features of the distribution being requested.
+    pub extras: Vec<String>,
+    /// PEP 440 version specifiers the distribution's version must satisfy.
+    pub version_specifiers: Vec<VersionSpecifier>,
+    /// A direct reference to the distribution (`name @ https://...`).
+    pub url: Option<String>,
+    /// The environment markers following the `;`.
+    pub markers: Option<MarkerTree>,
+}
+
+impl FromStr for Requirement {
+    type Err = HuakError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cursor = Cursor::new(s);
+        cursor.eat_whitespace();
+        let name = cursor.parse_name()?;
+        cursor.eat_whitespace();
+        let extras = match cursor.peek() {
+            Some('[') => cursor.parse_extras()?,
+            _ => Vec::new(),
+        };
+        cursor.eat_whitespace();
+        let mut version_specifiers = Vec::new();
+        let mut url = None;
+        match cursor.peek() {
+            Some('@') => {
+                cursor.next();
+                cursor.eat_whitespace();
+                url = Some(cursor.parse_url()?);
+                // A URL must be followed by whitespace before any markers since `;`
+                // is a valid URL character.
+                if !cursor.is_done() && !cursor.eat_whitespace() {
+                    return Err(cursor.error("expected whitespace after the URL"));
+                }
+            }
+            Some('(') => {
+                cursor.next();
+                version_specifiers = cursor.parse_version_specifiers()?;
+                cursor.eat_whitespace();
+                cursor.expect(')')?;
+            }
+            Some(c) if is_version_operator_char(c) => {
+                version_specifiers = cursor.parse_version_specifiers()?;
+            }
+            _ => (),
+        }
+        cursor.eat_whitespace();
+        let markers = match cursor.peek() {
+            Some(';') => {
+                cursor.next();
+                let markers = cursor.rest().trim();
+                if markers.is_empty() {
+                    return Err(cursor.error("expected a marker expression after `;`"));
+                }
+                let markers = MarkerTree::from_str(markers)?;
+                cursor.consume_rest();
+                Some(markers)
+            }
+            None => None,
+            Some(c) => return Err(cursor.error(&format!("unexpected character `{c}`"))),
+        };
+
+        Ok(Requirement {
+            name,
+            extras,
+            version_specifiers,
+            url,
+            markers,
+        })
+    }
+}
+
+fn is_version_operator_char(c: char) -> bool {
+    matches!(c, '<' | '>' | '=' | '!' | '~')
+}
+
+/// A simple cursor over a requirement string that tracks its position for error
+/// reporting.
+struct Cursor<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(input: &'a str) -> Cursor<'a> {
+        Cursor { input, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn consume_rest(&mut self) {
+        self.pos = self.input.len();
+    }
+
+    fn is_done(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    /// Skip any whitespace, returning true if any was skipped.
+    fn eat_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+            self.next();
+        }
+        self.pos > start
+    }
+
+    /// Consume characters while the predicate holds, returning them.
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if predicate(c)) {
+            self.next();
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn expect(&mut self, expected: char) -> HuakResult<()> {
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(self.error(&format!("expected `{expected}`, found `{c}`"))),
+            None => Err(self.error(&format!("expected `{expected}`"))),
+        }
+    }
+
+    fn error(&self, message: &str) -> HuakError {
+        HuakError::PackageFromStringError(format!(
+            "{message} at position {} in `{}`",
+            self.pos, self.input
+        ))
+    }
+
+    fn parse_name(&mut self) -> HuakResult<String> {
+        let name = self.take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if name.is_empty() {
+            return Err(self.error("expected a package name"));
+        }
+        let valid_bound = |c: Option<char>| matches!(c, Some(c) if c.is_ascii_alphanumeric());
+        if !valid_bound(name.chars().next()) || !valid_bound(name.chars().last()) {
+            return Err(self.error(&format!(
+                "package name `{name}` must start and end with a letter or digit"
+            )));
+        }
+        Ok(name.to_string())
+    }
+
+    fn parse_extras(&mut self) -> HuakResult<Vec<String>> {
+        self.expect('[')?;
+        let mut extras = Vec::new();
+        loop {
+            self.eat_whitespace();
+            if self.peek() == Some(']') {
+                self.next();
+                break;
+            }
+            if !extras.is_empty() {
+                self.expect(',')?;
+                self.eat_whitespace();
+            }
+            extras.push(self.parse_name()?);
+        }
+        Ok(extras)
+    }
+
+    fn parse_version_specifiers(&mut self) -> HuakResult<Vec<VersionSpecifier>> {
+        let mut specifiers = Vec::new();
+        loop {
+            self.eat_whitespace();
+            let operator = self.take_while(is_version_operator_char);
+            if operator.is_empty() {
+                return Err(self.error("expected a version comparison operator"));
+            }
+            self.eat_whitespace();
+            let version = self.take_while(|c| {
+                c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '*' | '+' | '!')
+            });
+            if version.is_empty() {
+                return Err(self.error("expected a version"));
+            }
+            let specifier = VersionSpecifier::from_str(&format!("{operator}{version}"))
+                .map_err(|e| self.error(&e.to_string()))?;
+            specifiers.push(specifier);
+            self.eat_whitespace();
+            if self.peek() == Some(',') {
+                self.next();
+            } else {
+                break;
+            }
+        }
+        Ok(specifiers)
+    }
+
+    fn parse_url(&mut self) -> HuakResult<String> {
+        let url = self.take_while(|c| !c.is_whitespace());
+        if url.is_empty() || !url.contains(':') {
+            return Err(self.error("expected a URL"));
+        }
+        Ok(url.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requirement_with_extras() {
+        let requirement = Requirement::from_str("requests[security, socks]").unwrap();
+
+        assert_eq!(requirement.name, "requests");
+        assert_eq!(requirement.extras, vec!["security", "socks"]);
+        assert!(requirement.version_specifiers.is_empty());
+    }
+
+    #[test]
+    fn requirement_with_parenthesized_specifiers() {
+        let requirement = Requirement::from_str("name (>=1.0, <2)").unwrap();
+
+        assert_eq!(
+            requirement
+                .version_specifiers
+                .iter()
+                .map(|it| it.to_string())
+                .collect::<Vec<_>>(),
+            vec![">=1.0", "<2"]
+        );
+    }
+
+    #[test]
+    fn requirement_with_url_and_markers() {
+        let requirement = Requirement::from_str(
+            "pip @ https://github.com/pypa/pip/archive/1.3.1.zip#sha1=da9234ee ; python_version >= \"3.8\"",
+        )
+        .unwrap();
+
+        assert_eq!(
+            requirement.url.unwrap(),
+            "https://github.com/pypa/pip/archive/1.3.1.zip#sha1=da9234ee"
+        );
+        assert_eq!(
+            requirement.markers.unwrap().to_string(),
+            "python_version >= \"3.8\""
+        );
+    }
+
+    #[test]
+    fn requirement_url_requires_whitespace_before_markers() {
+        assert!(
+            Requirement::from_str("pip @ https://example.com/pip.zip;python_version > \"3\"")
+                .is_err()
+        );
+        assert!(Requirement::from_str("pip @ https://example.com/pip.zip ;").is_err());
+    }
+
+    #[test]
+    fn requirement_invalid() {
+        assert!(Requirement::from_str("").is_err());
+        assert!(Requirement::from_str("-name").is_err());
+        assert!(Requirement::from_str("name[extra").is_err());
+        assert!(Requirement::from_str("name >=").is_err());
+        assert!(Requirement::from_str("name ==1.0 junk").is_err());
+    }
+}
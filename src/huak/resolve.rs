@@ -0,0 +1,948 @@
+///! This module implements resolving a project's dependencies into a consistent set of
+///! pinned versions.
+///!
+///! The resolver searches versions newest first and backtracks when a choice leads to
+///! a conflict. Like PubGrub, each conflict records the packages that caused it so
+///! the search can jump back past choices that had nothing to do with it, and so the
+///! conflict can be explained if no solution exists.
+use crate::{
+    canonical_package_name,
+    error::{HuakError, HuakResult},
+    MarkerEnvironment, MarkerTree, Package, Progress, ProgressBar, ProgressUnit, PythonRequest,
+};
+use pep440_rs::{Version, VersionSpecifier};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    io::{Read, Write},
+    str::FromStr,
+};
+
+/// A source of the versions and dependencies of packages such as a package index.
+pub trait PackageSource {
+    /// Get the available versions of a package.
+    fn versions(&mut self, name: &str) -> HuakResult<Vec<Version>>;
+
+    /// Get the dependencies of a version of a package.
+    fn dependencies(&mut self, name: &str, version: &Version) -> HuakResult<Vec<Package>>;
+
+    /// Get the files that can be downloaded for a version of a package.
+    fn artifacts(&mut self, name: &str, version: &Version) -> HuakResult<Vec<Artifact>>;
+
+    /// Get the URL of the index a package is served from.
+    fn index_url(&self, name: &str) -> String;
+
+    /// Get the versions of Python a version of a package supports from its
+    /// `Requires-Python`. Sources without this data support every version.
+    fn requires_python(
+        &mut self,
+        _name: &str,
+        _version: &Version,
+    ) -> HuakResult<Option<PythonRequest>> {
+        Ok(None)
+    }
+}
+
+/// A file that can be downloaded for a version of a package such as a wheel.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Artifact {
+    /// The file's name.
+    pub name: String,
+    /// Where the file can be downloaded from.
+    pub url: String,
+    /// The file's hash as `<algorithm>:<hex digest>`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+impl Artifact {
+    /// Get the hex digest of the file's sha256 hash.
+    pub fn sha256(&self) -> Option<&str> {
+        self.hash.as_deref()?.strip_prefix("sha256:")
+    }
+
+    /// Check the contents of a download against the file's hash. Files without a
+    /// hash aren't checked.
+    pub fn verify(&self, contents: &[u8]) -> HuakResult<()> {
+        self.copy_verified(&mut &contents[..], &mut std::io::sink(), |_| ())
+            .map(|_| ())
+    }
+
+    /// Copy a download from a reader to a writer while checking it against the file's
+    /// hash, so that it's never held in memory as a whole. Files without a hash aren't
+    /// checked. `progress` is called with the size of each chunk copied. The hex
+    /// digest of the download's sha256 hash is returned.
+    pub fn copy_verified(
+        &self,
+        reader: &mut impl Read,
+        writer: &mut impl Write,
+        progress: impl Fn(u64),
+    ) -> HuakResult<String> {
+        let expected = self
+            .hash
+            .as_deref()
+            .map(|it| it.split_once(':').unwrap_or(("", it)));
+        let mut hasher: Option<Box<dyn sha2::digest::DynDigest>> = match expected {
+            None | Some(("sha256", _)) => None,
+            Some(("sha384", _)) => Some(Box::new(Sha384::new())),
+            Some(("sha512", _)) => Some(Box::new(Sha512::new())),
+            Some(_) => {
+                return Err(HuakError::PackageInstallationError(format!(
+                    "unsupported hash {} for {}",
+                    self.hash.as_deref().unwrap_or_default(),
+                    self.name
+                )))
+            }
+        };
+        let mut sha256 = Sha256::new();
+        let mut buffer = [0; 64 * 1024];
+        loop {
+            let n = reader.read(&mut buffer)?;
+            if n == 0 {
+                break;
+            }
+            sha256.update(&buffer[..n]);
+            if let Some(hasher) = hasher.as_mut() {
+                hasher.update(&buffer[..n]);
+            }
+            writer.write_all(&buffer[..n])?;
+            progress(n as u64);
+        }
+        let sha256 = format!("{:x}", sha256.finalize());
+        if let Some((algorithm, digest)) = expected {
+            let actual = match hasher {
+                Some(it) => it.finalize().iter().map(|b| format!("{b:02x}")).collect(),
+                None => sha256.clone(),
+            };
+            if !actual.eq_ignore_ascii_case(digest) {
+                return Err(HuakError::HashMismatchError(
+                    self.name.clone(),
+                    self.hash.clone().unwrap_or_default(),
+                    format!("{algorithm}:{actual}"),
+                ));
+            }
+        }
+        Ok(sha256)
+    }
+}
+
+/// A dependency resolver producing the full transitive set of packages required by
+/// some requirements.
+pub struct Resolver<S: PackageSource> {
+    /// Where versions and dependencies are fetched from.
+    source: S,
+    /// The environment dependency markers are evaluated against.
+    environment: MarkerEnvironment,
+    /// Versions fetched from the source, newest first.
+    versions: HashMap<String, Vec<Version>>,
+    /// Dependencies fetched from the source.
+    dependencies: HashMap<(String, Version), Vec<Package>>,
+    /// Whether to resolve for every environment instead of only `environment`.
+    universal: bool,
+    /// The versions of Python the project being resolved supports.
+    requires_python: Option<PythonRequest>,
+    /// Where the packages fetched while resolving are reported.
+    progress: Progress,
+    /// The bar of the resolution in progress.
+    bar: Option<ProgressBar>,
+    /// Versions tried before any other version of a package, keyed by canonical name.
+    preferences: HashMap<String, Version>,
+}
+
+impl<S: PackageSource> Resolver<S> {
+    pub fn new(source: S, environment: MarkerEnvironment) -> Resolver<S> {
+        Resolver {
+            source,
+            environment,
+            versions: HashMap::new(),
+            dependencies: HashMap::new(),
+            universal: false,
+            requires_python: None,
+            progress: Progress::hidden(),
+            bar: None,
+            preferences: HashMap::new(),
+        }
+    }
+
+    /// Resolve for every environment instead of only the resolver's environment.
+    /// Dependencies are kept regardless of their environment markers, and the
+    /// resolved packages' markers record the environments they apply to.
+    pub fn with_universal(&mut self, universal: bool) -> &mut Resolver<S> {
+        self.universal = universal;
+        self
+    }
+
+    /// Only use releases supporting the project's `requires-python`. Releases are
+    /// skipped if their `Requires-Python` excludes the oldest Python the project
+    /// supports.
+    pub fn with_requires_python(
+        &mut self,
+        requires_python: Option<PythonRequest>,
+    ) -> &mut Resolver<S> {
+        self.requires_python = requires_python;
+        self
+    }
+
+    /// Report the packages fetched while resolving to a progress display.
+    pub fn with_progress(&mut self, progress: Progress) -> &mut Resolver<S> {
+        self.progress = progress;
+        self
+    }
+
+    /// Prefer versions of packages, such as the versions a lock already pins. A
+    /// preferred version is tried first as long as it satisfies the package's
+    /// constraints, so packages only move when they have to.
+    pub fn with_preferences(
+        &mut self,
+        preferences: impl IntoIterator<Item = (String, Version)>,
+    ) -> &mut Resolver<S> {
+        self.preferences = preferences
+            .into_iter()
+            .map(|(name, version)| (canonical_package_name(&name), version))
+            .collect();
+        self
+    }
+
+    /// Get the source versions and dependencies are fetched from.
+    pub fn source(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Resolve requirements into packages pinned with `==`. The extras are used to
+    /// evaluate the requirements' markers, so optional dependency groups can be
+    /// resolved with the requirements of their group. Each package's markers
+    /// combine the markers of the requirements leading to it.
+    pub fn resolve(
+        &mut self,
+        requirements: &[Package],
+        extras: &[&str],
+    ) -> HuakResult<Vec<Package>> {
+        self.bar =
+            Some(
+                self.progress
+                    .add_bar("Resolving", "dependencies", None, ProgressUnit::Items),
+            );
+        let result = self.resolve_pinned(requirements, extras);
+        self.bar = None;
+        result
+    }
+
+    fn resolve_pinned(
+        &mut self,
+        requirements: &[Package],
+        extras: &[&str],
+    ) -> HuakResult<Vec<Package>> {
+        let mut state = State::default();
+        let requirements = requirements
+            .iter()
+            .filter(|it| self.applies(it, extras))
+            .cloned()
+            .collect::<Vec<_>>();
+        for requirement in requirements.iter() {
+            if let Err(conflict) = self.add_constraint(&mut state, requirement, None)? {
+                return Err(HuakError::DependencyResolutionError(conflict.message));
+            }
+        }
+        let state = match self.solve(state)? {
+            Ok(it) => it,
+            Err(conflict) => return Err(HuakError::DependencyResolutionError(conflict.message)),
+        };
+        let mut markers = HashMap::new();
+        state
+            .decisions
+            .iter()
+            .map(|(canonical_name, version)| {
+                let mut package =
+                    Package::from_str(&format!("{}=={version}", state.names[canonical_name]))?;
+                package.markers =
+                    package_markers(&state, canonical_name, &mut markers, &mut Vec::new());
+                Ok(package)
+            })
+            .collect()
+    }
+
+    /// Check if a requirement applies with a set of extras requested.
+    fn applies(&self, package: &Package, extras: &[&str]) -> bool {
+        match package.markers() {
+            Some(markers) if self.universal => markers.evaluate_extras(extras),
+            _ => package.applies_to(&self.environment, extras),
+        }
+    }
+
+    /// Decide versions for every required package that doesn't have one yet.
+    fn solve(&mut self, state: State) -> HuakResult<Result<State, Conflict>> {
+        // Deciding the package with the fewest candidates first finds conflicts
+        // sooner.
+        let mut next: Option<(&String, Vec<Version>)> = None;
+        for canonical_name in state.constraints.keys() {
+            if state.decisions.contains_key(canonical_name) {
+                continue;
+            }
+            let candidates = self.candidates(canonical_name, &state.constraints[canonical_name])?;
+            let fewer = match next.as_ref() {
+                Some((_, it)) => candidates.len() < it.len(),
+                None => true,
+            };
+            if fewer {
+                next = Some((canonical_name, candidates));
+            }
+        }
+        let (canonical_name, candidates) = match next {
+            Some((name, candidates)) => (name.clone(), candidates),
+            None => return Ok(Ok(state)),
+        };
+        if candidates.is_empty() {
+            return Ok(Err(self.explain_unsatisfiable(&state, &canonical_name)?));
+        }
+
+        let name = state.names[&canonical_name].clone();
+        let constraints = &state.constraints[&canonical_name];
+        let mut extras = constraints
+            .iter()
+            .flat_map(|it| it.extras.iter().cloned())
+            .collect::<Vec<_>>();
+        extras.sort();
+        extras.dedup();
+
+        let mut conflicts = Vec::new();
+        for version in candidates {
+            let mut next_state = state.clone();
+            next_state
+                .decisions
+                .insert(canonical_name.clone(), version.clone());
+            next_state
+                .activated_extras
+                .insert(canonical_name.clone(), extras.clone());
+            let requirer = Requirer {
+                canonical_name: canonical_name.clone(),
+                display: format!("{name} {version}"),
+            };
+            let extras = extras.iter().map(|it| it.as_str()).collect::<Vec<_>>();
+            let dependencies = self
+                .dependencies(&name, &version)?
+                .into_iter()
+                .filter(|it| self.applies(it, &extras))
+                .collect::<Vec<_>>();
+            let mut result = Ok(());
+            for dependency in dependencies.iter() {
+                result = self.add_constraint(&mut next_state, dependency, Some(&requirer))?;
+                if result.is_err() {
+                    break;
+                }
+            }
+            let conflict = match result {
+                Ok(()) => match self.solve(next_state)? {
+                    Ok(it) => return Ok(Ok(it)),
+                    Err(conflict) => conflict,
+                },
+                Err(conflict) => conflict,
+            };
+            // Trying other versions can't help with a conflict this package didn't
+            // cause.
+            if !conflict.causes.contains(&canonical_name) {
+                return Ok(Err(conflict));
+            }
+            conflicts.push(conflict);
+        }
+
+        let mut causes = conflicts
+            .iter()
+            .flat_map(|it| it.causes.iter().cloned())
+            .collect::<BTreeSet<_>>();
+        causes.remove(&canonical_name);
+        causes.extend(constraints.iter().filter_map(|it| it.requirer_name()));
+        let mut message = conflicts.remove(0).message;
+        if !conflicts.is_empty() {
+            message.push_str(&format!(", and no other version of {name} is compatible"));
+        }
+        Ok(Err(Conflict { causes, message }))
+    }
+
+    /// Add a requirement on a package. If the package already has a version decided
+    /// the version must satisfy the requirement.
+    fn add_constraint(
+        &mut self,
+        state: &mut State,
+        package: &Package,
+        requirer: Option<&Requirer>,
+    ) -> HuakResult<Result<(), Conflict>> {
+        let canonical_name = package.canonical_name().to_string();
+        state
+            .names
+            .entry(canonical_name.clone())
+            .or_insert_with(|| package.name().to_string());
+        // Extras of dependencies were handled when their requirers were decided,
+        // while root requirements keep theirs to select optional dependency groups.
+        let markers = match requirer {
+            Some(_) => package.markers().and_then(|it| it.without_extras()),
+            None => package.markers().cloned(),
+        };
+        let constraint = Constraint {
+            specifiers: package.version_specifiers().to_vec(),
+            extras: package.extras().to_vec(),
+            markers,
+            requirer: requirer.cloned(),
+        };
+        let constraints = state.constraints.entry(canonical_name.clone()).or_default();
+        constraints.push(constraint.clone());
+
+        let version = match state.decisions.get(&canonical_name) {
+            Some(it) => it.clone(),
+            None => return Ok(Ok(())),
+        };
+        if !constraint.contains(&version) {
+            let name = &state.names[&canonical_name];
+            let constraints = constraints.clone();
+            let mut causes = constraints
+                .iter()
+                .filter_map(|it| it.requirer_name())
+                .collect::<BTreeSet<_>>();
+            causes.insert(canonical_name.clone());
+            let mut message = None;
+            for other in constraints.iter().take(constraints.len() - 1) {
+                if self
+                    .candidates(&canonical_name, &[other.clone(), constraint.clone()])?
+                    .is_empty()
+                {
+                    message = Some(format!(
+                        "{} but {}",
+                        other.describe(name),
+                        constraint.describe(name)
+                    ));
+                    break;
+                }
+            }
+            let message = message.unwrap_or_else(|| {
+                format!(
+                    "{} but {name} {version} was already selected",
+                    constraint.describe(name)
+                )
+            });
+            return Ok(Err(Conflict { causes, message }));
+        }
+
+        // Requesting new extras of a decided package requires their dependencies.
+        let activated = state
+            .activated_extras
+            .get(&canonical_name)
+            .cloned()
+            .unwrap_or_default();
+        if constraint.extras.iter().all(|it| activated.contains(it)) {
+            return Ok(Ok(()));
+        }
+        let mut extras = activated.clone();
+        extras.extend(constraint.extras.iter().cloned());
+        extras.sort();
+        extras.dedup();
+        state
+            .activated_extras
+            .insert(canonical_name.clone(), extras.clone());
+        let name = state.names[&canonical_name].clone();
+        let requirer = Requirer {
+            canonical_name,
+            display: format!("{name} {version}"),
+        };
+        let activated = activated.iter().map(|it| it.as_str()).collect::<Vec<_>>();
+        let extras = extras.iter().map(|it| it.as_str()).collect::<Vec<_>>();
+        let dependencies = self
+            .dependencies(&name, &version)?
+            .into_iter()
+            .filter(|it| self.applies(it, &extras) && !self.applies(it, &activated))
+            .collect::<Vec<_>>();
+        for dependency in dependencies.iter() {
+            if let Err(conflict) = self.add_constraint(state, dependency, Some(&requirer))? {
+                return Ok(Err(conflict));
+            }
+        }
+        Ok(Ok(()))
+    }
+
+    /// Explain why no version of a package satisfies its requirements.
+    fn explain_unsatisfiable(
+        &mut self,
+        state: &State,
+        canonical_name: &str,
+    ) -> HuakResult<Conflict> {
+        let name = &state.names[canonical_name];
+        let constraints = &state.constraints[canonical_name];
+        let mut causes = constraints
+            .iter()
+            .filter_map(|it| it.requirer_name())
+            .collect::<BTreeSet<_>>();
+        causes.insert(canonical_name.to_string());
+        let conflict = |message| Conflict {
+            causes: causes.clone(),
+            message,
+        };
+
+        if self.versions(canonical_name)?.is_empty() {
+            return Ok(conflict(format!(
+                "{} but no versions of {name} were found",
+                constraints[0].describe(name)
+            )));
+        }
+        for constraint in constraints {
+            if self
+                .candidates(canonical_name, std::slice::from_ref(constraint))?
+                .is_empty()
+            {
+                return Ok(conflict(format!(
+                    "{} but no version of {name} satisfies it",
+                    constraint.describe(name)
+                )));
+            }
+        }
+        for (i, a) in constraints.iter().enumerate() {
+            for b in constraints.iter().skip(i + 1) {
+                if self
+                    .candidates(canonical_name, &[a.clone(), b.clone()])?
+                    .is_empty()
+                {
+                    return Ok(conflict(format!(
+                        "{} but {}",
+                        a.describe(name),
+                        b.describe(name)
+                    )));
+                }
+            }
+        }
+        let requirements = constraints
+            .iter()
+            .map(|it| it.describe(name))
+            .collect::<Vec<_>>();
+        Ok(conflict(format!(
+            "no version of {name} satisfies all of: {}",
+            requirements.join(", ")
+        )))
+    }
+
+    /// Get the versions of a package satisfying constraints, newest first after the
+    /// package's preferred version. Pre-releases are only candidates if a constraint
+    /// requests one or if no final release satisfies the constraints.
+    fn candidates(
+        &mut self,
+        canonical_name: &str,
+        constraints: &[Constraint],
+    ) -> HuakResult<Vec<Version>> {
+        let satisfying = self
+            .versions(canonical_name)?
+            .iter()
+            .filter(|version| constraints.iter().all(|it| it.contains(version)))
+            .cloned()
+            .collect::<Vec<_>>();
+        let allow_prereleases = constraints
+            .iter()
+            .flat_map(|it| it.specifiers.iter())
+            .any(|it| it.version().any_prerelease())
+            || satisfying.iter().all(|it| it.any_prerelease());
+        let mut candidates = satisfying
+            .into_iter()
+            .filter(|it| allow_prereleases || !it.any_prerelease())
+            .collect::<Vec<_>>();
+        if let Some(preferred) = self.preferences.get(canonical_name) {
+            if let Some(index) = candidates.iter().position(|it| it == preferred) {
+                let version = candidates.remove(index);
+                candidates.insert(0, version);
+            }
+        }
+        Ok(candidates)
+    }
+
+    fn versions(&mut self, canonical_name: &str) -> HuakResult<&Vec<Version>> {
+        if !self.versions.contains_key(canonical_name) {
+            if let Some(bar) = self.bar.as_ref() {
+                bar.set_message(canonical_name);
+                bar.inc(1);
+            }
+            let mut versions = Vec::new();
+            let pythons = self.python_versions();
+            for version in self.source.versions(canonical_name)? {
+                let supported = match self.source.requires_python(canonical_name, &version)? {
+                    Some(requires_python) => pythons.iter().all(|it| requires_python.contains(it)),
+                    None => true,
+                };
+                if supported {
+                    versions.push(version);
+                }
+            }
+            versions.sort();
+            versions.reverse();
+            self.versions.insert(canonical_name.to_string(), versions);
+        }
+        Ok(&self.versions[canonical_name])
+    }
+
+    /// Get the versions of Python releases need to support: the oldest version the
+    /// project supports, and the environment's version unless resolving universally.
+    fn python_versions(&self) -> Vec<Version> {
+        let mut versions = Vec::new();
+        if let Some(version) = self
+            .requires_python
+            .as_ref()
+            .and_then(|it| it.lower_bound())
+        {
+            versions.push(version.clone());
+        }
+        if !self.universal {
+            versions.extend(Version::from_str(&self.environment.python_full_version).ok());
+        }
+        versions
+    }
+
+    fn dependencies(&mut self, name: &str, version: &Version) -> HuakResult<Vec<Package>> {
+        let key = (canonical_package_name(name), version.clone());
+        if !self.dependencies.contains_key(&key) {
+            let dependencies = self.source.dependencies(name, version)?;
+            self.dependencies.insert(key.clone(), dependencies);
+        }
+        Ok(self.dependencies[&key].clone())
+    }
+}
+
+/// Combine the markers of every requirement leading to a decided package. Requirers
+/// already being visited are skipped to break dependency cycles.
+fn package_markers(
+    state: &State,
+    canonical_name: &str,
+    memo: &mut HashMap<String, Option<MarkerTree>>,
+    visiting: &mut Vec<String>,
+) -> Option<MarkerTree> {
+    if let Some(markers) = memo.get(canonical_name) {
+        return markers.clone();
+    }
+    visiting.push(canonical_name.to_string());
+    let mut paths = Vec::new();
+    for constraint in state.constraints[canonical_name].iter() {
+        let requirer_markers = match constraint.requirer.as_ref() {
+            Some(it) if visiting.contains(&it.canonical_name) => continue,
+            Some(it) => package_markers(state, &it.canonical_name, memo, visiting),
+            None => None,
+        };
+        paths.push(MarkerTree::and([
+            requirer_markers,
+            constraint.markers.clone(),
+        ]));
+    }
+    visiting.pop();
+    let markers = MarkerTree::or(paths);
+    memo.insert(canonical_name.to_string(), markers.clone());
+    markers
+}
+
+/// The progress of a resolution.
+#[derive(Clone, Default)]
+struct State {
+    /// The requirements on each package by canonical name.
+    constraints: BTreeMap<String, Vec<Constraint>>,
+    /// The name each package was first required as.
+    names: HashMap<String, String>,
+    /// The version decided for each package.
+    decisions: BTreeMap<String, Version>,
+    /// The extras whose dependencies were added for each decided package.
+    activated_extras: HashMap<String, Vec<String>>,
+}
+
+/// A package version requiring another package.
+#[derive(Clone)]
+struct Requirer {
+    canonical_name: String,
+    /// The package and its version such as `requests 2.28.2`.
+    display: String,
+}
+
+/// A requirement on a package from the root requirements or a decided package.
+#[derive(Clone)]
+struct Constraint {
+    specifiers: Vec<VersionSpecifier>,
+    extras: Vec<String>,
+    /// The markers of the requirement.
+    markers: Option<MarkerTree>,
+    /// The package version requiring the package. Root requirements don't have one.
+    requirer: Option<Requirer>,
+}
+
+impl Constraint {
+    fn contains(&self, version: &Version) -> bool {
+        self.specifiers.iter().all(|it| it.contains(version))
+    }
+
+    fn requirer_name(&self) -> Option<String> {
+        self.requirer.as_ref().map(|it| it.canonical_name.clone())
+    }
+
+    /// Describe the requirement such as `requests 2.28.2 requires urllib3<1.27`.
+    fn describe(&self, name: &str) -> String {
+        let requirer = match self.requirer.as_ref() {
+            Some(it) => it.display.as_str(),
+            None => "the project",
+        };
+        let specifiers = self
+            .specifiers
+            .iter()
+            .map(|it| it.to_string())
+            .collect::<Vec<_>>();
+        format!("{requirer} requires {name}{}", specifiers.join(","))
+    }
+}
+
+/// Why a set of decisions can't be part of a solution.
+struct Conflict {
+    /// The packages whose decisions or requirements led to the conflict.
+    causes: BTreeSet<String>,
+    /// A human-readable explanation of the conflict.
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A package source backed by `(name, version, dependencies)` entries.
+    struct MockSource(Vec<(&'static str, &'static str, Vec<&'static str>)>);
+
+    impl PackageSource for MockSource {
+        fn versions(&mut self, name: &str) -> HuakResult<Vec<Version>> {
+            Ok(self
+                .0
+                .iter()
+                .filter(|(it, _, _)| canonical_package_name(it) == name)
+                .map(|(_, version, _)| Version::from_str(version).unwrap())
+                .collect())
+        }
+
+        fn dependencies(&mut self, name: &str, version: &Version) -> HuakResult<Vec<Package>> {
+            let (_, _, dependencies) = self
+                .0
+                .iter()
+                .find(|(it, v, _)| {
+                    canonical_package_name(it) == canonical_package_name(name)
+                        && Version::from_str(v).unwrap() == *version
+                })
+                .unwrap();
+            dependencies
+                .iter()
+                .map(|it| Package::from_str(it))
+                .collect()
+        }
+
+        fn artifacts(&mut self, name: &str, version: &Version) -> HuakResult<Vec<Artifact>> {
+            let file_name = format!("{name}-{version}-py3-none-any.whl");
+            Ok(vec![Artifact {
+                url: format!("https://example.com/{file_name}"),
+                name: file_name,
+                hash: None,
+            }])
+        }
+
+        fn index_url(&self, _name: &str) -> String {
+            "https://example.com".to_string()
+        }
+    }
+
+    fn resolve(
+        source: Vec<(&'static str, &'static str, Vec<&'static str>)>,
+        requirements: &[&str],
+    ) -> HuakResult<Vec<String>> {
+        let requirements = requirements
+            .iter()
+            .map(|it| Package::from_str(it).unwrap())
+            .collect::<Vec<_>>();
+        let mut resolver = Resolver::new(MockSource(source), MarkerEnvironment::default());
+        Ok(resolver
+            .resolve(&requirements, &[])?
+            .iter()
+            .map(|it| it.dependency_string())
+            .collect())
+    }
+
+    #[test]
+    fn resolve_transitive_dependencies() {
+        let source = vec![
+            ("a", "1.0.0", vec!["b>=1"]),
+            ("a", "2.0.0", vec!["b>=2", "c"]),
+            ("b", "1.0.0", vec![]),
+            ("b", "2.0.0", vec![]),
+            ("b", "3.0.0a1", vec![]),
+            ("c", "1.0.0", vec![]),
+        ];
+
+        assert_eq!(
+            resolve(source, &["a"]).unwrap(),
+            ["a==2.0.0", "b==2.0.0", "c==1.0.0"]
+        );
+    }
+
+    #[test]
+    fn resolve_backtracks() {
+        let source = vec![
+            ("a", "1.0.0", vec!["b<2"]),
+            ("a", "2.0.0", vec!["b>=2"]),
+            ("b", "1.0.0", vec![]),
+            ("b", "2.0.0", vec![]),
+            ("c", "1.0.0", vec!["b<2"]),
+        ];
+
+        assert_eq!(
+            resolve(source, &["a", "c"]).unwrap(),
+            ["a==1.0.0", "b==1.0.0", "c==1.0.0"]
+        );
+    }
+
+    #[test]
+    fn resolve_extras() {
+        let source = vec![
+            ("a", "1.0.0", vec!["b", "c; extra == \"c\""]),
+            ("b", "1.0.0", vec![]),
+            ("c", "1.0.0", vec![]),
+            ("d", "1.0.0", vec!["a[c]"]),
+        ];
+
+        assert_eq!(
+            resolve(source.clone(), &["a"]).unwrap(),
+            ["a==1.0.0", "b==1.0.0"]
+        );
+        assert_eq!(
+            resolve(source, &["a", "d"]).unwrap(),
+            ["a==1.0.0", "b==1.0.0", "c==1.0.0", "d==1.0.0"]
+        );
+    }
+
+    #[test]
+    fn resolve_universal_markers() {
+        let source = vec![
+            ("a", "1.0.0", vec!["b; sys_platform == \"win32\"", "c"]),
+            ("b", "1.0.0", vec!["c"]),
+            ("c", "1.0.0", vec![]),
+            (
+                "d",
+                "1.0.0",
+                vec!["b; python_version < \"3.8\" and extra == \"d\""],
+            ),
+        ];
+        let requirements = [
+            Package::from_str("a").unwrap(),
+            Package::from_str("d[d]; extra == \"test\"").unwrap(),
+        ];
+        let mut resolver = Resolver::new(MockSource(source), MarkerEnvironment::default());
+
+        let packages = resolver
+            .with_universal(true)
+            .resolve(&requirements, &["test"])
+            .unwrap()
+            .iter()
+            .map(|it| it.dependency_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            packages,
+            [
+                "a==1.0.0",
+                "b==1.0.0; sys_platform == \"win32\" or extra == \"test\" and python_version < \"3.8\"",
+                "c==1.0.0",
+                "d==1.0.0; extra == \"test\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_conflict_explanation() {
+        let source = vec![
+            ("a", "1.0.0", vec!["b<2"]),
+            ("b", "1.0.0", vec![]),
+            ("b", "2.0.0", vec![]),
+            ("c", "1.0.0", vec!["b>=2"]),
+        ];
+
+        let error = resolve(source.clone(), &["a", "c"]).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "a problem with dependency resolution occurred: a 1.0.0 requires b<2 but c 1.0.0 requires b>=2"
+        );
+
+        let error = resolve(source, &["a", "d"]).unwrap_err();
+
+        assert!(error
+            .to_string()
+            .ends_with("the project requires d but no versions of d were found"));
+    }
+
+    /// A package source whose releases declare the versions of Python they support
+    /// as `(name, version, requires-python)` entries.
+    struct RequiresPythonSource(MockSource, Vec<(&'static str, &'static str, &'static str)>);
+
+    impl PackageSource for RequiresPythonSource {
+        fn versions(&mut self, name: &str) -> HuakResult<Vec<Version>> {
+            self.0.versions(name)
+        }
+
+        fn dependencies(&mut self, name: &str, version: &Version) -> HuakResult<Vec<Package>> {
+            self.0.dependencies(name, version)
+        }
+
+        fn artifacts(&mut self, name: &str, version: &Version) -> HuakResult<Vec<Artifact>> {
+            self.0.artifacts(name, version)
+        }
+
+        fn index_url(&self, name: &str) -> String {
+            self.0.index_url(name)
+        }
+
+        fn requires_python(
+            &mut self,
+            name: &str,
+            version: &Version,
+        ) -> HuakResult<Option<PythonRequest>> {
+            Ok(self
+                .1
+                .iter()
+                .find(|(it, v, _)| *it == name && Version::from_str(v).unwrap() == *version)
+                .map(|(_, _, it)| PythonRequest::from_str(it).unwrap()))
+        }
+    }
+
+    #[test]
+    fn resolve_requires_python() {
+        let resolve = |requires_python: Option<&str>, python_version: &str, universal| {
+            let source = RequiresPythonSource(
+                MockSource(vec![
+                    ("a", "1.0.0", vec![]),
+                    ("a", "2.0.0", vec![]),
+                    ("b", "1.0.0", vec![]),
+                ]),
+                vec![("a", "2.0.0", ">=3.10"), ("b", "1.0.0", ">=3.7")],
+            );
+            let environment = MarkerEnvironment {
+                python_full_version: python_version.to_string(),
+                ..Default::default()
+            };
+            let mut resolver = Resolver::new(source, environment);
+            resolver
+                .with_universal(universal)
+                .with_requires_python(
+                    requires_python.map(|it| PythonRequest::from_str(it).unwrap()),
+                )
+                .resolve(
+                    &[
+                        Package::from_str("a").unwrap(),
+                        Package::from_str("b").unwrap(),
+                    ],
+                    &[],
+                )
+                .unwrap()
+                .iter()
+                .map(|it| it.dependency_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(resolve(Some(">=3.8"), "", true), ["a==1.0.0", "b==1.0.0"]);
+        assert_eq!(resolve(Some(">=3.10"), "", true), ["a==2.0.0", "b==1.0.0"]);
+        assert_eq!(resolve(None, "3.9.1", false), ["a==1.0.0", "b==1.0.0"]);
+        assert_eq!(resolve(None, "3.11.2", false), ["a==2.0.0", "b==1.0.0"]);
+        assert_eq!(
+            resolve(Some(">=3.8"), "3.11.2", false),
+            ["a==1.0.0", "b==1.0.0"]
+        );
+    }
+}
@@ -0,0 +1,592 @@
+///! This module implements installing and managing standalone CPython builds from
+///! python-build-standalone. See https://github.com/astral-sh/python-build-standalone
+///!
+///! Builds are installed to the `pythons` directory of huak's data directory, which is
+///! `HUAK_DATA_DIR` if it's set or the platform's data directory otherwise. Each build
+///! is kept in its own directory named `cpython-<version>-<target>`.
+///!
+///! Builds are found in one of:
+///!
+///! - A directory of archives set with `HUAK_PYTHON_ARCHIVES`, for working offline.
+///! - A mirror set with `HUAK_PYTHON_MIRROR`. Mirrors are pages linking to the
+///!   archives, like a PEP 503 project page.
+///! - The latest python-build-standalone release.
+///!
+///! Every archive is verified with its sha256 checksum before it's unpacked. The
+///! checksum is taken from the archive's link, a `SHA256SUMS` file, or a
+///! `<archive>.sha256` file next to the archive.
+use crate::{
+    error::{HuakError, HuakResult},
+    fs, index, sys, Artifact, PackageIndexClient, PythonRequest,
+};
+use pep440_rs::Version;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// The environment variable overriding huak's data directory.
+const DATA_DIR_ENV_VAR: &str = "HUAK_DATA_DIR";
+/// The environment variable naming a directory of pre-downloaded archives.
+const ARCHIVE_DIR_ENV_VAR: &str = "HUAK_PYTHON_ARCHIVES";
+/// The environment variable naming a mirror of python-build-standalone's archives.
+const MIRROR_ENV_VAR: &str = "HUAK_PYTHON_MIRROR";
+const RELEASES_URL: &str =
+    "https://api.github.com/repos/astral-sh/python-build-standalone/releases/latest";
+/// The suffixes of the archives containing only a build's installation, from most
+/// to least preferred.
+const ARCHIVE_SUFFIXES: [&str; 2] = ["-install_only.tar.gz", "-install_only_stripped.tar.gz"];
+/// The name of the file listing the checksums of a release's archives.
+const CHECKSUMS_FILE_NAME: &str = "SHA256SUMS";
+
+/// Where standalone builds are downloaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PythonSource {
+    /// The GitHub API URL of a python-build-standalone release.
+    Releases(String),
+    /// The URL of a page linking to archives.
+    Mirror(String),
+    /// A directory of archives.
+    Directory(PathBuf),
+}
+
+impl PythonSource {
+    /// Use the archive directory or mirror from the environment, falling back to the
+    /// latest python-build-standalone release.
+    pub fn from_env() -> PythonSource {
+        let var = |name| std::env::var(name).ok().filter(|it| !it.is_empty());
+        if let Some(dir) = var(ARCHIVE_DIR_ENV_VAR) {
+            return PythonSource::Directory(PathBuf::from(dir));
+        }
+        match var(MIRROR_ENV_VAR) {
+            Some(url) => PythonSource::Mirror(url),
+            None => PythonSource::Releases(RELEASES_URL.to_string()),
+        }
+    }
+
+    /// Get the files the source provides. Each file's location is a URL, or a path
+    /// for directory sources.
+    fn files(&self, client: &PackageIndexClient) -> HuakResult<Vec<SourceFile>> {
+        match self {
+            PythonSource::Directory(dir) => {
+                let mut files: Vec<SourceFile> = fs::flatten_directories([dir.clone()])
+                    .filter(|it| it.is_file())
+                    .filter_map(|it| {
+                        Some(SourceFile {
+                            name: it.file_name()?.to_str()?.to_string(),
+                            location: it.to_str()?.to_string(),
+                            sha256: None,
+                        })
+                    })
+                    .collect();
+                files.sort_by(|a, b| a.name.cmp(&b.name));
+                Ok(files)
+            }
+            PythonSource::Mirror(url) => {
+                let contents = self.read(client, url)?;
+                let base_url = reqwest::Url::parse(url).map_err(|e| {
+                    HuakError::PythonInstallError(format!("invalid mirror url {url}: {e}"))
+                })?;
+                let page =
+                    index::parse_simple_html("python", std::str::from_utf8(&contents)?, &base_url)?;
+                Ok(page
+                    .files()
+                    .iter()
+                    .map(|it| SourceFile {
+                        name: it.filename().to_string(),
+                        location: it.url().to_string(),
+                        sha256: it.hashes().get("sha256").cloned(),
+                    })
+                    .collect())
+            }
+            PythonSource::Releases(url) => {
+                let release: Release = serde_json::from_slice(&self.read(client, url)?)?;
+                Ok(release
+                    .assets
+                    .into_iter()
+                    .map(|it| SourceFile {
+                        name: it.name,
+                        location: it.browser_download_url,
+                        sha256: it
+                            .digest
+                            .as_deref()
+                            .and_then(|it| it.strip_prefix("sha256:"))
+                            .map(|it| it.to_lowercase()),
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// Read a file from the source.
+    fn read(&self, client: &PackageIndexClient, location: &str) -> HuakResult<Vec<u8>> {
+        if let PythonSource::Directory(_) = self {
+            return Ok(std::fs::read(location)?);
+        }
+        match client.get_cached(location, "*/*")? {
+            Some(response) => Ok(response.body),
+            None => Err(HuakError::PythonInstallError(format!(
+                "{} was not found",
+                crate::auth::redact(location)
+            ))),
+        }
+    }
+
+    /// Get the sha256 checksum of an archive. Checksums from the archive's link are
+    /// preferred over those listed in checksum files.
+    fn sha256(
+        &self,
+        client: &PackageIndexClient,
+        files: &[SourceFile],
+        archive: &SourceFile,
+    ) -> HuakResult<String> {
+        if let Some(sha256) = archive.sha256.as_ref() {
+            return Ok(sha256.clone());
+        }
+        if let Some(file) = files.iter().find(|it| it.name == CHECKSUMS_FILE_NAME) {
+            let contents =
+                String::from_utf8_lossy(&self.read(client, &file.location)?).into_owned();
+            if let Some(sha256) = parse_checksums(&contents).remove(&archive.name) {
+                return Ok(sha256);
+            }
+        }
+        let checksum_name = format!("{}.sha256", archive.name);
+        if let Some(file) = files.iter().find(|it| it.name == checksum_name) {
+            let contents =
+                String::from_utf8_lossy(&self.read(client, &file.location)?).into_owned();
+            if let Some(sha256) = contents.split_whitespace().next() {
+                return Ok(sha256.to_lowercase());
+            }
+        }
+        Err(HuakError::HashMissingError(archive.name.clone()))
+    }
+}
+
+impl Default for PythonSource {
+    fn default() -> Self {
+        PythonSource::from_env()
+    }
+}
+
+/// A file provided by a source.
+struct SourceFile {
+    name: String,
+    location: String,
+    sha256: Option<String>,
+}
+
+/// The parts of a GitHub release used to find its archives.
+#[derive(Deserialize)]
+struct Release {
+    assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Deserialize)]
+struct ReleaseAsset {
+    name: String,
+    browser_download_url: String,
+    #[serde(default)]
+    digest: Option<String>,
+}
+
+/// A standalone build installed by huak.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManagedPython {
+    /// The directory the build is installed to.
+    root: PathBuf,
+    version: Version,
+    /// The target triple the build is for, such as `x86_64-unknown-linux-gnu`.
+    target: String,
+}
+
+impl ManagedPython {
+    /// Get a managed build from the directory it's installed to.
+    fn from_path(path: impl AsRef<Path>) -> Option<ManagedPython> {
+        let name = path.as_ref().file_name()?.to_str()?;
+        let (version, target) = name.strip_prefix("cpython-")?.split_once('-')?;
+        Some(ManagedPython {
+            root: path.as_ref().to_path_buf(),
+            version: Version::from_str(version).ok()?,
+            target: target.to_string(),
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Get the absolute path to the build's interpreter.
+    pub fn python_path(&self) -> PathBuf {
+        #[cfg(unix)]
+        let path = self.root.join("bin").join("python3");
+        #[cfg(windows)]
+        let path = self.root.join("python.exe");
+        path
+    }
+}
+
+/// The standalone builds installed by huak.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManagedPythons {
+    /// The directory builds are installed to.
+    dir: PathBuf,
+}
+
+impl ManagedPythons {
+    /// Use the `pythons` directory of huak's data directory.
+    pub fn new() -> ManagedPythons {
+        ManagedPythons {
+            dir: default_data_dir().join("pythons"),
+        }
+    }
+
+    /// Use a directory to install builds to.
+    pub fn from_path(path: impl AsRef<Path>) -> ManagedPythons {
+        ManagedPythons {
+            dir: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Get the installed builds ordered by version.
+    pub fn installed(&self) -> Vec<ManagedPython> {
+        let mut installed: Vec<ManagedPython> = fs::flatten_directories([self.dir.clone()])
+            .filter(|it| it.is_dir())
+            .filter_map(ManagedPython::from_path)
+            .filter(|it| it.python_path().exists())
+            .collect();
+        installed.sort_by(|a, b| a.version.cmp(&b.version).then(a.target.cmp(&b.target)));
+        installed
+    }
+
+    /// Get the latest installed build satisfying a request.
+    pub fn find(&self, request: &PythonRequest) -> Option<ManagedPython> {
+        self.installed()
+            .into_iter()
+            .filter(|it| request.contains(&it.version))
+            .next_back()
+    }
+
+    /// Install the latest build satisfying a request from a source, unless one is
+    /// already installed. Downloaded archives are kept in the client's cache.
+    pub fn install(
+        &self,
+        request: &PythonRequest,
+        source: &PythonSource,
+        client: &PackageIndexClient,
+    ) -> HuakResult<ManagedPython> {
+        if let Some(installed) = self.find(request) {
+            return Ok(installed);
+        }
+        let targets = host_targets();
+        let files = source.files(client)?;
+        let archive = files
+            .iter()
+            .filter_map(|file| {
+                let (version, target, suffix) = parse_archive_name(&file.name)?;
+                let target_priority = targets.iter().position(|it| *it == target)?;
+                Some((file, version, target, (target_priority, suffix)))
+            })
+            .filter(|(_, version, _, _)| request.contains(version))
+            // The latest version, preferring the host's first target and archive type.
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.3.cmp(&a.3)));
+        let (file, version, target, _) = match archive {
+            Some(it) => it,
+            None => {
+                return Err(HuakError::PythonVersionNotFoundError(format!(
+                    "{request} for {}",
+                    targets.join(" or ")
+                )))
+            }
+        };
+        let artifact = Artifact {
+            name: file.name.clone(),
+            url: file.location.clone(),
+            hash: Some(format!("sha256:{}", source.sha256(client, &files, file)?)),
+        };
+        std::fs::create_dir_all(&self.dir)?;
+        let temp_dir = self.dir.join(format!(".{}.tmp", file.name));
+        if temp_dir.exists() {
+            std::fs::remove_dir_all(&temp_dir)?;
+        }
+        std::fs::create_dir_all(&temp_dir)?;
+        let result = self.unpack(source, client, &artifact, &temp_dir, &version, &target);
+        // Failing to clean up mustn't hide whether the build was installed.
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        result
+    }
+
+    /// Download, verify, and unpack an archive into its installation directory.
+    fn unpack(
+        &self,
+        source: &PythonSource,
+        client: &PackageIndexClient,
+        artifact: &Artifact,
+        temp_dir: &Path,
+        version: &Version,
+        target: &str,
+    ) -> HuakResult<ManagedPython> {
+        let archive_path = match source {
+            PythonSource::Directory(_) => {
+                let path = PathBuf::from(&artifact.url);
+                artifact.copy_verified(
+                    &mut std::fs::File::open(&path)?,
+                    &mut std::io::sink(),
+                    |_| (),
+                )?;
+                path
+            }
+            _ => client.download_artifact(artifact, temp_dir)?,
+        };
+        let unpacked = temp_dir.join("unpacked");
+        let archive = std::fs::File::open(archive_path)?;
+        tar::Archive::new(flate2::read::GzDecoder::new(archive)).unpack(&unpacked)?;
+        // The archives contain a single `python` directory.
+        let python_dir = unpacked.join("python");
+        if !python_dir.is_dir() {
+            return Err(HuakError::PythonInstallError(format!(
+                "{} doesn't contain a python directory",
+                artifact.name
+            )));
+        }
+        let root = self.dir.join(format!("cpython-{version}-{target}"));
+        if root.exists() {
+            std::fs::remove_dir_all(&root)?;
+        }
+        std::fs::rename(python_dir, &root)?;
+        ManagedPython::from_path(&root)
+            .filter(|it| it.python_path().exists())
+            .ok_or_else(|| {
+                HuakError::PythonInstallError(format!(
+                    "{} doesn't contain a python interpreter",
+                    artifact.name
+                ))
+            })
+    }
+
+    /// Uninstall the latest installed build satisfying a request.
+    pub fn uninstall(&self, request: &PythonRequest) -> HuakResult<ManagedPython> {
+        let installed = self
+            .find(request)
+            .ok_or_else(|| HuakError::PythonVersionNotFoundError(request.to_string()))?;
+        std::fs::remove_dir_all(installed.root())?;
+        Ok(installed)
+    }
+}
+
+impl Default for ManagedPythons {
+    fn default() -> Self {
+        ManagedPythons::new()
+    }
+}
+
+/// Parse the version, target, and archive type preference from the name of a
+/// python-build-standalone archive such as
+/// `cpython-3.11.4+20230726-x86_64-unknown-linux-gnu-install_only.tar.gz`.
+fn parse_archive_name(name: &str) -> Option<(Version, String, usize)> {
+    let name = name.strip_prefix("cpython-")?;
+    let (name, suffix) = ARCHIVE_SUFFIXES
+        .iter()
+        .enumerate()
+        .find_map(|(i, it)| Some((name.strip_suffix(it)?, i)))?;
+    let (version, target) = name.split_once('-')?;
+    // The version is followed by the build's release date.
+    let version = version.split('+').next()?;
+    Some((Version::from_str(version).ok()?, target.to_string(), suffix))
+}
+
+/// Parse a checksums file with lines of `<sha256> <file name>`.
+fn parse_checksums(contents: &str) -> BTreeMap<String, String> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let sha256 = parts.next()?;
+            let name = parts.next()?.trim_start_matches('*');
+            Some((name.to_string(), sha256.to_lowercase()))
+        })
+        .collect()
+}
+
+/// Get the target triples of builds that run on the host, from most to least
+/// preferred.
+pub(crate) fn host_targets() -> Vec<String> {
+    let arch = match std::env::consts::ARCH {
+        "x86" => "i686",
+        arch => arch,
+    };
+    #[cfg(target_os = "linux")]
+    let targets = match crate::tags::detect_libc() {
+        Some(crate::tags::Libc::Musl(..)) => vec![format!("{arch}-unknown-linux-musl")],
+        _ => vec![format!("{arch}-unknown-linux-gnu")],
+    };
+    #[cfg(target_os = "macos")]
+    let targets = vec![format!("{arch}-apple-darwin")];
+    #[cfg(windows)]
+    let targets = vec![
+        format!("{arch}-pc-windows-msvc"),
+        format!("{arch}-pc-windows-msvc-shared"),
+    ];
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    let targets = Vec::new();
+    targets
+}
+
+/// Get huak's data directory.
+fn default_data_dir() -> PathBuf {
+    if let Some(path) = std::env::var_os(DATA_DIR_ENV_VAR).filter(|it| !it.is_empty()) {
+        return PathBuf::from(path);
+    }
+    #[cfg(target_os = "macos")]
+    let dir = sys::home_dir().map(|it| it.join("Library").join("Application Support"));
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let dir = std::env::var_os("XDG_DATA_HOME")
+        .filter(|it| !it.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| sys::home_dir().map(|it| it.join(".local").join("share")));
+    #[cfg(windows)]
+    let dir = std::env::var_os("APPDATA").map(PathBuf::from);
+    dir.unwrap_or_else(std::env::temp_dir).join("huak")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cache;
+    use sha2::{Digest, Sha256};
+    use tempfile::tempdir;
+
+    /// Write a fake standalone build archive with an interpreter printing its
+    /// arguments, and get its sha256 checksum.
+    fn write_test_archive(path: &Path) -> String {
+        let file = std::fs::File::create(path).unwrap();
+        let mut builder =
+            tar::Builder::new(flate2::write::GzEncoder::new(file, Default::default()));
+        #[cfg(unix)]
+        let (name, contents) = ("python/bin/python3", "#!/bin/sh\necho \"$@\"\n");
+        #[cfg(windows)]
+        let (name, contents) = ("python/python.exe", "");
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, name, contents.as_bytes())
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+        format!("{:x}", Sha256::digest(std::fs::read(path).unwrap()))
+    }
+
+    #[test]
+    fn standalone_archive_names() {
+        assert_eq!(
+            parse_archive_name(
+                "cpython-3.11.4+20230726-x86_64-unknown-linux-gnu-install_only.tar.gz"
+            ),
+            Some((
+                Version::from_str("3.11.4").unwrap(),
+                "x86_64-unknown-linux-gnu".to_string(),
+                0
+            ))
+        );
+        assert_eq!(
+            parse_archive_name(
+                "cpython-3.12.1+20240107-aarch64-apple-darwin-install_only_stripped.tar.gz"
+            )
+            .map(|it| it.2),
+            Some(1)
+        );
+        assert_eq!(
+            parse_archive_name("cpython-3.11.4+20230726-x86_64-unknown-linux-gnu-pgo-full.tar.zst"),
+            None
+        );
+        assert_eq!(
+            parse_checksums("ABC  cpython-3.11.4.tar.gz\ndef *other.tar.gz\n"),
+            BTreeMap::from([
+                ("cpython-3.11.4.tar.gz".to_string(), "abc".to_string()),
+                ("other.tar.gz".to_string(), "def".to_string())
+            ])
+        );
+    }
+
+    #[test]
+    fn managed_python_install() {
+        let dir = tempdir().unwrap().into_path();
+        let archives = dir.join("archives");
+        std::fs::create_dir_all(&archives).unwrap();
+        let target = host_targets().remove(0);
+        let mut checksums = String::new();
+        for version in ["3.10.12", "3.11.4", "3.12.1"] {
+            let name = format!("cpython-{version}+20230726-{target}-install_only.tar.gz");
+            let sha256 = write_test_archive(&archives.join(&name));
+            checksums.push_str(&format!("{sha256}  {name}\n"));
+        }
+        std::fs::write(archives.join(CHECKSUMS_FILE_NAME), checksums).unwrap();
+        let pythons = ManagedPythons::from_path(dir.join("pythons"));
+        let source = PythonSource::Directory(archives.clone());
+        let mut client = PackageIndexClient::new();
+        client.with_cache(Cache::from_path(dir.join("cache")));
+        client.with_offline(true);
+
+        let python = pythons
+            .install(&PythonRequest::from_str("3.11").unwrap(), &source, &client)
+            .unwrap();
+        let installed = pythons
+            .install(
+                &PythonRequest::from_str(">=3.10").unwrap(),
+                &source,
+                &client,
+            )
+            .unwrap();
+        let latest = pythons
+            .install(
+                &PythonRequest::from_str(">=3.12").unwrap(),
+                &source,
+                &client,
+            )
+            .unwrap();
+
+        assert_eq!(python.version(), &Version::from_str("3.11.4").unwrap());
+        assert_eq!(installed, python);
+        assert_eq!(latest.version(), &Version::from_str("3.12.1").unwrap());
+        assert!(python.python_path().exists());
+        assert_eq!(pythons.installed(), [python.clone(), latest.clone()]);
+        assert!(matches!(
+            pythons.install(&PythonRequest::from_str("3.9").unwrap(), &source, &client),
+            Err(HuakError::PythonVersionNotFoundError(_))
+        ));
+
+        assert_eq!(
+            pythons
+                .uninstall(&PythonRequest::from_str("3").unwrap())
+                .unwrap(),
+            latest
+        );
+        assert_eq!(pythons.installed(), [python]);
+
+        let name = format!("cpython-3.13.0+20241008-{target}-install_only.tar.gz");
+        write_test_archive(&archives.join(&name));
+        std::fs::write(archives.join(format!("{name}.sha256")), "0".repeat(64)).unwrap();
+        assert!(matches!(
+            pythons.install(&PythonRequest::from_str("3.13").unwrap(), &source, &client),
+            Err(HuakError::HashMismatchError(..))
+        ));
+        assert!(!pythons
+            .dir()
+            .join(format!("cpython-3.13.0-{target}"))
+            .exists());
+    }
+}
@@ -1,7 +1,13 @@
-use crate::error::HuakResult;
+use crate::{
+    error::{HuakError, HuakResult},
+    Event, ManagedPython, ManagedPythons, PackageIndexClient, Progress, PythonImplementation,
+    PythonRequest, PythonSource,
+};
 use pep440_rs::Version;
-use std::io::Write;
-use std::process::Command;
+use std::io::{IsTerminal, Read, Write};
+use std::process::{Command, ExitStatus, Stdio};
+use std::str::FromStr;
+use std::sync::{mpsc, Arc, Mutex};
 use std::{collections::HashMap, ffi::OsString, path::PathBuf};
 use termcolor::{self, Color, ColorSpec, StandardStream, WriteColor};
 use termcolor::{
@@ -14,36 +20,154 @@ pub struct Platform {
     /// The name of the platform.
     name: String,
     /// Absolute paths to each Python interpreter installed.
-    python_paths: HashMap<Version, PathBuf>,
+    python_paths: HashMap<(PythonImplementation, Version), PathBuf>,
+    /// The standalone Python builds installed by huak.
+    managed_pythons: ManagedPythons,
+    /// Where standalone Python builds are installed from.
+    python_source: PythonSource,
+    /// Only use downloads that are already cached.
+    offline: bool,
     /// An abstraction for the terminal.
     terminal: Terminal,
 }
 
 impl Platform {
-    /// Create a new platform.
+    /// Create a new platform with the Python interpreters found on the system and the
+    /// standalone builds installed by huak.
     pub fn new() -> Platform {
-        todo!()
+        let mut platform = Platform {
+            name: std::env::consts::OS.to_string(),
+            python_paths: crate::find_python_interpreter_paths(),
+            managed_pythons: ManagedPythons::new(),
+            python_source: PythonSource::from_env(),
+            offline: false,
+            terminal: Terminal::new(),
+        };
+        platform.register_managed_pythons();
+        platform
+    }
+
+    /// Use the standalone builds installed to a directory.
+    pub fn with_managed_pythons(&mut self, managed_pythons: ManagedPythons) -> &mut Platform {
+        self.managed_pythons = managed_pythons;
+        self.register_managed_pythons();
+        self
+    }
+
+    /// Install standalone builds from a source.
+    pub fn with_python_source(&mut self, python_source: PythonSource) -> &mut Platform {
+        self.python_source = python_source;
+        self
     }
 
-    /// Install a Python interpreter.
+    /// Only install standalone builds from the cache or a local source.
+    pub fn with_offline(&mut self, offline: bool) -> &mut Platform {
+        self.offline = offline;
+        self
+    }
+
+    /// Get the name of the platform.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Install a Python interpreter. The latest standalone build of CPython satisfying
+    /// a version request such as `3.11` or `>=3.10` is installed, unless one already
+    /// is.
     pub fn install_python(&mut self, version_str: &str) -> HuakResult<()> {
-        todo!()
+        let request = PythonRequest::from_str(version_str)?;
+        let mut client = PackageIndexClient::new();
+        client
+            .with_offline(self.offline)
+            .with_progress(self.terminal.progress());
+        let python = self
+            .managed_pythons
+            .install(&request, &self.python_source, &client)?;
+        self.python_paths.insert(
+            (PythonImplementation::CPython, python.version().clone()),
+            python.python_path(),
+        );
+        self.terminal.status(
+            "Installed",
+            format!(
+                "CPython {} at {}",
+                python.version(),
+                python.root().display()
+            ),
+        )
+    }
+
+    /// Uninstall the latest standalone build satisfying a version request.
+    pub fn uninstall_python(&mut self, version_str: &str) -> HuakResult<()> {
+        let request = PythonRequest::from_str(version_str)?;
+        let python = self.managed_pythons.uninstall(&request)?;
+        self.python_paths
+            .retain(|_, path| *path != python.python_path());
+        self.terminal
+            .status("Uninstalled", format!("CPython {}", python.version()))
+    }
+
+    /// Get the standalone builds installed by huak ordered by version.
+    pub fn managed_pythons(&self) -> Vec<ManagedPython> {
+        self.managed_pythons.installed()
     }
 
     /// Get the absolute path to a specific Python interpreter with a version &str.
+    /// The latest interpreter satisfying the version request is used.
     pub fn python_path(&self, version_str: &str) -> Option<&PathBuf> {
-        todo!()
+        self.python_path_matching(&PythonRequest::from_str(version_str).ok()?)
+    }
+
+    /// Get the absolute path to the latest Python interpreter satisfying a request.
+    /// CPython is preferred over other implementations of the same version.
+    pub fn python_path_matching(&self, request: &PythonRequest) -> Option<&PathBuf> {
+        self.python_paths
+            .iter()
+            .filter(|((_, version), _)| request.contains(version))
+            .max_by_key(|((implementation, version), _)| {
+                (version, *implementation == PythonImplementation::CPython)
+            })
+            .map(|(_, path)| path)
     }
 
     /// Get the absolute path to the latest version Python interpreter installed.
+    /// CPython is preferred over other implementations of the same version.
     pub fn python_path_latest(&self) -> Option<&PathBuf> {
-        todo!()
+        self.python_paths
+            .iter()
+            .max_by_key(|((implementation, version), _)| {
+                (version, *implementation == PythonImplementation::CPython)
+            })
+            .map(|(_, path)| path)
+    }
+
+    /// Get the absolute paths to each Python interpreter installed by implementation
+    /// and version.
+    pub fn python_paths(&self) -> &HashMap<(PythonImplementation, Version), PathBuf> {
+        &self.python_paths
     }
 
     /// Get a reference to the platform's terminal.
     pub fn terminal(&self) -> &Terminal {
         &self.terminal
     }
+
+    /// Add the standalone builds to the interpreters. They're preferred over other
+    /// interpreters with the same version.
+    fn register_managed_pythons(&mut self) {
+        for python in self.managed_pythons.installed() {
+            self.python_paths.insert(
+                (PythonImplementation::CPython, python.version().clone()),
+                python.python_path(),
+            );
+        }
+    }
+}
+
+impl Default for Platform {
+    fn default() -> Self {
+        Platform::new()
+    }
 }
 
 /// Get a vector of paths from the system PATH environment variable.
@@ -58,6 +182,29 @@ pub fn env_path_string() -> OsString {
     }
 }
 
+/// Get the user's home directory.
+pub fn home_dir() -> Option<PathBuf> {
+    #[cfg(unix)]
+    let var = "HOME";
+    #[cfg(windows)]
+    let var = "USERPROFILE";
+    std::env::var_os(var)
+        .filter(|it| !it.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Get the path to the user's shell. Falls back to the platform's default shell.
+pub fn shell_path() -> PathBuf {
+    #[cfg(unix)]
+    let (var, default) = ("SHELL", "sh");
+    #[cfg(windows)]
+    let (var, default) = ("COMSPEC", "cmd.exe");
+    match std::env::var_os(var) {
+        Some(val) if !val.is_empty() => PathBuf::from(val),
+        _ => PathBuf::from(default),
+    }
+}
+
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
 pub enum Verbosity {
     #[default]
@@ -66,6 +213,16 @@ pub enum Verbosity {
     Quiet,
 }
 
+/// How a terminal formats its output.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    /// Colored, human-readable messages on stderr.
+    #[default]
+    Human,
+    /// Newline-delimited JSON events on stdout.
+    Json,
+}
+
 /// An abstraction around terminal output that remembers preferences for output
 /// verbosity and color (inspired by cargo's implementation).
 pub struct Terminal {
@@ -73,18 +230,37 @@ pub struct Terminal {
     output: TerminalOut,
     /// How verbose messages should be.
     verbosity: Verbosity,
+    /// How output is formatted.
+    format: OutputFormat,
+    /// The progress display shared by everything reporting progress to the terminal.
+    progress: Progress,
 }
 
 impl Terminal {
     /// Create a new terminal struct with maximum verbosity.
     pub fn new() -> Terminal {
-        Terminal {
+        let mut terminal = Terminal {
             verbosity: Verbosity::Verbose,
+            format: OutputFormat::Human,
             output: TerminalOut::Stream {
                 stdout: StandardStream::stdout(ColorChoice::Auto),
                 stderr: StandardStream::stderr(ColorChoice::Auto),
                 color_choice: ColorChoice::Auto,
             },
+            progress: Progress::hidden(),
+        };
+        terminal.progress = terminal.new_progress();
+        terminal
+    }
+
+    /// Create a terminal with maximum verbosity writing all of its output, including
+    /// the output of the commands it runs, to a writer without color.
+    pub fn from_write(write: Box<dyn Write>) -> Terminal {
+        Terminal {
+            verbosity: Verbosity::Verbose,
+            format: OutputFormat::Human,
+            output: TerminalOut::Write(write),
+            progress: Progress::hidden(),
         }
     }
 
@@ -94,14 +270,20 @@ impl Terminal {
         T: std::fmt::Display,
         U: std::fmt::Display,
     {
-        self.print(&status, Some(&message), Green, true)
+        self.status_with_color(status, message, Green)
     }
 
     pub fn status_header<T>(&mut self, status: T) -> HuakResult<()>
     where
         T: std::fmt::Display,
     {
-        self.print(&status, None, Cyan, true)
+        match self.format {
+            OutputFormat::Human => self.print(&status, None, Cyan, true),
+            OutputFormat::Json => self.print_event(Event::Status {
+                status: status.to_string(),
+                message: None,
+            }),
+        }
     }
 
     /// Shortcut to right-align a status message.
@@ -110,26 +292,104 @@ impl Terminal {
         T: std::fmt::Display,
         U: std::fmt::Display,
     {
-        self.print(&status, Some(&message), color, true)
+        match self.format {
+            OutputFormat::Human => self.print(&status, Some(&message), color, true),
+            OutputFormat::Json => self.print_event(Event::Status {
+                status: status.to_string(),
+                message: Some(message.to_string()),
+            }),
+        }
     }
 
     /// Print an error message.
     pub fn print_error<T: std::fmt::Display>(&mut self, message: T) -> HuakResult<()> {
-        self.output
-            .message_stderr(&"error", Some(&message), Red, false)
+        match self.format {
+            OutputFormat::Human => self.message_stderr(&"error", Some(&message), Red, false),
+            OutputFormat::Json => self.write_event(&Event::Error {
+                message: message.to_string(),
+            }),
+        }
     }
 
     /// Prints a warning message.
     pub fn print_warning<T: std::fmt::Display>(&mut self, message: T) -> HuakResult<()> {
-        match self.verbosity {
-            Verbosity::Quiet => Ok(()),
-            _ => self.print(&"warning", Some(&message), Yellow, false),
+        match (self.verbosity, self.format) {
+            (Verbosity::Quiet, _) => Ok(()),
+            (_, OutputFormat::Human) => self.print(&"warning", Some(&message), Yellow, false),
+            (_, OutputFormat::Json) => self.print_event(Event::Warning {
+                message: message.to_string(),
+            }),
         }
     }
 
     /// Prints a note message.
     pub fn print_note<T: std::fmt::Display>(&mut self, message: T) -> HuakResult<()> {
-        self.print(&"note", Some(&message), Cyan, false)
+        match self.format {
+            OutputFormat::Human => self.print(&"note", Some(&message), Cyan, false),
+            OutputFormat::Json => self.print_event(Event::Note {
+                message: message.to_string(),
+            }),
+        }
+    }
+
+    /// Emit the result of an operation. Terminals using the JSON output format write
+    /// the event regardless of their verbosity, while human-readable terminals print
+    /// it like any other message.
+    pub fn emit(&mut self, event: Event) -> HuakResult<()> {
+        if self.format == OutputFormat::Json {
+            return self.write_event(&event);
+        }
+        match event {
+            Event::Status {
+                status,
+                message: Some(message),
+            } => self.status(status, message),
+            Event::Status {
+                status,
+                message: None,
+            } => self.status_header(status),
+            Event::Note { message } => self.print_note(message),
+            Event::Warning { message } => self.print_warning(message),
+            Event::Error { message } => self.print_error(message),
+            Event::PackageInstalled { name, version } => match version {
+                Some(version) => self.status("Installed", format!("{name} {version}")),
+                None => self.status("Installed", name),
+            },
+            Event::PackageUninstalled { name, version } => match version {
+                Some(version) => self.status("Uninstalled", format!("{name} {version}")),
+                None => self.status("Uninstalled", name),
+            },
+            Event::PackageResolved { name, version, .. } => {
+                self.status("Resolved", format!("{name} {version}"))
+            }
+            Event::ProjectBuilt { path } => self.status("Built", path),
+            Event::ProjectVersion { version } => {
+                writeln!(self.output.stdout(), "{version}")?;
+                Ok(())
+            }
+            Event::CommandOutput { line } => {
+                writeln!(self.output.stdout(), "{line}")?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Write a message event unless the terminal is quiet.
+    fn print_event(&mut self, event: Event) -> HuakResult<()> {
+        match self.verbosity {
+            Verbosity::Quiet => Ok(()),
+            _ => self.write_event(&event),
+        }
+    }
+
+    /// Write an event as a line of JSON to stdout.
+    fn write_event(&mut self, event: &Event) -> HuakResult<()> {
+        // Credentials must never reach the terminal.
+        let line = crate::auth::redact(&event.to_json());
+        let stdout = self.output.stdout();
+        writeln!(stdout, "{line}")?;
+        stdout.flush()?;
+        Ok(())
     }
 
     /// Prints a message, where the status will have `color` color, and can be justified.
@@ -145,12 +405,24 @@ impl Terminal {
     ) -> HuakResult<()> {
         match self.verbosity {
             Verbosity::Quiet => Ok(()),
-            _ => self
-                .output
-                .message_stderr(status, message, color, justified),
+            _ => self.message_stderr(status, message, color, justified),
         }
     }
 
+    /// Write a message to stderr with the progress display suspended, so the message
+    /// isn't drawn over its bars.
+    fn message_stderr(
+        &mut self,
+        status: &dyn std::fmt::Display,
+        message: Option<&dyn std::fmt::Display>,
+        color: Color,
+        justified: bool,
+    ) -> HuakResult<()> {
+        let output = &mut self.output;
+        self.progress
+            .suspend(|| output.message_stderr(status, message, color, justified))
+    }
+
     /// Gets a reference to the underlying stdout writer.
     pub fn stdout(&mut self) -> &mut dyn Write {
         self.output.stdout()
@@ -164,6 +436,7 @@ impl Terminal {
     /// Set the verbosity level.
     pub fn set_verbosity(&mut self, verbosity: Verbosity) {
         self.verbosity = verbosity;
+        self.progress = self.new_progress();
     }
 
     /// Get a reference to the verbosity level.
@@ -171,6 +444,60 @@ impl Terminal {
         &self.verbosity
     }
 
+    /// Set the output format.
+    pub fn set_output_format(&mut self, format: OutputFormat) {
+        self.format = format;
+        self.progress = self.new_progress();
+    }
+
+    /// Get the output format.
+    pub fn output_format(&self) -> OutputFormat {
+        self.format
+    }
+
+    /// Set whether color is used. Only terminals writing to stdio use color.
+    pub fn set_color_choice(&mut self, color_choice: ColorChoice) {
+        if let TerminalOut::Stream { .. } = self.output {
+            self.output = TerminalOut::Stream {
+                stdout: StandardStream::stdout(color_choice),
+                stderr: StandardStream::stderr(color_choice),
+                color_choice,
+            };
+            self.progress = self.new_progress();
+        }
+    }
+
+    /// Get the terminal's progress display for long-running work. Every call returns
+    /// the same display, so work reporting progress at the same time shares its bars,
+    /// and the terminal's messages are printed above them.
+    pub fn progress(&self) -> Progress {
+        self.progress.clone()
+    }
+
+    /// Create a progress display drawn to stderr, redrawing bars in place when stderr
+    /// is an interactive terminal using color and as plain lines otherwise. It's
+    /// hidden if the terminal is quiet, emits JSON events or writes to a writer.
+    fn new_progress(&self) -> Progress {
+        if self.verbosity == Verbosity::Quiet || self.format == OutputFormat::Json {
+            return Progress::hidden();
+        }
+        match self.output {
+            TerminalOut::Write(_) => Progress::hidden(),
+            TerminalOut::Stream {
+                ref stderr,
+                color_choice,
+                ..
+            } => {
+                let stderr_tty = std::io::stderr().is_terminal();
+                if stderr_tty && color_choice != ColorChoice::Never && stderr.supports_color() {
+                    Progress::live(Box::new(std::io::stderr()))
+                } else {
+                    Progress::plain(Box::new(std::io::stderr()))
+                }
+            }
+        }
+    }
+
     /// Gets the current color choice.
     ///
     /// If we are not using a color stream, this will always return `Never`, even if the color
@@ -182,9 +509,216 @@ impl Terminal {
         }
     }
 
-    /// Run a command from the terminal's context.
+    /// Run a command from the terminal's context. The command's output is streamed
+    /// to the terminal as it's written. Terminals writing to stdio let the command
+    /// inherit it, unless they're quiet, in which case the output is only shown if
+    /// the command fails. Terminals writing to a writer capture the output into it.
+    /// Terminals emitting JSON events send the command's stdout to stderr instead.
+    /// Commands exiting unsuccessfully are reported with their exit code.
     pub fn run_command(&mut self, cmd: &mut Command) -> HuakResult<()> {
-        todo!()
+        let program = cmd.get_program().to_string_lossy().into_owned();
+        let capture =
+            matches!(self.output, TerminalOut::Write(_)) || self.verbosity == Verbosity::Quiet;
+        if capture {
+            cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        } else if self.format == OutputFormat::Json {
+            cmd.stdout(std::io::stderr()).stderr(Stdio::inherit());
+        } else {
+            cmd.stdout(Stdio::inherit()).stderr(Stdio::inherit());
+        }
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| HuakError::CommandError(format!("failed to run `{program}`: {e}")))?;
+        let guard = signals::ChildSignalGuard::new(child.id());
+
+        // Quiet output is held back until the command's status is known.
+        let mut held_output = Vec::new();
+        // Output written with events is split into lines so each is an event.
+        let mut event_output = Vec::new();
+        if capture {
+            let (sender, receiver) = mpsc::channel();
+            let mut readers = Vec::new();
+            let stdout = child
+                .stdout
+                .take()
+                .map(|it| Box::new(it) as Box<dyn Read + Send>);
+            let stderr = child
+                .stderr
+                .take()
+                .map(|it| Box::new(it) as Box<dyn Read + Send>);
+            for mut reader in stdout.into_iter().chain(stderr) {
+                let sender = sender.clone();
+                readers.push(std::thread::spawn(move || {
+                    let mut buffer = [0; 8192];
+                    while let Ok(n) = reader.read(&mut buffer) {
+                        if n == 0 || sender.send(buffer[..n].to_vec()).is_err() {
+                            break;
+                        }
+                    }
+                }));
+            }
+            drop(sender);
+            for chunk in receiver {
+                match self.output {
+                    TerminalOut::Write(_) if self.format == OutputFormat::Json => {
+                        event_output.extend(chunk);
+                        while let Some(i) = event_output.iter().position(|it| *it == b'\n') {
+                            let line = event_output.drain(..=i).collect::<Vec<_>>();
+                            self.write_event(&Event::command_output(&line))?;
+                        }
+                    }
+                    TerminalOut::Write(ref mut w) => {
+                        w.write_all(&chunk)?;
+                        w.flush()?;
+                    }
+                    TerminalOut::Stream { .. } => held_output.extend(chunk),
+                }
+            }
+            for reader in readers {
+                reader.join().ok();
+            }
+            if !event_output.is_empty() {
+                self.write_event(&Event::command_output(&event_output))?;
+            }
+        }
+
+        let status = child.wait()?;
+        drop(guard);
+        if status.success() {
+            return Ok(());
+        }
+        self.output.stderr().write_all(&held_output)?;
+        Err(HuakError::CommandExitError(program, exit_code(status)))
+    }
+}
+
+/// Get the code a process exited with. Processes terminated by a signal are given
+/// the shell's code of 128 plus the signal's number.
+fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+        return 128 + signal;
+    }
+    status.code().unwrap_or(1)
+}
+
+/// Handling of the signals huak receives while it waits on a child process.
+mod signals {
+    /// Keeps Ctrl-C from terminating huak while a child process runs so the child can
+    /// handle it, and forwards termination signals huak receives to the child. The
+    /// previous handlers are restored when the guard is dropped.
+    ///
+    /// The terminal sends Ctrl-C to every process in its foreground process group (or
+    /// attached to its console on Windows), so the child already receives it and it
+    /// isn't forwarded. Handlers rather than ignored dispositions are used since
+    /// ignored signals would be inherited by the child.
+    pub(super) struct ChildSignalGuard {
+        #[cfg(unix)]
+        previous: Vec<(libc::c_int, libc::sighandler_t)>,
+    }
+
+    #[cfg(unix)]
+    static CHILD_PID: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(0);
+
+    #[cfg(unix)]
+    extern "C" fn handle_signal(signal: libc::c_int) {
+        let pid = CHILD_PID.load(std::sync::atomic::Ordering::SeqCst);
+        if signal != libc::SIGINT && pid > 0 {
+            // SAFETY: kill is async-signal-safe.
+            unsafe {
+                libc::kill(pid, signal);
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    impl ChildSignalGuard {
+        pub(super) fn new(pid: u32) -> ChildSignalGuard {
+            CHILD_PID.store(pid as i32, std::sync::atomic::Ordering::SeqCst);
+            let handler = handle_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            let previous = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP]
+                .into_iter()
+                // SAFETY: The handler only reads an atomic and calls kill.
+                .map(|signal| (signal, unsafe { libc::signal(signal, handler) }))
+                .collect();
+            ChildSignalGuard { previous }
+        }
+    }
+
+    #[cfg(unix)]
+    impl Drop for ChildSignalGuard {
+        fn drop(&mut self) {
+            for (signal, handler) in self.previous.iter() {
+                // SAFETY: Restores the handler that was installed before the guard.
+                unsafe {
+                    libc::signal(*signal, *handler);
+                }
+            }
+            CHILD_PID.store(0, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    #[cfg(windows)]
+    extern "system" {
+        fn SetConsoleCtrlHandler(
+            handler: Option<unsafe extern "system" fn(u32) -> i32>,
+            add: i32,
+        ) -> i32;
+    }
+
+    /// Mark console control events as handled so only the child acts on them.
+    #[cfg(windows)]
+    unsafe extern "system" fn handle_ctrl(_ctrl_type: u32) -> i32 {
+        1
+    }
+
+    #[cfg(windows)]
+    impl ChildSignalGuard {
+        pub(super) fn new(_pid: u32) -> ChildSignalGuard {
+            // SAFETY: Registers a handler that doesn't touch any state.
+            unsafe {
+                SetConsoleCtrlHandler(Some(handle_ctrl), 1);
+            }
+            ChildSignalGuard {}
+        }
+    }
+
+    #[cfg(windows)]
+    impl Drop for ChildSignalGuard {
+        fn drop(&mut self) {
+            // SAFETY: Removes the handler registered by the guard.
+            unsafe {
+                SetConsoleCtrlHandler(Some(handle_ctrl), 0);
+            }
+        }
+    }
+}
+
+/// A buffer that terminal output can be captured in and read back from.
+#[derive(Clone, Default)]
+pub struct CapturedOutput(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedOutput {
+    pub fn new() -> CapturedOutput {
+        CapturedOutput::default()
+    }
+
+    /// Get the output captured so far.
+    pub fn contents(&self) -> String {
+        let buffer = self.0.lock().unwrap_or_else(|it| it.into_inner());
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+}
+
+impl Write for CapturedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut buffer = self.0.lock().unwrap_or_else(|it| it.into_inner());
+        buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
     }
 }
 
@@ -217,6 +751,10 @@ impl TerminalOut {
         color: Color,
         justified: bool,
     ) -> HuakResult<()> {
+        // Credentials must never reach the terminal.
+        let status = &crate::auth::redact(&status.to_string());
+        let message = message.map(|it| crate::auth::redact(&it.to_string()));
+        let message = message.as_ref().map(|it| it as &dyn std::fmt::Display);
         match *self {
             TerminalOut::Write(ref mut w) => {
                 if justified {
@@ -271,9 +809,99 @@ mod tests {
     use super::*;
 
     #[test]
+    /// NOTE: This test depends on a Python interpreter being available on the PATH.
     fn platform_terminal_command() {
         let platform = Platform::new();
+        let python_path = platform.python_path_latest().unwrap();
+        let output = CapturedOutput::new();
+        let mut terminal = Terminal::from_write(Box::new(output.clone()));
+
+        terminal
+            .run_command(Command::new(python_path).args(["-c", "print('hello')"]))
+            .unwrap();
+        let error = terminal
+            .run_command(Command::new(python_path).args([
+                "-c",
+                "import sys; print('failing', file=sys.stderr); sys.exit(3)",
+            ]))
+            .unwrap_err();
+
+        assert_eq!(
+            output.contents().lines().collect::<Vec<_>>(),
+            ["hello", "failing"]
+        );
+        assert!(matches!(error, HuakError::CommandExitError(_, 3)));
+    }
 
-        todo!()
+    #[test]
+    fn platform_python_paths_by_implementation() {
+        let mut platform = Platform::new();
+        let version = Version::from_str("3.10.12").unwrap();
+        platform.python_paths = HashMap::from([
+            (
+                (PythonImplementation::PyPy, version.clone()),
+                PathBuf::from("pypy3.10"),
+            ),
+            (
+                (PythonImplementation::CPython, version),
+                PathBuf::from("python3.10"),
+            ),
+        ]);
+
+        assert_eq!(platform.python_paths().len(), 2);
+        assert_eq!(
+            platform.python_path("3.10"),
+            Some(&PathBuf::from("python3.10"))
+        );
+        assert_eq!(
+            platform.python_path_latest(),
+            Some(&PathBuf::from("python3.10"))
+        );
+    }
+
+    #[test]
+    fn terminal_json_events() {
+        let output = CapturedOutput::new();
+        let mut terminal = Terminal::from_write(Box::new(output.clone()));
+        terminal.set_output_format(OutputFormat::Json);
+
+        terminal.status("Built", "dist/demo-0.1.0.tar.gz").unwrap();
+        terminal.print_warning("no lock file").unwrap();
+        terminal.set_verbosity(Verbosity::Quiet);
+        terminal.print_note("skipped").unwrap();
+        terminal
+            .emit(Event::ProjectVersion {
+                version: "0.1.0".to_string(),
+            })
+            .unwrap();
+        terminal.print_error("failed").unwrap();
+
+        assert_eq!(
+            output.contents().lines().collect::<Vec<_>>(),
+            [
+                r#"{"event":"status","status":"Built","message":"dist/demo-0.1.0.tar.gz"}"#,
+                r#"{"event":"warning","message":"no lock file"}"#,
+                r#"{"event":"project-version","version":"0.1.0"}"#,
+                r#"{"event":"error","message":"failed"}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn terminal_json_command_output() {
+        let platform = Platform::new();
+        let python_path = platform.python_path_latest().unwrap();
+        let output = CapturedOutput::new();
+        let mut terminal = Terminal::from_write(Box::new(output.clone()));
+        terminal.set_output_format(OutputFormat::Json);
+
+        terminal
+            .run_command(Command::new(python_path).args(["-c", "print('hello')"]))
+            .unwrap();
+
+        assert_eq!(
+            output.contents().lines().collect::<Vec<_>>(),
+            [r#"{"event":"command-output","line":"hello"}"#]
+        );
     }
 }
@@ -0,0 +1,811 @@
+///! This module implements platform compatibility tags, used to decide which wheels
+///! can be installed to an environment.
+///! See https://packaging.python.org/en/latest/specifications/platform-compatibility-tags/
+///!
+///! An environment supports an ordered list of tags, most specific first. A wheel is
+///! compatible if any of its tags is supported, and the wheel whose best tag comes
+///! earliest in the list is the best wheel for the environment.
+use crate::{
+    error::{HuakError, HuakResult},
+    MarkerEnvironment,
+};
+use pep440_rs::Version;
+use std::{collections::HashMap, fmt::Display, process::Command, str::FromStr};
+
+/// The oldest glibc minor versions manylinux tags are generated down to. Only x86
+/// wheels were built for glibc versions older than 2.17.
+const MANYLINUX_X86_OLDEST_MINOR: u16 = 5;
+const MANYLINUX_OLDEST_MINOR: u16 = 17;
+
+/// The oldest macOS 10 minor version macosx tags are generated down to.
+const MACOS_10_OLDEST_MINOR: u16 = 4;
+
+/// The platform a wheel can be installed on.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum PlatformTag {
+    /// Any platform.
+    Any,
+    /// Linux with at least a glibc version (PEP 600).
+    Manylinux {
+        major: u16,
+        minor: u16,
+        arch: String,
+    },
+    /// The legacy alias of `manylinux_2_5` (PEP 513).
+    Manylinux1(String),
+    /// The legacy alias of `manylinux_2_12` (PEP 571).
+    Manylinux2010(String),
+    /// The legacy alias of `manylinux_2_17` (PEP 599).
+    Manylinux2014(String),
+    /// Linux with at least a musl version (PEP 656).
+    Musllinux {
+        major: u16,
+        minor: u16,
+        arch: String,
+    },
+    /// The Linux distribution a wheel was built on.
+    Linux(String),
+    /// macOS at least at a version, for a binary format such as `arm64` or
+    /// `universal2`.
+    Macos {
+        major: u16,
+        minor: u16,
+        binary_format: String,
+    },
+    Win32,
+    WinAmd64,
+    WinArm64,
+    /// Any other platform.
+    Other(String),
+}
+
+impl FromStr for PlatformTag {
+    type Err = HuakError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || s.contains('-') {
+            return Err(HuakError::WheelFilenameError(format!(
+                "invalid platform tag {s:?}"
+            )));
+        }
+        // Versioned tags are `<prefix>_<major>_<minor>_<arch>`.
+        let versioned = |prefix: &str| {
+            let mut parts = s.strip_prefix(prefix)?.splitn(3, '_');
+            let major = parts.next()?.parse().ok()?;
+            let minor = parts.next()?.parse().ok()?;
+            Some((major, minor, parts.next()?.to_string()))
+        };
+        let tag = match s {
+            "any" => PlatformTag::Any,
+            "win32" => PlatformTag::Win32,
+            "win_amd64" => PlatformTag::WinAmd64,
+            "win_arm64" => PlatformTag::WinArm64,
+            _ => {
+                if let Some(arch) = s.strip_prefix("manylinux1_") {
+                    PlatformTag::Manylinux1(arch.to_string())
+                } else if let Some(arch) = s.strip_prefix("manylinux2010_") {
+                    PlatformTag::Manylinux2010(arch.to_string())
+                } else if let Some(arch) = s.strip_prefix("manylinux2014_") {
+                    PlatformTag::Manylinux2014(arch.to_string())
+                } else if let Some((major, minor, arch)) = versioned("manylinux_") {
+                    PlatformTag::Manylinux { major, minor, arch }
+                } else if let Some((major, minor, arch)) = versioned("musllinux_") {
+                    PlatformTag::Musllinux { major, minor, arch }
+                } else if let Some((major, minor, binary_format)) = versioned("macosx_") {
+                    PlatformTag::Macos {
+                        major,
+                        minor,
+                        binary_format,
+                    }
+                } else if let Some(arch) = s.strip_prefix("linux_") {
+                    PlatformTag::Linux(arch.to_string())
+                } else {
+                    PlatformTag::Other(s.to_string())
+                }
+            }
+        };
+        Ok(tag)
+    }
+}
+
+impl Display for PlatformTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlatformTag::Any => write!(f, "any"),
+            PlatformTag::Manylinux { major, minor, arch } => {
+                write!(f, "manylinux_{major}_{minor}_{arch}")
+            }
+            PlatformTag::Manylinux1(arch) => write!(f, "manylinux1_{arch}"),
+            PlatformTag::Manylinux2010(arch) => write!(f, "manylinux2010_{arch}"),
+            PlatformTag::Manylinux2014(arch) => write!(f, "manylinux2014_{arch}"),
+            PlatformTag::Musllinux { major, minor, arch } => {
+                write!(f, "musllinux_{major}_{minor}_{arch}")
+            }
+            PlatformTag::Linux(arch) => write!(f, "linux_{arch}"),
+            PlatformTag::Macos {
+                major,
+                minor,
+                binary_format,
+            } => write!(f, "macosx_{major}_{minor}_{binary_format}"),
+            PlatformTag::Win32 => write!(f, "win32"),
+            PlatformTag::WinAmd64 => write!(f, "win_amd64"),
+            PlatformTag::WinArm64 => write!(f, "win_arm64"),
+            PlatformTag::Other(it) => write!(f, "{it}"),
+        }
+    }
+}
+
+/// A compatibility tag such as `cp311-cp311-manylinux_2_17_x86_64` made of the
+/// Python implementation and version, the ABI, and the platform a wheel supports.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct CompatibilityTag {
+    pub python: String,
+    pub abi: String,
+    pub platform: PlatformTag,
+}
+
+impl CompatibilityTag {
+    pub fn new(python: &str, abi: &str, platform: PlatformTag) -> CompatibilityTag {
+        CompatibilityTag {
+            python: python.to_string(),
+            abi: abi.to_string(),
+            platform,
+        }
+    }
+
+    /// Expand a compressed tag set such as `py2.py3-none-any` into each tag it
+    /// stands for.
+    pub fn expand(s: &str) -> HuakResult<Vec<CompatibilityTag>> {
+        let parts = s.split('-').collect::<Vec<_>>();
+        let (python, abi, platform) = match parts.as_slice() {
+            [python, abi, platform] => (python, abi, platform),
+            _ => {
+                return Err(HuakError::WheelFilenameError(format!(
+                    "invalid compatibility tag {s:?}"
+                )))
+            }
+        };
+        let mut tags = Vec::new();
+        for python in python.split('.') {
+            for abi in abi.split('.') {
+                for platform in platform.split('.') {
+                    tags.push(CompatibilityTag::new(
+                        python,
+                        abi,
+                        PlatformTag::from_str(platform)?,
+                    ));
+                }
+            }
+        }
+        Ok(tags)
+    }
+}
+
+impl FromStr for CompatibilityTag {
+    type Err = HuakError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tags = CompatibilityTag::expand(s)?;
+        match tags.len() {
+            1 => Ok(tags.remove(0)),
+            _ => Err(HuakError::WheelFilenameError(format!(
+                "{s:?} is a compressed tag set"
+            ))),
+        }
+    }
+}
+
+impl Display for CompatibilityTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}-{}", self.python, self.abi, self.platform)
+    }
+}
+
+/// The parts of a wheel's file name.
+/// See https://packaging.python.org/en/latest/specifications/binary-distribution-format/#file-name-convention
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WheelFilename {
+    name: String,
+    version: Version,
+    build: Option<String>,
+    tags: Vec<CompatibilityTag>,
+}
+
+impl WheelFilename {
+    /// Get the name of the distribution.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the version of the distribution.
+    pub fn version(&self) -> &Version {
+        &self.version
+    }
+
+    /// Get the wheel's build tag.
+    pub fn build(&self) -> Option<&str> {
+        self.build.as_deref()
+    }
+
+    /// Get the tags of the expanded tag set.
+    pub fn tags(&self) -> &[CompatibilityTag] {
+        &self.tags
+    }
+
+    /// Get the platforms the wheel supports.
+    pub fn platform_tags(&self) -> Vec<PlatformTag> {
+        let mut platforms = Vec::new();
+        for tag in self.tags.iter() {
+            if !platforms.contains(&tag.platform) {
+                platforms.push(tag.platform.clone());
+            }
+        }
+        platforms
+    }
+}
+
+impl FromStr for WheelFilename {
+    type Err = HuakError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || HuakError::WheelFilenameError(format!("invalid wheel file name {s:?}"));
+        let stem = s.strip_suffix(".whl").ok_or_else(invalid)?;
+        let parts = stem.split('-').collect::<Vec<_>>();
+        let (name, version, build, tags) = match parts.as_slice() {
+            [name, version, python, abi, platform] => {
+                (name, version, None, format!("{python}-{abi}-{platform}"))
+            }
+            [name, version, build, python, abi, platform] => (
+                name,
+                version,
+                Some(build.to_string()),
+                format!("{python}-{abi}-{platform}"),
+            ),
+            _ => return Err(invalid()),
+        };
+        if build
+            .as_ref()
+            .is_some_and(|it| !it.starts_with(|c: char| c.is_ascii_digit()))
+        {
+            return Err(invalid());
+        }
+        Ok(WheelFilename {
+            name: name.to_string(),
+            version: Version::from_str(version).map_err(|_| invalid())?,
+            build,
+            tags: CompatibilityTag::expand(&tags)?,
+        })
+    }
+}
+
+/// The ordered list of tags an environment supports, most specific first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupportedTags {
+    tags: Vec<CompatibilityTag>,
+    /// Each tag's position in `tags`.
+    priorities: HashMap<CompatibilityTag, usize>,
+}
+
+impl SupportedTags {
+    /// Generate the tags supported by a Python implementation such as `cp` or `pp`
+    /// at a version on some platforms. The platforms are ordered most specific
+    /// first.
+    pub fn new(
+        implementation: &str,
+        version: (u16, u16),
+        platforms: &[PlatformTag],
+    ) -> SupportedTags {
+        SupportedTags::generate(implementation, version, None, platforms)
+    }
+
+    /// Generate the tags supported by PyPy at a Python version on some platforms.
+    /// PyPy's ABI tags like `pypy39_pp73` also depend on PyPy's own version.
+    pub fn pypy(
+        version: (u16, u16),
+        pypy_version: (u16, u16),
+        platforms: &[PlatformTag],
+    ) -> SupportedTags {
+        SupportedTags::generate("pp", version, Some(pypy_version), platforms)
+    }
+
+    fn generate(
+        implementation: &str,
+        version: (u16, u16),
+        pypy_version: Option<(u16, u16)>,
+        platforms: &[PlatformTag],
+    ) -> SupportedTags {
+        let (major, minor) = version;
+        let interpreter = format!("{implementation}{major}{minor}");
+        let mut tags = Vec::new();
+        let mut push = |python: &str, abi: &str, platform: &PlatformTag| {
+            tags.push(CompatibilityTag::new(python, abi, platform.clone()))
+        };
+
+        // Tags specific to the implementation.
+        if implementation == "cp" {
+            let abi = match (major, minor) {
+                (3, minor) if minor < 8 => format!("{interpreter}m"),
+                _ => interpreter.clone(),
+            };
+            let abi3 = major == 3 && minor >= 2;
+            for platform in platforms {
+                push(&interpreter, &abi, platform);
+            }
+            if abi3 {
+                for platform in platforms {
+                    push(&interpreter, "abi3", platform);
+                }
+            }
+            for platform in platforms {
+                push(&interpreter, "none", platform);
+            }
+            if abi3 {
+                for older in (2..minor).rev() {
+                    for platform in platforms {
+                        push(&format!("cp{major}{older}"), "abi3", platform);
+                    }
+                }
+            }
+        } else if let Some((pypy_major, pypy_minor)) = pypy_version {
+            let pypy_abi = format!("pp{pypy_major}{pypy_minor}");
+            for abi in [format!("pypy{major}{minor}_{pypy_abi}"), pypy_abi] {
+                for platform in platforms {
+                    push(&interpreter, &abi, platform);
+                }
+            }
+            for platform in platforms {
+                push(&interpreter, "none", platform);
+            }
+        } else {
+            for platform in platforms {
+                push(&interpreter, "none", platform);
+            }
+        }
+
+        // Tags for any implementation of the Python version.
+        let versions = python_versions(major, minor);
+        for python in versions.iter() {
+            for platform in platforms {
+                push(python, "none", platform);
+            }
+        }
+        push(&interpreter, "none", &PlatformTag::Any);
+        for python in versions.iter() {
+            push(python, "none", &PlatformTag::Any);
+        }
+
+        let mut priorities = HashMap::new();
+        for (priority, tag) in tags.iter().enumerate() {
+            priorities.entry(tag.clone()).or_insert(priority);
+        }
+        SupportedTags { tags, priorities }
+    }
+
+    /// Generate the tags supported by the interpreter of a marker environment.
+    /// Linux platforms are generated for the C library found on the host.
+    pub fn from_marker_environment(environment: &MarkerEnvironment) -> HuakResult<SupportedTags> {
+        let implementation = match environment.implementation_name.as_str() {
+            "cpython" => "cp",
+            "pypy" => "pp",
+            "ironpython" => "ip",
+            "jython" => "jy",
+            _ => "py",
+        };
+        let version = major_minor(&environment.python_version).ok_or_else(|| {
+            HuakError::PackageInstallationError(format!(
+                "invalid python version {:?}",
+                environment.python_version
+            ))
+        })?;
+        let platforms = match environment.sys_platform.as_str() {
+            "linux" => linux_platforms(&environment.platform_machine, detect_libc()),
+            "darwin" => match macos_version(&environment.platform_release) {
+                Some(version) => macos_platforms(version, &environment.platform_machine),
+                None => Vec::new(),
+            },
+            "win32" => windows_platforms(&environment.platform_machine),
+            _ => Vec::new(),
+        };
+        // On PyPy the implementation version is PyPy's own version.
+        match major_minor(&environment.implementation_version) {
+            Some(pypy_version) if implementation == "pp" => {
+                Ok(SupportedTags::pypy(version, pypy_version, &platforms))
+            }
+            _ => Ok(SupportedTags::new(implementation, version, &platforms)),
+        }
+    }
+
+    /// Get the supported tags, most specific first.
+    pub fn tags(&self) -> &[CompatibilityTag] {
+        &self.tags
+    }
+
+    /// Get the position of a tag in the supported tags, or `None` if it isn't
+    /// supported. Lower is better.
+    pub fn priority(&self, tag: &CompatibilityTag) -> Option<usize> {
+        self.priorities.get(tag).copied()
+    }
+
+    /// Get the priority of a wheel's best supported tag, or `None` if the wheel
+    /// can't be installed. Lower is better.
+    pub fn wheel_priority(&self, wheel: &WheelFilename) -> Option<usize> {
+        wheel.tags().iter().filter_map(|it| self.priority(it)).min()
+    }
+
+    /// Check if a wheel file can be installed.
+    pub fn is_compatible(&self, filename: &str) -> bool {
+        WheelFilename::from_str(filename).is_ok_and(|it| self.wheel_priority(&it).is_some())
+    }
+
+    /// Pick the best wheel out of some file names. Files that aren't compatible
+    /// wheels are ignored.
+    pub fn best_wheel<'a>(&self, filenames: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+        filenames
+            .into_iter()
+            .filter_map(|it| {
+                let wheel = WheelFilename::from_str(it).ok()?;
+                Some((self.wheel_priority(&wheel)?, it))
+            })
+            .min_by_key(|(priority, _)| *priority)
+            .map(|(_, it)| it)
+    }
+}
+
+/// Parse the major and minor parts of a version like `3.11.4`.
+fn major_minor(version: &str) -> Option<(u16, u16)> {
+    let mut parts = version.split('.');
+    Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+}
+
+/// The C library of a Linux system.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Libc {
+    Glibc(u16, u16),
+    Musl(u16, u16),
+}
+
+/// Detect the C library of the host by running `ldd --version`, which reports the
+/// version of glibc or musl.
+pub fn detect_libc() -> Option<Libc> {
+    let output = Command::new("ldd").arg("--version").output().ok()?;
+    // musl's ldd prints its version to stderr and exits with an error.
+    let text = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    parse_ldd_version(&text)
+}
+
+fn parse_ldd_version(text: &str) -> Option<Libc> {
+    let version = |line: &str| {
+        let (major, minor) = line.split_whitespace().last()?.split_once('.')?;
+        let minor = minor.split('.').next()?;
+        Some((major.parse().ok()?, minor.parse().ok()?))
+    };
+    if text.contains("musl") {
+        let (major, minor) = text
+            .lines()
+            .find(|it| it.starts_with("Version"))
+            .and_then(version)?;
+        return Some(Libc::Musl(major, minor));
+    }
+    let (major, minor) = text.lines().next().and_then(version)?;
+    Some(Libc::Glibc(major, minor))
+}
+
+/// Generate the platforms of a Linux machine with a C library, newest first.
+pub fn linux_platforms(machine: &str, libc: Option<Libc>) -> Vec<PlatformTag> {
+    let arch = match machine {
+        "i386" | "i586" => "i686".to_string(),
+        "arm64" => "aarch64".to_string(),
+        it => it.to_lowercase().replace(['-', '.'], "_"),
+    };
+    let mut platforms = Vec::new();
+    match libc {
+        Some(Libc::Glibc(2, minor)) => {
+            let oldest = match arch.as_str() {
+                "x86_64" | "i686" => MANYLINUX_X86_OLDEST_MINOR,
+                _ => MANYLINUX_OLDEST_MINOR,
+            };
+            for minor in (oldest..=minor).rev() {
+                platforms.push(PlatformTag::Manylinux {
+                    major: 2,
+                    minor,
+                    arch: arch.clone(),
+                });
+                let legacy = match minor {
+                    17 => Some(PlatformTag::Manylinux2014(arch.clone())),
+                    12 => Some(PlatformTag::Manylinux2010(arch.clone())),
+                    5 => Some(PlatformTag::Manylinux1(arch.clone())),
+                    _ => None,
+                };
+                platforms.extend(legacy);
+            }
+        }
+        Some(Libc::Musl(1, minor)) => {
+            for minor in (0..=minor).rev() {
+                platforms.push(PlatformTag::Musllinux {
+                    major: 1,
+                    minor,
+                    arch: arch.clone(),
+                });
+            }
+        }
+        _ => (),
+    }
+    platforms.push(PlatformTag::Linux(arch));
+    platforms
+}
+
+/// Get the macOS version of a Darwin kernel release such as `22.1.0`.
+fn macos_version(release: &str) -> Option<(u16, u16)> {
+    let darwin: u16 = release.split('.').next()?.parse().ok()?;
+    match darwin {
+        20.. => Some((darwin - 9, 0)),
+        8..=19 => Some((10, darwin - 4)),
+        _ => None,
+    }
+}
+
+/// Generate the platforms of a macOS version on a machine, newest first. This
+/// matches `packaging.tags.mac_platforms`.
+pub fn macos_platforms(version: (u16, u16), machine: &str) -> Vec<PlatformTag> {
+    let (major, minor) = version;
+    let mut versions = Vec::new();
+    if major == 10 {
+        versions.extend((0..=minor).rev().map(|minor| ((10, minor), machine)));
+    }
+    if major >= 11 {
+        versions.extend((11..=major).rev().map(|major| ((major, 0), machine)));
+        // Binaries built for macOS 10 still run on x86 Macs with newer versions. Arm
+        // Macs only run the universal2 ones, whose x86 part can target macOS 10.
+        let machine = match machine {
+            "x86_64" => machine,
+            _ => "universal2",
+        };
+        versions.extend(
+            (MACOS_10_OLDEST_MINOR..=16)
+                .rev()
+                .map(|minor| ((10, minor), machine)),
+        );
+    }
+    let mut platforms = Vec::new();
+    for ((major, minor), machine) in versions {
+        for binary_format in macos_binary_formats((major, minor), machine) {
+            platforms.push(PlatformTag::Macos {
+                major,
+                minor,
+                binary_format: binary_format.to_string(),
+            });
+        }
+    }
+    platforms
+}
+
+/// Get the binary formats a machine runs on a macOS version, most specific first.
+fn macos_binary_formats(version: (u16, u16), machine: &str) -> Vec<&str> {
+    let oldest = (10, MACOS_10_OLDEST_MINOR);
+    let mut formats = vec![machine];
+    match machine {
+        "x86_64" if version < oldest => return Vec::new(),
+        "x86_64" => formats.extend(["intel", "fat64", "fat32"]),
+        "i386" if version < oldest => return Vec::new(),
+        "i386" => formats.extend(["intel", "fat32", "fat"]),
+        "ppc64" if version > (10, 5) || version < oldest => return Vec::new(),
+        "ppc64" => formats.push("fat64"),
+        "ppc" if version > (10, 6) => return Vec::new(),
+        "ppc" => formats.extend(["fat32", "fat"]),
+        _ => {}
+    }
+    if matches!(machine, "arm64" | "x86_64") {
+        formats.push("universal2");
+    }
+    if matches!(machine, "x86_64" | "i386" | "ppc64" | "ppc" | "intel") {
+        formats.push("universal");
+    }
+    formats
+}
+
+/// Generate the platforms of a Windows machine.
+pub fn windows_platforms(machine: &str) -> Vec<PlatformTag> {
+    match machine.to_lowercase().as_str() {
+        "amd64" | "x86_64" => vec![PlatformTag::WinAmd64],
+        "arm64" => vec![PlatformTag::WinArm64],
+        "x86" | "i386" | "i686" => vec![PlatformTag::Win32],
+        _ => Vec::new(),
+    }
+}
+
+/// Get the Python version tags of a version, newest first, such as `py311`, `py3`,
+/// `py310`, ..., `py30`.
+fn python_versions(major: u16, minor: u16) -> Vec<String> {
+    let mut versions = vec![format!("py{major}{minor}"), format!("py{major}")];
+    versions.extend((0..minor).rev().map(|minor| format!("py{major}{minor}")));
+    versions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn platform_tag_round_trip() {
+        for tag in [
+            "any",
+            "manylinux_2_17_x86_64",
+            "manylinux2014_aarch64",
+            "manylinux1_i686",
+            "musllinux_1_1_x86_64",
+            "linux_armv7l",
+            "macosx_10_9_universal2",
+            "win_amd64",
+            "cygwin",
+        ] {
+            assert_eq!(PlatformTag::from_str(tag).unwrap().to_string(), tag);
+        }
+        assert_eq!(
+            PlatformTag::from_str("manylinux_2_17_x86_64").unwrap(),
+            PlatformTag::Manylinux {
+                major: 2,
+                minor: 17,
+                arch: "x86_64".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn wheel_filename() {
+        let wheel = WheelFilename::from_str("Demo_Package-1.0-1-py2.py3-none-any.whl").unwrap();
+
+        assert_eq!(wheel.name(), "Demo_Package");
+        assert_eq!(wheel.version(), &Version::from_str("1.0").unwrap());
+        assert_eq!(wheel.build(), Some("1"));
+        assert_eq!(
+            wheel
+                .tags()
+                .iter()
+                .map(|it| it.to_string())
+                .collect::<Vec<_>>(),
+            ["py2-none-any", "py3-none-any"]
+        );
+        assert!(WheelFilename::from_str("demo-1.0.tar.gz").is_err());
+        assert!(WheelFilename::from_str("demo-1.0-none-any.whl").is_err());
+        assert!(WheelFilename::from_str("demo-1.0-x-py3-none-any.whl").is_err());
+    }
+
+    #[test]
+    fn supported_tags_order() {
+        let platforms = linux_platforms("x86_64", Some(Libc::Glibc(2, 17)));
+        let tags = SupportedTags::new("cp", (3, 11), &platforms);
+        let strings = tags
+            .tags()
+            .iter()
+            .map(|it| it.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(strings[0], "cp311-cp311-manylinux_2_17_x86_64");
+        assert_eq!(strings[1], "cp311-cp311-manylinux2014_x86_64");
+        assert!(strings.contains(&"cp37-abi3-manylinux1_x86_64".to_string()));
+        assert_eq!(strings[strings.len() - 14], "cp311-none-any");
+        assert_eq!(strings[strings.len() - 12], "py3-none-any");
+        assert_eq!(strings.last().unwrap(), "py30-none-any");
+
+        let position = |tag: &str| strings.iter().position(|it| it == tag).unwrap();
+        assert!(position("cp311-abi3-linux_x86_64") < position("cp310-abi3-manylinux_2_17_x86_64"));
+        assert!(position("py3-none-manylinux1_x86_64") < position("py3-none-any"));
+    }
+
+    #[test]
+    fn supported_tags_pypy() {
+        let platforms = linux_platforms("x86_64", Some(Libc::Glibc(2, 17)));
+        let tags = SupportedTags::pypy((3, 9), (7, 3), &platforms);
+        let strings = tags
+            .tags()
+            .iter()
+            .map(|it| it.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(strings[0], "pp39-pypy39_pp73-manylinux_2_17_x86_64");
+        assert!(strings.contains(&"pp39-pp73-linux_x86_64".to_string()));
+        assert!(strings.contains(&"pp39-none-any".to_string()));
+        assert!(!strings.iter().any(|it| it.contains("abi3")));
+        assert!(tags.is_compatible("demo-1.0-pp39-pypy39_pp73-manylinux2014_x86_64.whl"));
+    }
+
+    #[test]
+    fn platforms() {
+        assert_eq!(
+            parse_ldd_version("ldd (GNU libc) 2.31\nCopyright (C) 2020"),
+            Some(Libc::Glibc(2, 31))
+        );
+        assert_eq!(
+            parse_ldd_version("musl libc (x86_64)\nVersion 1.2.3\n"),
+            Some(Libc::Musl(1, 2))
+        );
+        assert_eq!(
+            linux_platforms("aarch64", Some(Libc::Musl(1, 1)))
+                .iter()
+                .map(|it| it.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "musllinux_1_1_aarch64",
+                "musllinux_1_0_aarch64",
+                "linux_aarch64"
+            ]
+        );
+        assert_eq!(
+            linux_platforms("aarch64", Some(Libc::Glibc(2, 17))).len(),
+            3
+        );
+        assert_eq!(macos_version("22.1.0"), Some((13, 0)));
+        assert_eq!(macos_version("19.6.0"), Some((10, 15)));
+        let macos = macos_platforms((13, 0), "arm64");
+        assert_eq!(macos[0].to_string(), "macosx_13_0_arm64");
+        assert_eq!(macos.len(), 19);
+        assert_eq!(
+            macos_platforms((11, 0), "arm64")
+                .iter()
+                .map(|it| it.to_string())
+                .take(5)
+                .collect::<Vec<_>>(),
+            [
+                "macosx_11_0_arm64",
+                "macosx_11_0_universal2",
+                "macosx_10_16_universal2",
+                "macosx_10_15_universal2",
+                "macosx_10_14_universal2",
+            ]
+        );
+        assert_eq!(
+            macos_platforms((11, 0), "arm64")
+                .last()
+                .unwrap()
+                .to_string(),
+            "macosx_10_4_universal2"
+        );
+        let macos = macos_platforms((10, 15), "x86_64")
+            .iter()
+            .map(|it| it.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            macos[..6],
+            [
+                "macosx_10_15_x86_64",
+                "macosx_10_15_intel",
+                "macosx_10_15_fat64",
+                "macosx_10_15_fat32",
+                "macosx_10_15_universal2",
+                "macosx_10_15_universal",
+            ]
+        );
+        assert_eq!(macos.len(), 12 * 6);
+        assert_eq!(macos.last().unwrap(), "macosx_10_4_universal");
+        assert_eq!(windows_platforms("AMD64"), [PlatformTag::WinAmd64]);
+    }
+
+    #[test]
+    fn best_wheel() {
+        let platforms = linux_platforms("x86_64", Some(Libc::Glibc(2, 31)));
+        let tags = SupportedTags::new("cp", (3, 11), &platforms);
+        let files = [
+            "numpy-1.26.0.tar.gz",
+            "numpy-1.26.0-cp311-cp311-musllinux_1_1_x86_64.whl",
+            "numpy-1.26.0-cp311-cp311-win_amd64.whl",
+            "numpy-1.26.0-cp310-cp310-manylinux_2_17_x86_64.manylinux2014_x86_64.whl",
+            "numpy-1.26.0-cp311-cp311-manylinux_2_17_x86_64.manylinux2014_x86_64.whl",
+            "numpy-1.26.0-py3-none-any.whl",
+        ];
+
+        assert_eq!(
+            tags.best_wheel(files),
+            Some("numpy-1.26.0-cp311-cp311-manylinux_2_17_x86_64.manylinux2014_x86_64.whl")
+        );
+        assert_eq!(
+            tags.best_wheel([files[3], files[5]]),
+            Some("numpy-1.26.0-py3-none-any.whl")
+        );
+        assert!(!tags.is_compatible(files[1]));
+        assert_eq!(tags.best_wheel([files[0], files[2]]), None);
+    }
+}
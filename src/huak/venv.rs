@@ -0,0 +1,359 @@
+///! This module implements writing the parts of a PEP 405 virtual environment that
+///! would otherwise be written by the base interpreter's `venv` module.
+///! See https://peps.python.org/pep-0405/
+use crate::error::{HuakError, HuakResult};
+use pep440_rs::Version;
+use serde::Deserialize;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    str::FromStr,
+};
+
+/// A Python script printing the base interpreter's executable and version as JSON.
+const BASE_PYTHON_SCRIPT: &str = r#"import json, platform, sys
+print(json.dumps({
+    "executable": getattr(sys, "_base_executable", sys.executable),
+    "version": platform.python_version(),
+}))
+"#;
+
+/// The Python interpreter a virtual environment is created from.
+pub(crate) struct BasePython {
+    /// Absolute path to the interpreter's executable.
+    pub(crate) executable: PathBuf,
+    /// The interpreter's version.
+    pub(crate) version: Version,
+}
+
+#[derive(Deserialize)]
+struct BasePythonOutput {
+    executable: PathBuf,
+    version: String,
+}
+
+/// Query a Python interpreter for the data needed to create a virtual environment
+/// from it. If the interpreter belongs to a virtual environment its base interpreter
+/// is used.
+pub(crate) fn query_base_python(path: impl AsRef<Path>) -> HuakResult<BasePython> {
+    let output = Command::new(path.as_ref())
+        .args(["-c", BASE_PYTHON_SCRIPT])
+        .output()
+        .map_err(|_| HuakError::PythonNotFoundError)?;
+    if !output.status.success() {
+        return Err(HuakError::CommandError(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    let output: BasePythonOutput = serde_json::from_slice(&output.stdout)?;
+    Ok(BasePython {
+        executable: output.executable,
+        version: Version::from_str(&output.version).map_err(HuakError::InternalError)?,
+    })
+}
+
+/// Link the base interpreter into the virtual environment's executables directory as
+/// `python`, `python3`, and `pythonX.Y`.
+#[cfg(unix)]
+pub(crate) fn link_python_executables(
+    executables_dir: &Path,
+    base_executable: &Path,
+    version: &Version,
+) -> HuakResult<()> {
+    let major = version.release.first().copied().unwrap_or(3);
+    let minor = version.release.get(1).copied().unwrap_or(0);
+    for name in [
+        "python".to_string(),
+        format!("python{major}"),
+        format!("python{major}.{minor}"),
+    ] {
+        let link = executables_dir.join(name);
+        if link.symlink_metadata().is_ok() {
+            std::fs::remove_file(&link)?;
+        }
+        std::os::unix::fs::symlink(base_executable, link)?;
+    }
+    Ok(())
+}
+
+/// Copy the base interpreter's executables and the libraries they load into the
+/// virtual environment's executables directory.
+#[cfg(windows)]
+pub(crate) fn link_python_executables(
+    executables_dir: &Path,
+    base_executable: &Path,
+    _version: &Version,
+) -> HuakResult<()> {
+    let home = base_executable
+        .parent()
+        .ok_or(HuakError::PythonNotFoundError)?;
+    for entry in std::fs::read_dir(home)? {
+        let path = entry?.path();
+        let file_name = match path.file_name().and_then(|it| it.to_str()) {
+            Some(it) => it.to_lowercase(),
+            None => continue,
+        };
+        if file_name == "python.exe" || file_name == "pythonw.exe" || file_name.ends_with(".dll") {
+            std::fs::copy(&path, executables_dir.join(&file_name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the shell activation scripts to the virtual environment's executables
+/// directory.
+pub(crate) fn write_activation_scripts(
+    root: &Path,
+    executables_dir: &Path,
+    prompt: &str,
+) -> HuakResult<()> {
+    let bin_name = executables_dir
+        .file_name()
+        .and_then(|it| it.to_str())
+        .ok_or_else(|| HuakError::InternalError("invalid executables directory".to_string()))?;
+    let root = root
+        .to_str()
+        .ok_or_else(|| HuakError::InternalError("invalid virtual environment path".to_string()))?;
+    #[cfg(unix)]
+    let scripts = [
+        ("activate", ACTIVATE_SH),
+        ("activate.fish", ACTIVATE_FISH),
+        ("activate.csh", ACTIVATE_CSH),
+        ("Activate.ps1", ACTIVATE_PS1),
+    ];
+    #[cfg(windows)]
+    let scripts = [
+        ("activate", ACTIVATE_SH),
+        ("activate.bat", ACTIVATE_BAT),
+        ("deactivate.bat", DEACTIVATE_BAT),
+        ("Activate.ps1", ACTIVATE_PS1),
+    ];
+    for (file_name, template) in scripts {
+        let contents = template
+            .replace("__VENV_DIR__", root)
+            .replace("__VENV_BIN_NAME__", bin_name)
+            .replace("__VENV_PROMPT__", prompt);
+        std::fs::write(executables_dir.join(file_name), contents)?;
+    }
+    Ok(())
+}
+
+const ACTIVATE_SH: &str = r#"# This file must be used with "source bin/activate" *from bash or zsh*.
+# You cannot run it directly.
+
+deactivate () {
+    if [ -n "${_OLD_VIRTUAL_PATH:-}" ] ; then
+        PATH="${_OLD_VIRTUAL_PATH:-}"
+        export PATH
+        unset _OLD_VIRTUAL_PATH
+    fi
+    if [ -n "${_OLD_VIRTUAL_PYTHONHOME:-}" ] ; then
+        PYTHONHOME="${_OLD_VIRTUAL_PYTHONHOME:-}"
+        export PYTHONHOME
+        unset _OLD_VIRTUAL_PYTHONHOME
+    fi
+    hash -r 2> /dev/null
+    if [ -n "${_OLD_VIRTUAL_PS1:-}" ] ; then
+        PS1="${_OLD_VIRTUAL_PS1:-}"
+        export PS1
+        unset _OLD_VIRTUAL_PS1
+    fi
+    unset VIRTUAL_ENV
+    unset VIRTUAL_ENV_PROMPT
+    if [ ! "${1:-}" = "nondestructive" ] ; then
+        unset -f deactivate
+    fi
+}
+
+deactivate nondestructive
+
+VIRTUAL_ENV="__VENV_DIR__"
+export VIRTUAL_ENV
+
+_OLD_VIRTUAL_PATH="$PATH"
+PATH="$VIRTUAL_ENV/__VENV_BIN_NAME__:$PATH"
+export PATH
+
+if [ -n "${PYTHONHOME:-}" ] ; then
+    _OLD_VIRTUAL_PYTHONHOME="${PYTHONHOME:-}"
+    unset PYTHONHOME
+fi
+
+if [ -z "${VIRTUAL_ENV_DISABLE_PROMPT:-}" ] ; then
+    _OLD_VIRTUAL_PS1="${PS1:-}"
+    PS1="(__VENV_PROMPT__) ${PS1:-}"
+    export PS1
+    VIRTUAL_ENV_PROMPT="(__VENV_PROMPT__) "
+    export VIRTUAL_ENV_PROMPT
+fi
+
+hash -r 2> /dev/null
+"#;
+
+#[cfg(unix)]
+const ACTIVATE_FISH: &str = r#"# This file must be used with "source <venv>/bin/activate.fish" *from fish*.
+# You cannot run it directly.
+
+function deactivate -d "Exit virtual environment and return to normal shell environment"
+    if test -n "$_OLD_VIRTUAL_PATH"
+        set -gx PATH $_OLD_VIRTUAL_PATH
+        set -e _OLD_VIRTUAL_PATH
+    end
+    if test -n "$_OLD_VIRTUAL_PYTHONHOME"
+        set -gx PYTHONHOME $_OLD_VIRTUAL_PYTHONHOME
+        set -e _OLD_VIRTUAL_PYTHONHOME
+    end
+    if test -n "$_OLD_FISH_PROMPT_OVERRIDE"
+        set -e _OLD_FISH_PROMPT_OVERRIDE
+        if functions -q _old_fish_prompt
+            functions -e fish_prompt
+            functions -c _old_fish_prompt fish_prompt
+            functions -e _old_fish_prompt
+        end
+    end
+    set -e VIRTUAL_ENV
+    set -e VIRTUAL_ENV_PROMPT
+    if test "$argv[1]" != "nondestructive"
+        functions -e deactivate
+    end
+end
+
+deactivate nondestructive
+
+set -gx VIRTUAL_ENV "__VENV_DIR__"
+
+set -gx _OLD_VIRTUAL_PATH $PATH
+set -gx PATH "$VIRTUAL_ENV/__VENV_BIN_NAME__" $PATH
+
+if set -q PYTHONHOME
+    set -gx _OLD_VIRTUAL_PYTHONHOME $PYTHONHOME
+    set -e PYTHONHOME
+end
+
+if test -z "$VIRTUAL_ENV_DISABLE_PROMPT"
+    functions -c fish_prompt _old_fish_prompt
+    function fish_prompt
+        set -l old_status $status
+        printf "%s%s%s" (set_color 4B8BBE) "(__VENV_PROMPT__) " (set_color normal)
+        echo "exit $old_status" | .
+        _old_fish_prompt
+    end
+    set -gx _OLD_FISH_PROMPT_OVERRIDE "$VIRTUAL_ENV"
+    set -gx VIRTUAL_ENV_PROMPT "(__VENV_PROMPT__) "
+end
+"#;
+
+#[cfg(unix)]
+const ACTIVATE_CSH: &str = r#"# This file must be used with "source bin/activate.csh" *from csh*.
+# You cannot run it directly.
+
+alias deactivate 'test $?_OLD_VIRTUAL_PATH != 0 && setenv PATH "$_OLD_VIRTUAL_PATH" && unset _OLD_VIRTUAL_PATH; rehash; test $?_OLD_VIRTUAL_PROMPT != 0 && set prompt="$_OLD_VIRTUAL_PROMPT" && unset _OLD_VIRTUAL_PROMPT; unsetenv VIRTUAL_ENV; unsetenv VIRTUAL_ENV_PROMPT; test "\!:*" != "nondestructive" && unalias deactivate'
+
+deactivate nondestructive
+
+setenv VIRTUAL_ENV "__VENV_DIR__"
+
+set _OLD_VIRTUAL_PATH="$PATH"
+setenv PATH "$VIRTUAL_ENV/__VENV_BIN_NAME__:$PATH"
+
+set _OLD_VIRTUAL_PROMPT="$prompt"
+
+if (! "$?VIRTUAL_ENV_DISABLE_PROMPT") then
+    set prompt = "(__VENV_PROMPT__) $prompt"
+    setenv VIRTUAL_ENV_PROMPT "(__VENV_PROMPT__) "
+endif
+
+rehash
+"#;
+
+#[cfg(windows)]
+const ACTIVATE_BAT: &str = r#"@echo off
+
+set "VIRTUAL_ENV=__VENV_DIR__"
+
+if not defined PROMPT set PROMPT=$P$G
+
+if defined _OLD_VIRTUAL_PROMPT set PROMPT=%_OLD_VIRTUAL_PROMPT%
+if defined _OLD_VIRTUAL_PYTHONHOME set PYTHONHOME=%_OLD_VIRTUAL_PYTHONHOME%
+
+set _OLD_VIRTUAL_PROMPT=%PROMPT%
+set PROMPT=(__VENV_PROMPT__) %PROMPT%
+
+if defined PYTHONHOME set _OLD_VIRTUAL_PYTHONHOME=%PYTHONHOME%
+set PYTHONHOME=
+
+if defined _OLD_VIRTUAL_PATH set PATH=%_OLD_VIRTUAL_PATH%
+if not defined _OLD_VIRTUAL_PATH set _OLD_VIRTUAL_PATH=%PATH%
+
+set PATH=%VIRTUAL_ENV%\__VENV_BIN_NAME__;%PATH%
+set "VIRTUAL_ENV_PROMPT=(__VENV_PROMPT__) "
+"#;
+
+#[cfg(windows)]
+const DEACTIVATE_BAT: &str = r#"@echo off
+
+if defined _OLD_VIRTUAL_PROMPT (
+    set "PROMPT=%_OLD_VIRTUAL_PROMPT%"
+)
+set _OLD_VIRTUAL_PROMPT=
+
+if defined _OLD_VIRTUAL_PYTHONHOME (
+    set "PYTHONHOME=%_OLD_VIRTUAL_PYTHONHOME%"
+    set _OLD_VIRTUAL_PYTHONHOME=
+)
+
+if defined _OLD_VIRTUAL_PATH (
+    set "PATH=%_OLD_VIRTUAL_PATH%"
+)
+set _OLD_VIRTUAL_PATH=
+
+set VIRTUAL_ENV=
+set VIRTUAL_ENV_PROMPT=
+"#;
+
+const ACTIVATE_PS1: &str = r#"function global:deactivate ([switch]$NonDestructive) {
+    if (Test-Path -Path Function:_OLD_VIRTUAL_PROMPT) {
+        Copy-Item -Path Function:_OLD_VIRTUAL_PROMPT -Destination Function:prompt
+        Remove-Item -Path Function:_OLD_VIRTUAL_PROMPT
+    }
+    if (Test-Path -Path Env:_OLD_VIRTUAL_PYTHONHOME) {
+        Copy-Item -Path Env:_OLD_VIRTUAL_PYTHONHOME -Destination Env:PYTHONHOME
+        Remove-Item -Path Env:_OLD_VIRTUAL_PYTHONHOME
+    }
+    if (Test-Path -Path Env:_OLD_VIRTUAL_PATH) {
+        Copy-Item -Path Env:_OLD_VIRTUAL_PATH -Destination Env:PATH
+        Remove-Item -Path Env:_OLD_VIRTUAL_PATH
+    }
+    if (Test-Path -Path Env:VIRTUAL_ENV) {
+        Remove-Item -Path Env:VIRTUAL_ENV
+    }
+    if (Test-Path -Path Env:VIRTUAL_ENV_PROMPT) {
+        Remove-Item -Path Env:VIRTUAL_ENV_PROMPT
+    }
+    if (-not $NonDestructive) {
+        Remove-Item -Path Function:deactivate
+    }
+}
+
+deactivate -NonDestructive
+
+$Env:VIRTUAL_ENV = "__VENV_DIR__"
+
+if (-not $Env:VIRTUAL_ENV_DISABLE_PROMPT) {
+    function global:_OLD_VIRTUAL_PROMPT { "" }
+    Copy-Item -Path Function:prompt -Destination Function:_OLD_VIRTUAL_PROMPT
+    function global:prompt {
+        Write-Host -NoNewline -ForegroundColor Green "(__VENV_PROMPT__) "
+        _OLD_VIRTUAL_PROMPT
+    }
+    $Env:VIRTUAL_ENV_PROMPT = "(__VENV_PROMPT__) "
+}
+
+if (Test-Path -Path Env:PYTHONHOME) {
+    Copy-Item -Path Env:PYTHONHOME -Destination Env:_OLD_VIRTUAL_PYTHONHOME
+    Remove-Item -Path Env:PYTHONHOME
+}
+
+Copy-Item -Path Env:PATH -Destination Env:_OLD_VIRTUAL_PATH
+$Env:PATH = "$Env:VIRTUAL_ENV/__VENV_BIN_NAME__$([System.IO.Path]::PathSeparator)$Env:PATH"
+"#;
@@ -0,0 +1,923 @@
+///! This module implements installing wheels as specified by the binary distribution
+///! format.
+///! See https://packaging.python.org/en/latest/specifications/binary-distribution-format/
+use crate::{
+    error::{HuakError, HuakResult},
+    index::is_plain_file_name,
+    metadata::{parse_entry_points, EntryPoint, WheelMetadata},
+    PackageMetadata,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Read,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+};
+use zip::ZipArchive;
+
+/// The directories the parts of a wheel are installed to.
+/// See https://docs.python.org/3/library/sysconfig.html#installation-paths
+pub(crate) struct InstallScheme {
+    /// Pure Python modules.
+    pub(crate) purelib: PathBuf,
+    /// Platform-specific modules.
+    pub(crate) platlib: PathBuf,
+    /// Scripts and console script launchers.
+    pub(crate) scripts: PathBuf,
+    /// C headers. Each distribution's headers go in a directory named after it.
+    pub(crate) headers: PathBuf,
+    /// Data files installed relative to the environment's root.
+    pub(crate) data: PathBuf,
+}
+
+impl InstallScheme {
+    fn dir(&self, key: &str) -> Option<&Path> {
+        match key {
+            "purelib" => Some(&self.purelib),
+            "platlib" => Some(&self.platlib),
+            "scripts" => Some(&self.scripts),
+            "headers" => Some(&self.headers),
+            "data" => Some(&self.data),
+            _ => None,
+        }
+    }
+}
+
+/// A row of a RECORD file.
+/// See https://packaging.python.org/en/latest/specifications/recording-installed-packages/#the-record-file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordEntry {
+    /// The file's path relative to the directory containing the .dist-info directory.
+    pub(crate) path: String,
+    /// The file's hash as `<algorithm>=<urlsafe-base64-nopad digest>`.
+    pub(crate) hash: Option<String>,
+    /// The file's size in bytes.
+    pub(crate) size: Option<u64>,
+}
+
+impl RecordEntry {
+    /// Get the file's path relative to the directory containing the .dist-info
+    /// directory.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Get the file's hash.
+    pub fn hash(&self) -> Option<&str> {
+        self.hash.as_deref()
+    }
+
+    /// Get the file's size in bytes.
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+}
+
+/// A wheel whose files were checked against its RECORD. Installing a verified wheel
+/// won't fail halfway through on a file that doesn't match.
+pub(crate) struct VerifiedWheel {
+    /// The path to the wheel file.
+    path: PathBuf,
+    /// The name of the wheel's .dist-info directory.
+    dist_info_name: String,
+    /// The name of the wheel's .data directory.
+    data_name: String,
+    /// The name of the distribution from its core metadata.
+    dist_name: String,
+    /// Whether the wheel's root is installed to purelib rather than platlib.
+    root_is_purelib: bool,
+    /// The console scripts the wheel's entry points declare.
+    console_scripts: Vec<EntryPoint>,
+}
+
+/// Install a wheel file using an install scheme. Scripts are rewritten to use the
+/// Python interpreter at `python_path`. The path to the installed .dist-info
+/// directory is returned.
+pub(crate) fn install_wheel(
+    wheel_path: &Path,
+    scheme: &InstallScheme,
+    python_path: &Path,
+    installer: &str,
+    requested: bool,
+) -> HuakResult<PathBuf> {
+    install_verified_wheel(
+        &verify_wheel(wheel_path, scheme)?,
+        scheme,
+        python_path,
+        installer,
+        requested,
+    )
+}
+
+/// Check every file of a wheel before anything is written, so that a wheel that
+/// fails verification leaves the environment untouched. Each file other than the
+/// RECORD and its signatures must be listed in RECORD with a hash it matches, and
+/// nothing may be installed outside of the install scheme.
+pub(crate) fn verify_wheel(wheel_path: &Path, scheme: &InstallScheme) -> HuakResult<VerifiedWheel> {
+    let mut archive = ZipArchive::new(File::open(wheel_path)?)?;
+    let dist_info_name = find_dist_info_name(&archive, wheel_path)?;
+    let data_name = format!("{}.data", dist_info_name.trim_end_matches(".dist-info"));
+
+    let wheel_metadata = WheelMetadata::from_str(&read_archive_file(
+        &mut archive,
+        &format!("{dist_info_name}/WHEEL"),
+    )?)?;
+    if wheel_metadata.wheel_version().split('.').next() != Some("1") {
+        return Err(wheel_error(wheel_path, "unsupported Wheel-Version"));
+    }
+    let dist_name = PackageMetadata::from_str(&read_archive_file(
+        &mut archive,
+        &format!("{dist_info_name}/METADATA"),
+    )?)?
+    .name()
+    .to_string();
+
+    let record_name = format!("{dist_info_name}/RECORD");
+    let unhashed = [
+        record_name.clone(),
+        format!("{record_name}.jws"),
+        format!("{record_name}.p7s"),
+    ];
+    let expected_hashes = parse_record(&read_archive_file(&mut archive, &record_name)?)?
+        .into_iter()
+        .filter_map(|it| it.hash.map(|hash| (it.path, hash)))
+        .collect::<HashMap<_, _>>();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let name = entry.name().to_string();
+        if entry.is_dir() || unhashed.contains(&name) {
+            continue;
+        }
+        if entry.enclosed_name().is_none() {
+            return Err(wheel_error(
+                wheel_path,
+                &format!("`{name}` would be installed outside of the environment"),
+            ));
+        }
+        if let Some(key) = name
+            .strip_prefix(&format!("{data_name}/"))
+            .map(|rest| rest.split_once('/').map_or(rest, |it| it.0))
+        {
+            if scheme.dir(key).is_none() {
+                return Err(wheel_error(
+                    wheel_path,
+                    &format!("unknown data directory `{key}`"),
+                ));
+            }
+        }
+        let expected = expected_hashes.get(&name).ok_or_else(|| {
+            wheel_error(
+                wheel_path,
+                &format!("`{name}` isn't listed with a hash in RECORD"),
+            )
+        })?;
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+        if !hash_matches(expected, &contents) {
+            return Err(wheel_error(
+                wheel_path,
+                &format!("the hash of `{name}` doesn't match its RECORD entry"),
+            ));
+        }
+    }
+
+    let mut console_scripts = Vec::new();
+    if let Ok(entry_points) =
+        read_archive_file(&mut archive, &format!("{dist_info_name}/entry_points.txt"))
+    {
+        for entry_point in parse_entry_points(&entry_points)? {
+            if !entry_point.is_script() {
+                continue;
+            }
+            // Launchers are written to the scripts directory under the script's name.
+            if !is_plain_file_name(entry_point.name()) {
+                return Err(wheel_error(
+                    wheel_path,
+                    &format!("invalid console script name `{}`", entry_point.name()),
+                ));
+            }
+            console_scripts.push(entry_point);
+        }
+    }
+
+    Ok(VerifiedWheel {
+        path: wheel_path.to_path_buf(),
+        dist_info_name,
+        data_name,
+        dist_name,
+        root_is_purelib: wheel_metadata.root_is_purelib(),
+        console_scripts,
+    })
+}
+
+/// Install a verified wheel using an install scheme. Scripts are rewritten to use the
+/// Python interpreter at `python_path`. A REQUESTED file is written if the user asked
+/// for the distribution rather than it being installed as a dependency. The path to
+/// the installed .dist-info directory is returned.
+pub(crate) fn install_verified_wheel(
+    wheel: &VerifiedWheel,
+    scheme: &InstallScheme,
+    python_path: &Path,
+    installer: &str,
+    requested: bool,
+) -> HuakResult<PathBuf> {
+    let wheel_path = wheel.path.as_path();
+    let mut archive = ZipArchive::new(File::open(wheel_path)?)?;
+    let record_name = format!("{}/RECORD", wheel.dist_info_name);
+    let root = match wheel.root_is_purelib {
+        true => &scheme.purelib,
+        false => &scheme.platlib,
+    };
+
+    let mut records = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if entry.is_dir() || entry.name() == record_name {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let (destination, is_script) = match name.strip_prefix(&format!("{}/", wheel.data_name)) {
+            Some(rest) => {
+                let (key, path) = rest.split_once('/').unwrap_or((rest, ""));
+                let dir = scheme.dir(key).ok_or_else(|| {
+                    wheel_error(wheel_path, &format!("unknown data directory `{key}`"))
+                })?;
+                match key {
+                    "headers" => (dir.join(&wheel.dist_name).join(path), false),
+                    _ => (dir.join(path), key == "scripts"),
+                }
+            }
+            None => (root.join(&name), false),
+        };
+
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+        let executable = entry.unix_mode().is_some_and(|mode| mode & 0o111 != 0);
+        if is_script && contents.starts_with(b"#!python") {
+            let body_start = contents
+                .iter()
+                .position(|it| *it == b'\n')
+                .map_or(contents.len(), |it| it + 1);
+            let mut script = shebang(python_path).into_bytes();
+            script.extend_from_slice(&contents[body_start..]);
+            contents = script;
+        }
+        records.push(write_file(
+            &destination,
+            &contents,
+            executable || is_script,
+            root,
+        )?);
+    }
+
+    for entry_point in wheel.console_scripts.iter() {
+        for (file_name, contents) in script_launchers(entry_point, python_path)? {
+            records.push(write_file(
+                &scheme.scripts.join(file_name),
+                contents.as_bytes(),
+                true,
+                root,
+            )?);
+        }
+    }
+
+    let dist_info_path = root.join(&wheel.dist_info_name);
+    records.push(write_file(
+        &dist_info_path.join("INSTALLER"),
+        format!("{installer}\n").as_bytes(),
+        false,
+        root,
+    )?);
+    if requested {
+        records.push(write_file(
+            &dist_info_path.join("REQUESTED"),
+            b"",
+            false,
+            root,
+        )?);
+    }
+    records.push(RecordEntry {
+        path: record_path(root, &dist_info_path.join("RECORD")),
+        hash: None,
+        size: None,
+    });
+    std::fs::write(dist_info_path.join("RECORD"), format_record(&records))?;
+
+    Ok(dist_info_path)
+}
+
+/// Read the core metadata of a wheel from the METADATA file of its .dist-info
+/// directory.
+pub(crate) fn read_wheel_metadata(wheel_path: &Path) -> HuakResult<String> {
+    let mut archive = ZipArchive::new(File::open(wheel_path)?)?;
+    let dist_info_name = find_dist_info_name(&archive, wheel_path)?;
+    read_archive_file(&mut archive, &format!("{dist_info_name}/METADATA"))
+}
+
+/// Uninstall the distribution installed with a .dist-info directory. The files listed
+/// in its RECORD and the bytecode compiled from them are removed, and then any
+/// directories left empty are pruned. Directories of the install scheme are never
+/// pruned. Nothing is removed if a file is outside of `env_root` or if `dry_run` is
+/// true. The paths of the removed files are returned.
+pub(crate) fn uninstall_dist_info(
+    dist_info_path: &Path,
+    scheme: &InstallScheme,
+    env_root: &Path,
+    dry_run: bool,
+) -> HuakResult<Vec<PathBuf>> {
+    let dist_info_path = std::fs::canonicalize(dist_info_path)?;
+    let env_root = std::fs::canonicalize(env_root)?;
+    let root = dist_info_path.parent().unwrap_or(&dist_info_path);
+    let record = std::fs::read_to_string(dist_info_path.join("RECORD"))?;
+
+    let mut files = Vec::new();
+    for entry in parse_record(&record)? {
+        let path = normalize_path(&root.join(&entry.path));
+        if !path.starts_with(&env_root) {
+            return Err(HuakError::PackageInstallationError(format!(
+                "refusing to remove {} since it is outside of {}",
+                path.display(),
+                env_root.display()
+            )));
+        }
+        if path.extension().is_some_and(|it| it == "py") {
+            files.extend(compiled_bytecode_paths(&path));
+        }
+        files.push(path);
+    }
+    files.retain(|it| it.symlink_metadata().is_ok());
+    files.sort();
+    files.dedup();
+    if dry_run {
+        return Ok(files);
+    }
+
+    for file in files.iter() {
+        std::fs::remove_file(file)?;
+    }
+    let protected = [
+        &scheme.purelib,
+        &scheme.platlib,
+        &scheme.scripts,
+        &scheme.headers,
+        &scheme.data,
+    ]
+    .iter()
+    .filter_map(|it| std::fs::canonicalize(it).ok())
+    .collect::<Vec<_>>();
+    for file in files.iter() {
+        for dir in file.ancestors().skip(1) {
+            if dir == env_root
+                || !dir.starts_with(&env_root)
+                || protected.iter().any(|it| it == dir)
+                || std::fs::remove_dir(dir).is_err()
+            {
+                break;
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Get the paths of the bytecode compiled from a Python file.
+/// See https://peps.python.org/pep-3147/
+fn compiled_bytecode_paths(path: &Path) -> Vec<PathBuf> {
+    let (dir, stem) = match (path.parent(), path.file_stem().and_then(|it| it.to_str())) {
+        (Some(dir), Some(stem)) => (dir, stem),
+        _ => return Vec::new(),
+    };
+    let prefix = format!("{stem}.");
+    match std::fs::read_dir(dir.join("__pycache__")) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|it| {
+                it.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".pyc"))
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Lexically resolve `.` and `..` components of a path.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn wheel_error(wheel_path: &Path, message: &str) -> HuakError {
+    HuakError::PackageInstallationError(format!("{}: {message}", wheel_path.display()))
+}
+
+/// Find the name of the wheel's single top-level .dist-info directory.
+fn find_dist_info_name(archive: &ZipArchive<File>, wheel_path: &Path) -> HuakResult<String> {
+    let mut names = archive
+        .file_names()
+        .filter_map(|it| it.split_once('/'))
+        .filter(|(dir, file)| dir.ends_with(".dist-info") && *file == "WHEEL")
+        .map(|(dir, _)| dir.to_string())
+        .collect::<Vec<_>>();
+    names.dedup();
+    match names.len() {
+        1 => Ok(names.remove(0)),
+        0 => Err(wheel_error(wheel_path, "missing a .dist-info directory")),
+        _ => Err(wheel_error(
+            wheel_path,
+            "contains more than one .dist-info directory",
+        )),
+    }
+}
+
+fn read_archive_file(archive: &mut ZipArchive<File>, name: &str) -> HuakResult<String> {
+    let mut contents = String::new();
+    archive.by_name(name)?.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+/// Write a file, creating its parent directories, and return its RECORD entry.
+fn write_file(
+    path: &Path,
+    contents: &[u8],
+    executable: bool,
+    record_root: &Path,
+) -> HuakResult<RecordEntry> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)?;
+    #[cfg(unix)]
+    if executable {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    #[cfg(not(unix))]
+    let _ = executable;
+    Ok(RecordEntry {
+        path: record_path(record_root, path),
+        hash: Some(hash_contents(contents)),
+        size: Some(contents.len() as u64),
+    })
+}
+
+/// Hash contents as `sha256=<urlsafe-base64-nopad digest>`.
+pub(crate) fn hash_contents(contents: &[u8]) -> String {
+    format!(
+        "sha256={}",
+        URL_SAFE_NO_PAD.encode(Sha256::digest(contents))
+    )
+}
+
+/// Check contents against a RECORD hash. Hashes using algorithms other than sha256,
+/// sha384, and sha512 never match.
+fn hash_matches(expected: &str, contents: &[u8]) -> bool {
+    let (algorithm, digest) = match expected.split_once('=') {
+        Some(it) => it,
+        None => return false,
+    };
+    let actual = match algorithm {
+        "sha256" => Sha256::digest(contents).to_vec(),
+        "sha384" => Sha384::digest(contents).to_vec(),
+        "sha512" => Sha512::digest(contents).to_vec(),
+        _ => return false,
+    };
+    digest == URL_SAFE_NO_PAD.encode(actual)
+}
+
+/// Get a path as it's written in RECORD: relative to the directory containing the
+/// .dist-info directory and using `/` separators.
+pub(crate) fn record_path(root: &Path, path: &Path) -> String {
+    let root = root.components().collect::<Vec<_>>();
+    let path = path.components().collect::<Vec<_>>();
+    let common = root
+        .iter()
+        .zip(path.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut parts = vec![".."; root.len() - common];
+    parts.extend(path[common..].iter().filter_map(|it| match it {
+        Component::Normal(part) => part.to_str(),
+        _ => None,
+    }));
+    parts.join("/")
+}
+
+/// Parse the CSV rows of a RECORD file.
+pub(crate) fn parse_record(contents: &str) -> HuakResult<Vec<RecordEntry>> {
+    let mut entries = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = parse_csv_line(line).ok_or_else(|| {
+            HuakError::PackageInstallationError(format!("invalid RECORD line {}", i + 1))
+        })?;
+        let field = |i: usize| fields.get(i).filter(|it| !it.is_empty()).cloned();
+        entries.push(RecordEntry {
+            path: field(0).ok_or_else(|| {
+                HuakError::PackageInstallationError(format!(
+                    "missing path on RECORD line {}",
+                    i + 1
+                ))
+            })?,
+            hash: field(1),
+            size: field(2).and_then(|it| it.parse().ok()),
+        });
+    }
+    Ok(entries)
+}
+
+/// Format RECORD entries as CSV rows.
+pub(crate) fn format_record(entries: &[RecordEntry]) -> String {
+    let quote = |field: &str| {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    };
+    entries
+        .iter()
+        .map(|it| {
+            format!(
+                "{},{},{}\n",
+                quote(&it.path),
+                it.hash.as_deref().map(quote).unwrap_or_default(),
+                it.size.map(|size| size.to_string()).unwrap_or_default()
+            )
+        })
+        .collect()
+}
+
+fn parse_csv_line(line: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            (c, _) => field.push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    fields.push(field);
+    Some(fields)
+}
+
+/// Get the files used to launch a console script `module:attr [extras]`.
+fn script_launchers(
+    entry_point: &EntryPoint,
+    python_path: &Path,
+) -> HuakResult<Vec<(String, String)>> {
+    let name = entry_point.name();
+    let (module, attr) = match (entry_point.module(), entry_point.attr()) {
+        (module, Some(attr)) if !module.is_empty() && !attr.is_empty() => (module, attr),
+        _ => {
+            return Err(HuakError::PackageInstallationError(format!(
+                "invalid console script `{name} = {}`",
+                entry_point.value()
+            )))
+        }
+    };
+    let import_name = attr.split('.').next().unwrap_or(attr);
+    let script = format!(
+        r#"{}# -*- coding: utf-8 -*-
+import re
+import sys
+from {module} import {import_name}
+if __name__ == "__main__":
+    sys.argv[0] = re.sub(r"(-script\.pyw|\.exe)?$", "", sys.argv[0])
+    sys.exit({attr}())
+"#,
+        shebang(python_path)
+    );
+    #[cfg(unix)]
+    let launchers = vec![(name.to_string(), script)];
+    #[cfg(windows)]
+    let launchers = vec![
+        (
+            format!("{name}.cmd"),
+            format!("@\"%~dp0python.exe\" \"%~dp0{name}-script.py\" %*\r\n"),
+        ),
+        (format!("{name}-script.py"), script),
+    ];
+    Ok(launchers)
+}
+
+/// Get the shebang line for a Python interpreter. Interpreter paths that can't be
+/// used in a shebang directly are run through `/bin/sh`.
+fn shebang(python_path: &Path) -> String {
+    let python_path = python_path.display().to_string();
+    if python_path.contains(' ') || python_path.len() > 127 {
+        format!("#!/bin/sh\n'''exec' \"{python_path}\" \"$0\" \"$@\"\n' '''\n")
+    } else {
+        format!("#!{python_path}\n")
+    }
+}
+
+/// The files of the minimal wheel written for tests.
+#[cfg(test)]
+const TEST_WHEEL_FILES: [(&str, &[u8]); 6] = [
+    ("demo/__init__.py", b"def main():\n    print('demo')\n"),
+    (
+        "demo-1.0.data/scripts/demo-tool",
+        b"#!python\nprint('tool')\n",
+    ),
+    ("demo-1.0.data/headers/demo.h", b"int demo;\n"),
+    (
+        "demo-1.0.dist-info/METADATA",
+        b"Metadata-Version: 2.1\nName: demo\nVersion: 1.0\n",
+    ),
+    (
+        "demo-1.0.dist-info/WHEEL",
+        b"Wheel-Version: 1.0\nGenerator: test\nRoot-Is-Purelib: true\nTag: py3-none-any\n",
+    ),
+    (
+        "demo-1.0.dist-info/entry_points.txt",
+        b"[console_scripts]\ndemo = demo:main\n",
+    ),
+];
+
+/// Write a minimal wheel for tests. The wheel contains a `demo` package, a `.data`
+/// script, and a `demo` console script.
+#[cfg(test)]
+pub(crate) fn write_test_wheel(dir: &Path) -> PathBuf {
+    write_wheel(&dir.join("demo-1.0-py3-none-any.whl"), &TEST_WHEEL_FILES)
+}
+
+/// Write a wheel with files and a RECORD listing them with their hashes for tests.
+#[cfg(test)]
+fn write_wheel(path: &Path, files: &[(&str, &[u8])]) -> PathBuf {
+    use std::io::Write;
+    use zip::{write::FileOptions, ZipWriter};
+
+    let mut record = files
+        .iter()
+        .map(|(name, contents)| RecordEntry {
+            path: name.to_string(),
+            hash: Some(hash_contents(contents)),
+            size: Some(contents.len() as u64),
+        })
+        .collect::<Vec<_>>();
+    record.push(RecordEntry {
+        path: "demo-1.0.dist-info/RECORD".to_string(),
+        hash: None,
+        size: None,
+    });
+
+    let mut writer = ZipWriter::new(File::create(path).unwrap());
+    for (name, contents) in files {
+        writer.start_file(*name, FileOptions::default()).unwrap();
+        writer.write_all(contents).unwrap();
+    }
+    writer
+        .start_file("demo-1.0.dist-info/RECORD", FileOptions::default())
+        .unwrap();
+    writer.write_all(format_record(&record).as_bytes()).unwrap();
+    writer.finish().unwrap();
+    path.to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn test_scheme(root: &Path) -> InstallScheme {
+        let site_packages = root.join("lib").join("python3.11").join("site-packages");
+        InstallScheme {
+            purelib: site_packages.clone(),
+            platlib: site_packages,
+            scripts: root.join("bin"),
+            headers: root.join("include").join("site").join("python3.11"),
+            data: root.to_path_buf(),
+        }
+    }
+
+    #[test]
+    fn wheel_install() {
+        let dir = tempdir().unwrap().into_path();
+        let wheel_path = write_test_wheel(&dir);
+        let root = dir.join(".venv");
+        let scheme = test_scheme(&root);
+        let python_path = root.join("bin").join("python");
+
+        let dist_info = install_wheel(&wheel_path, &scheme, &python_path, "huak", true).unwrap();
+
+        assert!(scheme.purelib.join("demo").join("__init__.py").exists());
+        assert!(scheme.headers.join("demo").join("demo.h").exists());
+        let tool = std::fs::read_to_string(scheme.scripts.join("demo-tool")).unwrap();
+        assert_eq!(
+            tool,
+            format!("#!{}\nprint('tool')\n", python_path.display())
+        );
+        #[cfg(unix)]
+        assert!(std::fs::read_to_string(scheme.scripts.join("demo"))
+            .unwrap()
+            .contains("from demo import main"));
+        assert_eq!(
+            std::fs::read_to_string(dist_info.join("INSTALLER")).unwrap(),
+            "huak\n"
+        );
+        assert!(dist_info.join("REQUESTED").exists());
+
+        let record =
+            parse_record(&std::fs::read_to_string(dist_info.join("RECORD")).unwrap()).unwrap();
+        let init = record
+            .iter()
+            .find(|it| it.path == "demo/__init__.py")
+            .unwrap();
+        assert_eq!(
+            init.hash.as_deref(),
+            Some(hash_contents(b"def main():\n    print('demo')\n").as_str())
+        );
+        assert!(record.iter().any(|it| it.path == "../../../bin/demo-tool"));
+        assert!(record
+            .iter()
+            .any(|it| it.path == "demo-1.0.dist-info/RECORD" && it.hash.is_none()));
+    }
+
+    /// Copy the test wheel with its RECORD replaced.
+    fn with_record(dir: &Path, edit: impl Fn(&str) -> String) -> PathBuf {
+        use std::io::Write;
+
+        let mut archive = ZipArchive::new(File::open(write_test_wheel(dir)).unwrap()).unwrap();
+        let path = dir.join("tampered-1.0-py3-none-any.whl");
+        let mut writer = zip::ZipWriter::new(File::create(&path).unwrap());
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).unwrap();
+            let name = entry.name().to_string();
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents).unwrap();
+            if name.ends_with("RECORD") {
+                contents = edit(std::str::from_utf8(&contents).unwrap()).into_bytes();
+            }
+            writer
+                .start_file(name, zip::write::FileOptions::default())
+                .unwrap();
+            writer.write_all(&contents).unwrap();
+        }
+        writer.finish().unwrap();
+        path
+    }
+
+    /// Install a wheel to a new environment and check that it's rejected without
+    /// anything being written.
+    fn assert_rejected(dir: &Path, wheel_path: &Path) {
+        let root = dir.join(".venv");
+
+        assert!(install_wheel(
+            wheel_path,
+            &test_scheme(&root),
+            &root.join("bin").join("python"),
+            "huak",
+            false
+        )
+        .is_err());
+        assert!(!root.exists());
+    }
+
+    #[test]
+    fn wheel_install_hash_mismatch() {
+        let dir = tempdir().unwrap().into_path();
+        let wheel_path = with_record(&dir, |_| "demo/__init__.py,sha256=AAAA,1\n".to_string());
+
+        assert_rejected(&dir, &wheel_path);
+    }
+
+    #[test]
+    fn wheel_install_unlisted_file() {
+        let dir = tempdir().unwrap().into_path();
+        let wheel_path = with_record(&dir, |record| {
+            record
+                .lines()
+                .filter(|it| !it.starts_with("demo/__init__.py,"))
+                .map(|it| format!("{it}\n"))
+                .collect()
+        });
+
+        assert_rejected(&dir, &wheel_path);
+    }
+
+    #[test]
+    fn wheel_install_unhashed_record_row() {
+        let dir = tempdir().unwrap().into_path();
+        let wheel_path = with_record(&dir, |record| {
+            record
+                .lines()
+                .map(|it| match it.starts_with("demo/__init__.py,") {
+                    true => "demo/__init__.py,,\n".to_string(),
+                    false => format!("{it}\n"),
+                })
+                .collect()
+        });
+
+        assert_rejected(&dir, &wheel_path);
+    }
+
+    #[test]
+    fn wheel_install_script_outside_of_scripts_dir() {
+        let dir = tempdir().unwrap().into_path();
+        let mut files = TEST_WHEEL_FILES;
+        files[5].1 = b"[console_scripts]\n../../lib/x = demo:main\n";
+        let wheel_path = write_wheel(&dir.join("demo-1.0-py3-none-any.whl"), &files);
+
+        assert_rejected(&dir, &wheel_path);
+    }
+
+    #[test]
+    fn wheel_uninstall() {
+        let dir = tempdir().unwrap().into_path();
+        let root = dir.join(".venv");
+        let scheme = test_scheme(&root);
+        let python_path = root.join("bin").join("python");
+        let dist_info =
+            install_wheel(&write_test_wheel(&dir), &scheme, &python_path, "huak", true).unwrap();
+        let pycache = scheme.purelib.join("demo").join("__pycache__");
+        std::fs::create_dir_all(&pycache).unwrap();
+        std::fs::write(pycache.join("__init__.cpython-311.pyc"), b"").unwrap();
+        std::fs::write(scheme.scripts.join("python"), b"").unwrap();
+
+        let dry_run = uninstall_dist_info(&dist_info, &scheme, &root, true).unwrap();
+
+        assert!(dist_info.join("RECORD").exists());
+
+        let removed = uninstall_dist_info(&dist_info, &scheme, &root, false).unwrap();
+
+        assert_eq!(dry_run, removed);
+        assert!(removed
+            .iter()
+            .any(|it| it.ends_with("__init__.cpython-311.pyc")));
+        assert!(!scheme.purelib.join("demo").exists());
+        assert!(!dist_info.exists());
+        assert!(!scheme.scripts.join("demo").exists());
+        assert!(scheme.scripts.join("python").exists());
+        assert!(scheme.purelib.exists());
+        assert!(scheme.headers.exists());
+    }
+
+    #[test]
+    fn wheel_uninstall_outside_root() {
+        let dir = tempdir().unwrap().into_path();
+        let root = dir.join(".venv");
+        let scheme = test_scheme(&root);
+        let dist_info = install_wheel(
+            &write_test_wheel(&dir),
+            &scheme,
+            &root.join("bin").join("python"),
+            "huak",
+            false,
+        )
+        .unwrap();
+        let outside = dir.join("outside.txt");
+        std::fs::write(&outside, b"").unwrap();
+        let mut record = std::fs::read_to_string(dist_info.join("RECORD")).unwrap();
+        record.push_str("../../../../outside.txt,,\n");
+        std::fs::write(dist_info.join("RECORD"), record).unwrap();
+
+        assert!(uninstall_dist_info(&dist_info, &scheme, &root, false).is_err());
+        assert!(outside.exists());
+        assert!(dist_info.join("RECORD").exists());
+    }
+
+    #[test]
+    fn record_csv_round_trip() {
+        let entries = vec![
+            RecordEntry {
+                path: "pkg/a,b.py".to_string(),
+                hash: Some("sha256=abc".to_string()),
+                size: Some(3),
+            },
+            RecordEntry {
+                path: "pkg-1.0.dist-info/RECORD".to_string(),
+                hash: None,
+                size: None,
+            },
+        ];
+
+        assert_eq!(parse_record(&format_record(&entries)).unwrap(), entries);
+    }
+}